@@ -27,6 +27,41 @@ struct StreamBuildParams {
     source_channels: usize,
 }
 
+/// Sends audio chunks without ever dropping samples
+///
+/// A chunk that doesn't fit in the channel is held back and sent along with
+/// the next one, so the chunks always add up to a prefix of the recording.
+/// Streaming transcription relies on this to find its offsets in the
+/// recording returned by `stop()`.
+struct ChunkSender {
+    tx: mpsc::Sender<Vec<f32>>,
+    /// Samples the channel had no room for yet
+    backlog: Vec<f32>,
+}
+
+impl ChunkSender {
+    fn new(tx: mpsc::Sender<Vec<f32>>) -> Self {
+        Self {
+            tx,
+            backlog: Vec::new(),
+        }
+    }
+
+    /// Send a chunk, or keep it for the next send if the channel is full
+    fn send(&mut self, chunk: Vec<f32>) {
+        let chunk = if self.backlog.is_empty() {
+            chunk
+        } else {
+            self.backlog.extend_from_slice(&chunk);
+            std::mem::take(&mut self.backlog)
+        };
+        // A closed channel is fine, the receiver might be gone
+        if let Err(mpsc::error::TrySendError::Full(chunk)) = self.tx.try_send(chunk) {
+            self.backlog = chunk;
+        }
+    }
+}
+
 /// cpal-based audio capture implementation
 pub struct CpalCapture {
     /// Audio configuration
@@ -231,6 +266,7 @@ where
         source_channels,
     } = params;
 
+    let mut chunks = ChunkSender::new(tx);
     let stream = device
         .build_input_stream(
             config,
@@ -259,8 +295,8 @@ where
                     guard.extend_from_slice(&resampled);
                 }
 
-                // Send chunk for streaming (ignore errors - receiver might be gone)
-                let _ = tx.try_send(resampled);
+                // Send chunk for streaming
+                chunks.send(resampled);
             },
             err_fn,
             None,
@@ -325,6 +361,22 @@ mod tests {
         assert_eq!(result.len(), 4);
     }
 
+    #[test]
+    fn test_chunk_sender_holds_back_chunks_when_full() {
+        let (tx, mut rx) = mpsc::channel(1);
+        let mut chunks = ChunkSender::new(tx);
+
+        // The second chunk finds the channel full
+        chunks.send(vec![1.0]);
+        chunks.send(vec![2.0]);
+        assert_eq!(rx.try_recv().unwrap(), vec![1.0]);
+        assert!(rx.try_recv().is_err());
+
+        // It goes out with the next one instead of being lost
+        chunks.send(vec![3.0]);
+        assert_eq!(rx.try_recv().unwrap(), vec![2.0, 3.0]);
+    }
+
     #[test]
     fn test_resample_empty() {
         let samples: Vec<f32> = vec![];
@@ -5,6 +5,8 @@
 
 pub mod cpal_capture;
 pub mod feedback;
+pub mod trim;
+pub mod vad;
 
 use crate::config::AudioConfig;
 use crate::error::AudioError;
@@ -15,13 +17,36 @@ use tokio::sync::mpsc;
 pub trait AudioCapture: Send + Sync {
     /// Start capturing audio
     /// Returns a channel receiver for audio chunks (f32 samples, mono, 16kHz)
+    /// No samples are dropped: the chunks add up to the start of the recording.
     async fn start(&mut self) -> Result<mpsc::Receiver<Vec<f32>>, AudioError>;
 
     /// Stop capturing and return all recorded samples
     async fn stop(&mut self) -> Result<Vec<f32>, AudioError>;
 }
 
+/// Compute the RMS level of a chunk of samples (0.0 = silence, 1.0 = full scale)
+pub fn rms_level(samples: &[f32]) -> f32 {
+    if samples.is_empty() {
+        return 0.0;
+    }
+    let sum_squares: f32 = samples.iter().map(|s| s * s).sum();
+    (sum_squares / samples.len() as f32).sqrt().min(1.0)
+}
+
 /// Factory function to create audio capture
 pub fn create_capture(config: &AudioConfig) -> Result<Box<dyn AudioCapture>, AudioError> {
     Ok(Box::new(cpal_capture::CpalCapture::new(config)?))
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_rms_level() {
+        assert_eq!(rms_level(&[]), 0.0);
+        assert_eq!(rms_level(&[0.0; 160]), 0.0);
+        assert!((rms_level(&[0.5, -0.5, 0.5, -0.5]) - 0.5).abs() < 1e-6);
+        assert_eq!(rms_level(&[2.0, -2.0]), 1.0);
+    }
+}
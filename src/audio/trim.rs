@@ -0,0 +1,137 @@
+//! Silence trimming
+//!
+//! Preprocessing applied to a finished recording before it is transcribed.
+//! Whisper tends to hallucinate text on silence, and every second of dead
+//! air costs transcription time, so leading and trailing silence is cut
+//! and long pauses inside the recording can be shortened.
+
+use super::rms_level;
+use crate::config::TrimConfig;
+
+/// Analysis frame size (10ms at 16kHz)
+const FRAME: usize = 160;
+
+/// Samples per millisecond at 16kHz
+const SAMPLES_PER_MS: usize = 16;
+
+/// Trim silence from a recording (f32 samples, mono, 16kHz)
+///
+/// Speech is padded by `padding_ms` on each side. Pauses longer than
+/// `max_pause_ms` are shortened to that length (0 keeps pauses intact).
+/// Returns an empty vector if no frame reaches the speech threshold.
+pub fn trim_silence(samples: &[f32], config: &TrimConfig) -> Vec<f32> {
+    let speech: Vec<bool> = samples
+        .chunks(FRAME)
+        .map(|frame| rms_level(frame) >= config.threshold)
+        .collect();
+
+    let (Some(first), Some(last)) = (
+        speech.iter().position(|&s| s),
+        speech.iter().rposition(|&s| s),
+    ) else {
+        return Vec::new();
+    };
+
+    let padding = config.padding_ms as usize * SAMPLES_PER_MS;
+    let start = (first * FRAME).saturating_sub(padding);
+    let end = ((last + 1) * FRAME + padding).min(samples.len());
+
+    let max_pause = config.max_pause_ms as usize * SAMPLES_PER_MS;
+    if max_pause == 0 {
+        return samples[start..end].to_vec();
+    }
+
+    // Copy speech as-is; for each long pause keep half of the allowed
+    // length from its start and half from its end
+    let mut trimmed = Vec::with_capacity(end - start);
+    let mut pos = start;
+    let mut frame = first;
+    while frame <= last {
+        if speech[frame] {
+            frame += 1;
+            continue;
+        }
+
+        let gap_start = frame * FRAME;
+        while !speech[frame] {
+            frame += 1;
+        }
+        let gap_end = frame * FRAME;
+
+        if gap_end - gap_start > max_pause {
+            let keep = max_pause / 2;
+            trimmed.extend_from_slice(&samples[pos..gap_start + keep]);
+            pos = gap_end - keep;
+        }
+    }
+    trimmed.extend_from_slice(&samples[pos..end]);
+
+    trimmed
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn config(padding_ms: u32, max_pause_ms: u32) -> TrimConfig {
+        TrimConfig {
+            enabled: true,
+            threshold: 0.01,
+            padding_ms,
+            max_pause_ms,
+        }
+    }
+
+    /// Build a recording from (level, milliseconds) segments
+    fn recording(segments: &[(f32, usize)]) -> Vec<f32> {
+        segments
+            .iter()
+            .flat_map(|&(level, ms)| std::iter::repeat_n(level, ms * SAMPLES_PER_MS))
+            .collect()
+    }
+
+    #[test]
+    fn test_trims_leading_and_trailing_silence() {
+        let samples = recording(&[(0.0, 1000), (0.3, 500), (0.0, 2000)]);
+        let trimmed = trim_silence(&samples, &config(0, 0));
+        assert_eq!(trimmed.len(), 500 * SAMPLES_PER_MS);
+        assert!(trimmed.iter().all(|&s| s == 0.3));
+    }
+
+    #[test]
+    fn test_keeps_padding_around_speech() {
+        let samples = recording(&[(0.0, 1000), (0.3, 500), (0.0, 1000)]);
+        let trimmed = trim_silence(&samples, &config(200, 0));
+        assert_eq!(trimmed.len(), 900 * SAMPLES_PER_MS);
+    }
+
+    #[test]
+    fn test_padding_is_clamped_to_recording() {
+        let samples = recording(&[(0.0, 50), (0.3, 500), (0.0, 50)]);
+        let trimmed = trim_silence(&samples, &config(200, 0));
+        assert_eq!(trimmed.len(), samples.len());
+    }
+
+    #[test]
+    fn test_no_speech_returns_empty() {
+        let samples = recording(&[(0.001, 2000)]);
+        assert!(trim_silence(&samples, &config(200, 0)).is_empty());
+        assert!(trim_silence(&[], &config(200, 0)).is_empty());
+    }
+
+    #[test]
+    fn test_shortens_long_pauses() {
+        let samples = recording(&[(0.3, 500), (0.0, 3000), (0.3, 500)]);
+        let trimmed = trim_silence(&samples, &config(0, 600));
+        assert_eq!(trimmed.len(), 1600 * SAMPLES_PER_MS);
+        assert_eq!(trimmed[0], 0.3);
+        assert_eq!(trimmed[trimmed.len() - 1], 0.3);
+    }
+
+    #[test]
+    fn test_keeps_short_pauses() {
+        let samples = recording(&[(0.3, 500), (0.0, 400), (0.3, 500)]);
+        let trimmed = trim_silence(&samples, &config(0, 600));
+        assert_eq!(trimmed.len(), samples.len());
+    }
+}
@@ -0,0 +1,115 @@
+//! Voice activity detection
+//!
+//! Energy-based detector run on the chunk stream from `AudioCapture::start`.
+//! It reports when the speaker has gone quiet for long enough that the
+//! recording can end on its own (hands-free dictation in toggle mode).
+
+use super::rms_level;
+use crate::config::VadConfig;
+
+/// Sample rate of the chunk stream
+const SAMPLE_RATE: u64 = 16000;
+
+/// Speech needed before trailing silence counts (ignores clicks and pops)
+const MIN_SPEECH_MS: u64 = 100;
+
+/// Tracks speech and trailing silence in a recording
+pub struct VoiceActivityDetector {
+    threshold: f32,
+    silence_limit: u64,
+    min_speech: u64,
+    speech_samples: u64,
+    silence_samples: u64,
+}
+
+impl VoiceActivityDetector {
+    /// Create a detector from configuration
+    pub fn new(config: &VadConfig) -> Self {
+        Self {
+            threshold: config.threshold,
+            silence_limit: config.silence_ms as u64 * SAMPLE_RATE / 1000,
+            min_speech: MIN_SPEECH_MS * SAMPLE_RATE / 1000,
+            speech_samples: 0,
+            silence_samples: 0,
+        }
+    }
+
+    /// Feed a chunk of samples
+    /// Returns true once speech has been heard and followed by enough silence
+    pub fn process(&mut self, chunk: &[f32]) -> bool {
+        if chunk.is_empty() {
+            return false;
+        }
+
+        if rms_level(chunk) >= self.threshold {
+            self.speech_samples += chunk.len() as u64;
+            self.silence_samples = 0;
+        } else if self.heard_speech() {
+            self.silence_samples += chunk.len() as u64;
+        }
+
+        self.heard_speech() && self.silence_samples >= self.silence_limit
+    }
+
+    /// Whether enough speech has been heard for silence to end the recording
+    fn heard_speech(&self) -> bool {
+        self.speech_samples >= self.min_speech
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// 10ms chunks, like a typical capture callback
+    const CHUNK: usize = 160;
+
+    fn detector(silence_ms: u32) -> VoiceActivityDetector {
+        VoiceActivityDetector::new(&VadConfig {
+            enabled: true,
+            threshold: 0.01,
+            silence_ms,
+        })
+    }
+
+    /// Feed `ms` milliseconds of a constant level, returning whether it triggered
+    fn feed(vad: &mut VoiceActivityDetector, level: f32, ms: usize) -> bool {
+        let chunk = vec![level; CHUNK];
+        let mut triggered = false;
+        for _ in 0..ms / 10 {
+            triggered |= vad.process(&chunk);
+        }
+        triggered
+    }
+
+    #[test]
+    fn test_silence_before_speech_does_not_stop() {
+        let mut vad = detector(500);
+        assert!(!feed(&mut vad, 0.0, 3000));
+    }
+
+    #[test]
+    fn test_trailing_silence_stops() {
+        let mut vad = detector(500);
+        assert!(!feed(&mut vad, 0.2, 1000));
+        assert!(!feed(&mut vad, 0.0, 400));
+        assert!(feed(&mut vad, 0.0, 100));
+    }
+
+    #[test]
+    fn test_speech_resets_silence() {
+        let mut vad = detector(500);
+        feed(&mut vad, 0.2, 500);
+        assert!(!feed(&mut vad, 0.0, 400));
+        assert!(!feed(&mut vad, 0.2, 100));
+        assert!(!feed(&mut vad, 0.0, 400));
+        assert!(feed(&mut vad, 0.0, 100));
+    }
+
+    #[test]
+    fn test_short_click_is_not_speech() {
+        let mut vad = detector(500);
+        assert!(!feed(&mut vad, 0.5, 20));
+        assert!(!feed(&mut vad, 0.0, 2000));
+    }
+}
@@ -69,6 +69,7 @@ pub struct Cli {
     pub toggle: bool,
 
     /// Delay before wtype starts typing (ms), helps prevent first character drop
+    /// (wtype fallback only)
     #[arg(long, value_name = "MS")]
     pub wtype_delay: Option<u32>,
 
@@ -139,7 +140,7 @@ pub enum Commands {
         #[arg(long)]
         follow: bool,
 
-        /// Output format: "text" (default) or "json" (for Waybar)
+        /// Output format: "text" (default), "json" (for Waybar), or "events" (raw daemon events as JSON lines)
         #[arg(long, default_value = "text")]
         format: String,
 
@@ -157,6 +158,27 @@ pub enum Commands {
         #[command(subcommand)]
         action: RecordAction,
     },
+
+    /// Output the last transcription again (e.g. after it went to the wrong window)
+    Replay {
+        /// Override output mode to simulate keyboard typing
+        #[arg(long = "type", group = "output_mode")]
+        type_mode: bool,
+
+        /// Override output mode to clipboard only
+        #[arg(long, group = "output_mode")]
+        clipboard: bool,
+
+        /// Override output mode to paste (clipboard + Ctrl+V)
+        #[arg(long, group = "output_mode")]
+        paste: bool,
+    },
+
+    /// Browse and reuse past transcriptions
+    History {
+        #[command(subcommand)]
+        action: Option<HistoryAction>,
+    },
 }
 
 /// Output mode override for record commands
@@ -167,9 +189,24 @@ pub enum OutputModeOverride {
     Paste,
 }
 
+impl OutputModeOverride {
+    /// Resolve the mutually exclusive --type/--clipboard/--paste flags
+    pub fn from_flags(type_mode: bool, clipboard: bool, paste: bool) -> Option<Self> {
+        if type_mode {
+            Some(OutputModeOverride::Type)
+        } else if clipboard {
+            Some(OutputModeOverride::Clipboard)
+        } else if paste {
+            Some(OutputModeOverride::Paste)
+        } else {
+            None
+        }
+    }
+}
+
 #[derive(Subcommand)]
 pub enum RecordAction {
-    /// Start recording (send SIGUSR1 to daemon)
+    /// Start recording
     Start {
         /// Override output mode to simulate keyboard typing
         #[arg(long = "type", group = "output_mode")]
@@ -182,8 +219,17 @@ pub enum RecordAction {
         /// Override output mode to paste (clipboard + Ctrl+V)
         #[arg(long, group = "output_mode")]
         paste: bool,
+
+        /// Wait until the recording is transcribed and print the text to
+        /// stdout instead of outputting it (stop it with `voxtype record stop`)
+        #[arg(long, group = "output_mode")]
+        wait: bool,
+
+        /// Use a named profile from [profiles] for this recording
+        #[arg(long, value_name = "NAME")]
+        profile: Option<String>,
     },
-    /// Stop recording and transcribe (send SIGUSR2 to daemon)
+    /// Stop recording and transcribe
     Stop {
         /// Override output mode to simulate keyboard typing
         #[arg(long = "type", group = "output_mode")]
@@ -210,9 +256,15 @@ pub enum RecordAction {
         /// Override output mode to paste (clipboard + Ctrl+V)
         #[arg(long, group = "output_mode")]
         paste: bool,
+
+        /// Use a named profile from [profiles] if this starts a recording
+        #[arg(long, value_name = "NAME")]
+        profile: Option<String>,
     },
     /// Cancel current recording or transcription (discard without output)
     Cancel,
+    /// Erase the last typed transcription (sends backspaces)
+    Undo,
 }
 
 impl RecordAction {
@@ -223,6 +275,7 @@ impl RecordAction {
                 type_mode,
                 clipboard,
                 paste,
+                ..
             } => (*type_mode, *clipboard, *paste),
             RecordAction::Stop {
                 type_mode,
@@ -233,22 +286,66 @@ impl RecordAction {
                 type_mode,
                 clipboard,
                 paste,
+                ..
             } => (*type_mode, *clipboard, *paste),
-            RecordAction::Cancel => return None,
+            RecordAction::Cancel | RecordAction::Undo => return None,
         };
 
-        if type_mode {
-            Some(OutputModeOverride::Type)
-        } else if clipboard {
-            Some(OutputModeOverride::Clipboard)
-        } else if paste {
-            Some(OutputModeOverride::Paste)
-        } else {
-            None
+        OutputModeOverride::from_flags(type_mode, clipboard, paste)
+    }
+
+    /// Whether the text is to be printed (`record start --wait`)
+    pub fn waits(&self) -> bool {
+        matches!(self, RecordAction::Start { wait: true, .. })
+    }
+
+    /// Profile selected with --profile
+    pub fn profile(&self) -> Option<&str> {
+        match self {
+            RecordAction::Start { profile, .. } | RecordAction::Toggle { profile, .. } => {
+                profile.as_deref()
+            }
+            _ => None,
         }
     }
 }
 
+#[derive(Subcommand)]
+pub enum HistoryAction {
+    /// List recent transcriptions (default)
+    List {
+        /// Number of entries to show
+        #[arg(short = 'n', long, default_value = "20")]
+        limit: usize,
+    },
+    /// Search transcriptions (case-insensitive)
+    Search {
+        /// Text to search for
+        query: String,
+
+        /// Number of matches to show
+        #[arg(short = 'n', long, default_value = "20")]
+        limit: usize,
+    },
+    /// Show a transcription in full (default: most recent)
+    Show {
+        /// Entry number from `voxtype history list`
+        id: Option<u64>,
+    },
+    /// Copy a transcription to the clipboard (default: most recent)
+    Copy {
+        /// Entry number from `voxtype history list`
+        id: Option<u64>,
+    },
+    /// Type a transcription at the cursor (default: most recent)
+    Type {
+        /// Entry number from `voxtype history list`
+        id: Option<u64>,
+    },
+    /// Delete all stored transcriptions
+    Clear,
+}
+
 #[derive(Subcommand)]
 pub enum SetupAction {
     /// Check system configuration and dependencies
@@ -590,6 +687,17 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_record_undo() {
+        let cli = Cli::parse_from(["voxtype", "record", "undo"]);
+        assert!(matches!(
+            cli.command,
+            Some(Commands::Record {
+                action: RecordAction::Undo
+            })
+        ));
+    }
+
     #[test]
     fn test_record_start_no_override() {
         let cli = Cli::parse_from(["voxtype", "record", "start"]);
@@ -643,6 +751,47 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_record_start_wait() {
+        let cli = Cli::parse_from(["voxtype", "record", "start", "--wait"]);
+        match cli.command {
+            Some(Commands::Record { action }) => {
+                assert!(action.waits());
+                assert_eq!(action.output_mode_override(), None);
+            }
+            _ => panic!("Expected Record command"),
+        }
+
+        // The text goes to stdout, so no other output mode
+        let result = Cli::try_parse_from(["voxtype", "record", "start", "--wait", "--paste"]);
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn test_record_start_profile() {
+        let cli = Cli::parse_from([
+            "voxtype",
+            "record",
+            "start",
+            "--profile",
+            "email",
+            "--paste",
+        ]);
+        match cli.command {
+            Some(Commands::Record { action }) => {
+                assert_eq!(action.profile(), Some("email"));
+                assert_eq!(
+                    action.output_mode_override(),
+                    Some(OutputModeOverride::Paste)
+                );
+            }
+            _ => panic!("Expected Record command"),
+        }
+
+        // Profiles are chosen when recording starts
+        assert!(Cli::try_parse_from(["voxtype", "record", "stop", "--profile", "email"]).is_err());
+    }
+
     #[test]
     fn test_record_stop_paste_override() {
         let cli = Cli::parse_from(["voxtype", "record", "stop", "--paste"]);
@@ -670,4 +819,61 @@ mod tests {
             _ => panic!("Expected Record command"),
         }
     }
+
+    #[test]
+    fn test_replay_clipboard_override() {
+        let cli = Cli::parse_from(["voxtype", "replay", "--clipboard"]);
+        match cli.command {
+            Some(Commands::Replay {
+                type_mode,
+                clipboard,
+                paste,
+            }) => {
+                assert_eq!(
+                    OutputModeOverride::from_flags(type_mode, clipboard, paste),
+                    Some(OutputModeOverride::Clipboard)
+                );
+            }
+            _ => panic!("Expected Replay command"),
+        }
+    }
+
+    #[test]
+    fn test_replay_rejects_conflicting_modes() {
+        assert!(Cli::try_parse_from(["voxtype", "replay", "--type", "--paste"]).is_err());
+    }
+
+    #[test]
+    fn test_history_defaults_to_list() {
+        let cli = Cli::parse_from(["voxtype", "history"]);
+        assert!(matches!(
+            cli.command,
+            Some(Commands::History { action: None })
+        ));
+    }
+
+    #[test]
+    fn test_history_search() {
+        let cli = Cli::parse_from(["voxtype", "history", "search", "meeting", "-n", "5"]);
+        match cli.command {
+            Some(Commands::History {
+                action: Some(HistoryAction::Search { query, limit }),
+            }) => {
+                assert_eq!(query, "meeting");
+                assert_eq!(limit, 5);
+            }
+            _ => panic!("Expected History Search command"),
+        }
+    }
+
+    #[test]
+    fn test_history_copy_id() {
+        let cli = Cli::parse_from(["voxtype", "history", "copy", "12"]);
+        assert!(matches!(
+            cli.command,
+            Some(Commands::History {
+                action: Some(HistoryAction::Copy { id: Some(12) })
+            })
+        ));
+    }
 }
@@ -21,7 +21,7 @@ pub const DEFAULT_CONFIG: &str = r#"# Voxtype Configuration
 # Use "auto" for default location ($XDG_RUNTIME_DIR/voxtype/state),
 # a custom path, or "disabled" to turn off. The daemon writes state
 # ("idle", "recording", "transcribing") to this file whenever it changes.
-# Required for `voxtype record toggle` and `voxtype status` commands.
+# Required for the `voxtype status` command.
 state_file = "auto"
 
 [hotkey]
@@ -34,16 +34,61 @@ key = "SCROLLLOCK"
 # Example: modifiers = ["LEFTCTRL", "LEFTALT"]
 modifiers = []
 
-# Activation mode: "push_to_talk" or "toggle"
+# Activation mode: "push_to_talk", "toggle" or "hybrid"
 # - push_to_talk: Hold hotkey to record, release to transcribe (default)
 # - toggle: Press hotkey once to start recording, press again to stop
+# - hybrid: Hold to talk, or tap to start recording and tap again to stop
 # mode = "push_to_talk"
 
+# Hybrid mode: presses shorter than this (milliseconds) count as taps
+# tap_threshold_ms = 300
+
+# Gesture timing for hotkey bindings (milliseconds)
+# double_tap_window_ms: longest time between the two presses of a double tap
+# chord_window_ms: longest time between the first and last key of a chord
+# double_tap_window_ms = 300
+# chord_window_ms = 100
+
 # Enable built-in hotkey detection (default: true)
 # Set to false when using compositor keybindings (Hyprland, Sway) instead
 # When disabled, use `voxtype record start/stop/toggle` to control recording
 # enabled = true
 
+# Additional hotkeys, each with its own key, modifiers, mode and action
+# action: "record" (default), "cancel" or "undo"
+# Record bindings may set output_mode, language and profile for their recordings
+# Bindings can also trigger on a double tap (double_tap = true) or on a chord
+# of several keys pressed together (chord = ["..."], in addition to key)
+# [[hotkey.bindings]]
+# key = "F13"
+# modifiers = ["LEFTCTRL"]
+# mode = "toggle"
+# output_mode = "clipboard"
+# language = "de"
+#
+# [[hotkey.bindings]]
+# key = "RIGHTCTRL"
+# double_tap = true
+
+# Input devices to read hotkeys from (default: any device that can send
+# the configured keys, including mice and foot pedals)
+# Mouse and joystick buttons use BTN_* names, e.g. key = "BTN_SIDE"
+# Each entry may set name (part of the device name), vendor_id, product_id
+# and path; all options given must match. Use `evtest` to find them.
+# [[hotkey.devices]]
+# name = "FootSwitch"
+# vendor_id = 0x0c45
+# product_id = 0x7403
+
+# Keep hotkey presses away from applications:
+# - "none": applications see the hotkey too (default)
+# - "exclusive": grab the hotkey devices, so none of their input reaches
+#   applications (for a pedal or keypad used only for voxtype; needs
+#   [[hotkey.devices]] so the keyboard isn't grabbed)
+# - "passthrough": grab the hotkey devices and pass everything except the
+#   hotkey presses on through a virtual device (needs write access to /dev/uinput)
+# grab = "none"
+
 [audio]
 # Audio input device ("default" uses system default)
 # List devices with: pactl list sources short
@@ -68,6 +113,32 @@ max_duration_secs = 60
 # Volume level (0.0 to 1.0)
 # volume = 0.7
 
+# [audio.vad]
+# Stop recording automatically after trailing silence (toggle mode only,
+# also for `voxtype record start/toggle`; push-to-talk ends on key release)
+# enabled = true
+#
+# RMS level (0.0 to 1.0) above which audio counts as speech
+# Raise this in noisy environments
+# threshold = 0.01
+#
+# Milliseconds of silence after speech before recording stops
+# silence_ms = 1500
+
+# [audio.trim]
+# Cut silence from recordings before transcription (reduces Whisper
+# hallucinations on silence and speeds up transcription)
+# enabled = true
+#
+# RMS level (0.0 to 1.0) above which audio counts as speech
+# threshold = 0.01
+#
+# Silence kept before and after speech (milliseconds)
+# padding_ms = 200
+#
+# Shorten pauses inside the recording to this length (0 = keep pauses)
+# max_pause_ms = 0
+
 [whisper]
 # Transcription backend: "local" or "remote"
 # - local: Use whisper.cpp locally (default)
@@ -92,6 +163,11 @@ translate = false
 # Number of CPU threads for inference (omit for auto-detection)
 # threads = 4
 
+# Transcribe while recording (local backend only). Partial results are
+# published to `voxtype status --follow --format events` subscribers, and
+# long dictations finish faster because most audio is already decoded.
+# streaming = false
+
 # --- Remote backend settings (used when backend = "remote") ---
 #
 # Remote server endpoint URL (required for remote backend)
@@ -110,9 +186,15 @@ translate = false
 # remote_timeout_secs = 30
 
 [output]
-# Primary output mode: "type" or "clipboard"
+# Primary output mode: "type", "clipboard", "paste", "input_method",
+# "file", "fifo", "command" or "stdout"
 # - type: Simulates keyboard input at cursor position (requires ydotool)
 # - clipboard: Copies text to clipboard (requires wl-copy)
+# - input_method: Inserts text as a Wayland input method (any layout, no
+#   clipboard), typing into apps without text-input-v3 support
+# - file, fifo, command: Hands the text to a program (see [output.file],
+#   [output.fifo] and [output.command] below), no display server needed
+# - stdout: Prints the text from a waiting `voxtype record start --wait`
 mode = "type"
 
 # Fall back to clipboard if typing fails
@@ -122,6 +204,26 @@ fallback_to_clipboard = true
 # 0 = fastest possible, increase if characters are dropped
 type_delay_ms = 0
 
+# Keyboard layout for the built-in uinput typing (used when wtype isn't
+# available), e.g. "de" or "de(nodeadkeys)". Detected from the session
+# (XKB_DEFAULT_LAYOUT, Hyprland, setxkbmap, localectl) if not set
+# keyboard_layout = "us"
+
+# Paste mode: put back what was in the clipboard once the text is pasted
+# (every format it held), after the delay in milliseconds
+# restore_clipboard = false
+# restore_clipboard_delay_ms = 300
+
+# Paste mode: selection to paste through, "clipboard" or "primary"
+# paste_selection = "clipboard"
+
+# Custom fallback chain, tried in order instead of the built-in chain of
+# `mode` (fallback_to_clipboard then doesn't apply, list "clipboard" instead)
+# Backends: virtual_keyboard, wtype, xtest, uinput, ydotool, clipboard,
+# paste, input_method, file, fifo, command, stdout. Tables set options for one backend only
+# (type_delay_ms, wtype_delay_ms, paste_keys, keyboard_layout, auto_submit)
+# chain = ["uinput", { backend = "ydotool", type_delay_ms = 5 }, "clipboard"]
+
 # Automatically submit (send Enter key) after outputting transcribed text
 # Useful for chat applications, command lines, or forms where you want
 # to auto-submit after dictation
@@ -144,6 +246,24 @@ type_delay_ms = 0
 # command = "ollama run llama3.2:1b 'Clean up this dictation. Fix grammar, remove filler words. Output only the cleaned text:'"
 # timeout_ms = 30000  # 30 second timeout (generous for LLM)
 
+# Output sinks for scripts (mode = "file", "fifo" or "command")
+#
+# Append each transcription to a file. strftime patterns (%Y, %H:%M, ...)
+# are filled in in both the path and the template, {text} in the template
+# [output.file]
+# path = "~/notes/dictation-%Y-%m-%d.md"
+# template = "[%H:%M] {text}\n"
+#
+# Write each transcription to a named pipe (created if missing). Fails
+# unless a reader has the pipe open, e.g. `cat ~/voxtype.fifo`
+# [output.fifo]
+# path = "~/voxtype.fifo"
+#
+# Pipe each transcription into a shell command's stdin
+# [output.command]
+# command = "notify-send Voxtype \"$(cat)\""
+# timeout_ms = 10000
+
 [output.notification]
 # Show notification when recording starts (hotkey pressed)
 on_recording_start = false
@@ -162,6 +282,10 @@ on_transcription = true
 #
 # Custom word replacements (case-insensitive)
 # replacements = { "vox type" = "voxtype" }
+#
+# Saying one of these phrases on its own erases the last dictation
+# instead of typing anything (same as `voxtype record undo`)
+# undo_phrases = ["scratch that"]
 
 # [status]
 # Status display icons for Waybar/tray integrations
@@ -187,6 +311,48 @@ on_transcription = true
 # recording = "🎤"
 # transcribing = "⏳"
 # stopped = ""
+
+# [history]
+# Transcription history, browsable with `voxtype history`
+#
+# Store transcriptions (disable for sensitive dictation)
+# enabled = true
+#
+# Maximum number of entries kept
+# max_entries = 1000
+#
+# Drop entries older than this many days (0 = keep forever)
+# max_age_days = 30
+
+# [profiles.<name>]
+# Named sets of overrides, selected per recording with
+# `voxtype record start --profile <name>`. A profile can override any
+# [whisper], [text] and [output] setting; everything else is inherited.
+#
+# [profiles.german.whisper]
+# model = "base"
+# language = "de"
+#
+# [profiles.email.post_process]
+# command = "ollama run llama3.2:1b 'Rewrite as a polite email:'"
+# timeout_ms = 60000
+
+# [[app_rules]]
+# Output settings for the application with the keyboard focus, found with
+# hyprctl, swaymsg, niri msg or X11 when the text is ready. `app` is a
+# case-insensitive regex matched against the whole app_id (Wayland) or
+# WM_CLASS class (X11); the first matching rule applies. A rule may set
+# mode, paste_keys, auto_submit and type_delay_ms, and a profile whose
+# text processing and [output] settings to use.
+#
+# [[app_rules]]
+# app = "kitty|Alacritty|foot|org.wezfurlong.wezterm"
+# mode = "paste"
+# paste_keys = "ctrl+shift+v"
+#
+# [[app_rules]]
+# app = "Slack|discord|signal"
+# auto_submit = true
 "#;
 
 /// Hotkey activation mode
@@ -198,6 +364,23 @@ pub enum ActivationMode {
     PushToTalk,
     /// Press once to start recording, press again to stop
     Toggle,
+    /// Hold to record like push-to-talk; a short tap keeps recording
+    /// until the next tap
+    Hybrid,
+}
+
+/// How hotkey devices are shared with applications
+#[derive(Debug, Clone, Copy, Deserialize, Serialize, PartialEq, Eq, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum GrabMode {
+    /// Applications also see the hotkey (default)
+    #[default]
+    None,
+    /// Grab the hotkey devices; applications see none of their input
+    Exclusive,
+    /// Grab the hotkey devices and re-emit everything except hotkey presses
+    /// through a uinput device
+    Passthrough,
 }
 
 /// Root configuration structure
@@ -216,6 +399,18 @@ pub struct Config {
     #[serde(default)]
     pub status: StatusConfig,
 
+    /// Transcription history configuration
+    #[serde(default)]
+    pub history: HistoryConfig,
+
+    /// Named profiles overriding parts of the configuration per recording
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+    pub profiles: HashMap<String, ProfileConfig>,
+
+    /// Output settings per focused application, first match wins
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub app_rules: Vec<AppRule>,
+
     /// Optional path to state file for external integrations (e.g., Waybar)
     /// When set, the daemon writes current state ("idle", "recording", "transcribing")
     /// to this file whenever state changes.
@@ -237,10 +432,24 @@ pub struct HotkeyConfig {
     #[serde(default)]
     pub modifiers: Vec<String>,
 
-    /// Activation mode: push_to_talk (hold to record) or toggle (press to start/stop)
+    /// Activation mode: push_to_talk (hold to record), toggle (press to start/stop)
+    /// or hybrid (hold to record, or tap to start/stop)
     #[serde(default)]
     pub mode: ActivationMode,
 
+    /// Hybrid mode: presses shorter than this (milliseconds) are taps
+    /// that keep recording until the next tap
+    #[serde(default = "default_tap_threshold_ms")]
+    pub tap_threshold_ms: u32,
+
+    /// Longest time between the two presses of a double tap (milliseconds)
+    #[serde(default = "default_double_tap_window_ms")]
+    pub double_tap_window_ms: u32,
+
+    /// Longest time between the first and last key of a chord going down (milliseconds)
+    #[serde(default = "default_chord_window_ms")]
+    pub chord_window_ms: u32,
+
     /// Enable built-in hotkey detection (default: true)
     /// Set to false when using compositor keybindings (Hyprland, Sway) instead
     /// When disabled, use `voxtype record start/stop/toggle` to control recording
@@ -252,6 +461,149 @@ pub struct HotkeyConfig {
     /// Examples: "ESC", "BACKSPACE", "F12"
     #[serde(default)]
     pub cancel_key: Option<String>,
+
+    /// Optional undo key (evdev KEY_* constant name, without KEY_ prefix)
+    /// When pressed, erases the last typed transcription
+    /// Examples: "F11", "PAUSE"
+    #[serde(default)]
+    pub undo_key: Option<String>,
+
+    /// Additional hotkeys bound to their own actions
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub bindings: Vec<HotkeyBinding>,
+
+    /// Input devices to read hotkeys from
+    /// Empty: any device that can send one of the configured keys
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub devices: Vec<DeviceFilter>,
+
+    /// Keep hotkey presses away from applications by grabbing the devices
+    #[serde(default)]
+    pub grab: GrabMode,
+}
+
+/// Input device selection for hotkeys (`[[hotkey.devices]]`)
+/// All options that are set must match
+#[derive(Debug, Clone, Default, Deserialize, Serialize, PartialEq)]
+#[serde(deny_unknown_fields)]
+pub struct DeviceFilter {
+    /// Part of the device name, case-insensitive (as shown by evtest)
+    #[serde(default)]
+    pub name: Option<String>,
+
+    /// USB vendor ID, e.g. 0x0c45
+    #[serde(default)]
+    pub vendor_id: Option<u16>,
+
+    /// USB product ID, e.g. 0x7403
+    #[serde(default)]
+    pub product_id: Option<u16>,
+
+    /// Device path, e.g. "/dev/input/by-id/usb-PCsensor_FootSwitch-event-kbd"
+    #[serde(default)]
+    pub path: Option<PathBuf>,
+}
+
+impl HotkeyConfig {
+    /// All hotkeys the listener watches for, in a fixed order:
+    /// the main key, the cancel and undo keys (if set), then `bindings`
+    /// Hotkey events refer to bindings by their index in this list.
+    pub fn all_bindings(&self) -> Vec<HotkeyBinding> {
+        let single = |key: &String, action| HotkeyBinding {
+            key: key.clone(),
+            modifiers: Vec::new(),
+            chord: Vec::new(),
+            double_tap: false,
+            mode: None,
+            action,
+            output_mode: None,
+            language: None,
+            profile: None,
+        };
+
+        let mut bindings = vec![HotkeyBinding {
+            modifiers: self.modifiers.clone(),
+            ..single(&self.key, HotkeyAction::Record)
+        }];
+        bindings.extend(
+            self.cancel_key
+                .iter()
+                .map(|k| single(k, HotkeyAction::Cancel)),
+        );
+        bindings.extend(self.undo_key.iter().map(|k| single(k, HotkeyAction::Undo)));
+        bindings.extend(self.bindings.iter().cloned());
+        bindings
+    }
+}
+
+/// What a hotkey binding does
+#[derive(Debug, Clone, Copy, Deserialize, Serialize, PartialEq, Eq, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum HotkeyAction {
+    /// Record and transcribe (default)
+    #[default]
+    Record,
+    /// Cancel the current recording or transcription
+    Cancel,
+    /// Erase the last typed transcription
+    Undo,
+}
+
+/// An additional hotkey (`[[hotkey.bindings]]`)
+#[derive(Debug, Clone, Deserialize, Serialize, PartialEq)]
+#[serde(deny_unknown_fields)]
+pub struct HotkeyBinding {
+    /// Key name (evdev KEY_* constant name, without the KEY_ prefix)
+    pub key: String,
+
+    /// Modifier keys that must also be held
+    #[serde(default)]
+    pub modifiers: Vec<String>,
+
+    /// Other keys that must be pressed together with `key` (in any order)
+    #[serde(default)]
+    pub chord: Vec<String>,
+
+    /// Trigger on a quick double tap of `key` instead of a single press
+    #[serde(default)]
+    pub double_tap: bool,
+
+    /// Activation mode for recording (default: the [hotkey] mode)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub mode: Option<ActivationMode>,
+
+    /// What the hotkey does
+    #[serde(default)]
+    pub action: HotkeyAction,
+
+    /// Output mode for recordings started by this hotkey
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub output_mode: Option<OutputMode>,
+
+    /// Transcription language for recordings started by this hotkey
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub language: Option<String>,
+
+    /// Profile for recordings started by this hotkey
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub profile: Option<String>,
+}
+
+impl std::fmt::Display for HotkeyBinding {
+    /// Key combination, e.g. "LEFTCTRL+F13" or "double-tap RIGHTCTRL"
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if self.double_tap {
+            write!(f, "double-tap ")?;
+        }
+        for modifier in &self.modifiers {
+            write!(f, "{}+", modifier)?;
+        }
+        write!(f, "{}", self.key)?;
+        for key in &self.chord {
+            write!(f, "+{}", key)?;
+        }
+        Ok(())
+    }
 }
 
 /// Audio capture configuration
@@ -273,6 +625,85 @@ pub struct AudioConfig {
     /// Audio feedback settings
     #[serde(default)]
     pub feedback: AudioFeedbackConfig,
+
+    /// Voice activity detection (auto-stop on silence)
+    #[serde(default)]
+    pub vad: VadConfig,
+
+    /// Silence trimming before transcription
+    #[serde(default)]
+    pub trim: TrimConfig,
+}
+
+/// Voice activity detection configuration
+/// Ends toggle-mode recordings automatically after trailing silence
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct VadConfig {
+    /// Enable auto-stop on silence
+    #[serde(default)]
+    pub enabled: bool,
+
+    /// RMS level (0.0 to 1.0) above which audio counts as speech
+    #[serde(default = "default_vad_threshold")]
+    pub threshold: f32,
+
+    /// How long the speaker must be silent before recording stops (milliseconds)
+    #[serde(default = "default_vad_silence_ms")]
+    pub silence_ms: u32,
+}
+
+fn default_vad_threshold() -> f32 {
+    0.01
+}
+
+fn default_vad_silence_ms() -> u32 {
+    1500
+}
+
+/// Silence trimming configuration
+/// Removes dead air from recordings before they are transcribed
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct TrimConfig {
+    /// Enable silence trimming
+    #[serde(default)]
+    pub enabled: bool,
+
+    /// RMS level (0.0 to 1.0) above which audio counts as speech
+    #[serde(default = "default_vad_threshold")]
+    pub threshold: f32,
+
+    /// Silence kept before and after speech (milliseconds)
+    #[serde(default = "default_trim_padding_ms")]
+    pub padding_ms: u32,
+
+    /// Shorten pauses inside the recording to this length (milliseconds, 0 = keep)
+    #[serde(default)]
+    pub max_pause_ms: u32,
+}
+
+fn default_trim_padding_ms() -> u32 {
+    200
+}
+
+impl Default for TrimConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            threshold: default_vad_threshold(),
+            padding_ms: default_trim_padding_ms(),
+            max_pause_ms: 0,
+        }
+    }
+}
+
+impl Default for VadConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            threshold: default_vad_threshold(),
+            silence_ms: default_vad_silence_ms(),
+        }
+    }
 }
 
 /// Audio feedback configuration for sound cues
@@ -295,6 +726,18 @@ fn default_hotkey_key() -> String {
     "SCROLLLOCK".to_string()
 }
 
+fn default_tap_threshold_ms() -> u32 {
+    300
+}
+
+fn default_double_tap_window_ms() -> u32 {
+    300
+}
+
+fn default_chord_window_ms() -> u32 {
+    100
+}
+
 fn default_sound_theme() -> String {
     "default".to_string()
 }
@@ -346,6 +789,139 @@ impl Default for StatusConfig {
     }
 }
 
+/// Transcription history configuration
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct HistoryConfig {
+    /// Store transcriptions in the history file
+    #[serde(default = "default_true")]
+    pub enabled: bool,
+
+    /// Maximum number of entries kept (oldest are dropped first)
+    #[serde(default = "default_history_max_entries")]
+    pub max_entries: usize,
+
+    /// Drop entries older than this many days (0 = keep forever)
+    #[serde(default = "default_history_max_age_days")]
+    pub max_age_days: u32,
+}
+
+fn default_history_max_entries() -> usize {
+    1000
+}
+
+fn default_history_max_age_days() -> u32 {
+    30
+}
+
+impl Default for HistoryConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            max_entries: default_history_max_entries(),
+            max_age_days: default_history_max_age_days(),
+        }
+    }
+}
+
+/// A named profile: settings that replace the base configuration for
+/// recordings started with `--profile <name>`
+///
+/// Each section holds only the keys to change, e.g. `[profiles.german.whisper]`
+/// with `language = "de"`. Unknown sections are rejected.
+#[derive(Debug, Clone, Default, Deserialize, Serialize)]
+#[serde(deny_unknown_fields)]
+pub struct ProfileConfig {
+    /// Overrides for [whisper]
+    #[serde(default, skip_serializing_if = "toml::Table::is_empty")]
+    pub whisper: toml::Table,
+
+    /// Overrides for [text]
+    #[serde(default, skip_serializing_if = "toml::Table::is_empty")]
+    pub text: toml::Table,
+
+    /// Overrides for [output]
+    #[serde(default, skip_serializing_if = "toml::Table::is_empty")]
+    pub output: toml::Table,
+
+    /// Overrides for [output.post_process], or `false` to turn it off
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub post_process: Option<PostProcessOverride>,
+}
+
+/// Post-processing setting of a profile
+#[derive(Debug, Clone, Deserialize, Serialize)]
+#[serde(untagged)]
+pub enum PostProcessOverride {
+    /// `post_process = false` disables post-processing
+    Enabled(bool),
+    /// Settings merged over the base [output.post_process]
+    Settings(toml::Table),
+}
+
+/// Output settings for the application with the keyboard focus
+#[derive(Debug, Clone, Default, Deserialize, Serialize)]
+#[serde(deny_unknown_fields)]
+pub struct AppRule {
+    /// Regex for the whole app_id (Wayland) or WM_CLASS class (X11),
+    /// case-insensitive
+    pub app: String,
+
+    /// Output mode for the app (uses the built-in chain of the mode)
+    #[serde(default)]
+    pub mode: Option<OutputMode>,
+
+    /// Keystroke for paste mode
+    #[serde(default)]
+    pub paste_keys: Option<String>,
+
+    /// Send Enter after the text
+    #[serde(default)]
+    pub auto_submit: Option<bool>,
+
+    /// Delay between typed characters (ms)
+    #[serde(default)]
+    pub type_delay_ms: Option<u32>,
+
+    /// Profile whose text processing and [output] settings apply
+    #[serde(default)]
+    pub profile: Option<String>,
+
+    /// `app` compiled by [`AppRule::compile`]
+    #[serde(skip)]
+    pub(crate) pattern: Option<regex::Regex>,
+}
+
+impl AppRule {
+    /// Compile the `app` regex, anchored and case-insensitive
+    pub fn compile(&mut self) -> Result<(), regex::Error> {
+        self.pattern = Some(regex::Regex::new(&format!("(?i)^(?:{})$", self.app))?);
+        Ok(())
+    }
+
+    /// Whether the rule is for `app` (never, before it was compiled)
+    pub fn matches(&self, app: &str) -> bool {
+        self.pattern
+            .as_ref()
+            .is_some_and(|pattern| pattern.is_match(app))
+    }
+
+    /// Apply the rule's output settings
+    pub fn apply(&self, output: &mut OutputConfig) {
+        if let Some(ref mode) = self.mode {
+            output.set_mode(mode.clone());
+        }
+        if self.paste_keys.is_some() {
+            output.paste_keys = self.paste_keys.clone();
+        }
+        if let Some(auto_submit) = self.auto_submit {
+            output.auto_submit = auto_submit;
+        }
+        if let Some(delay) = self.type_delay_ms {
+            output.type_delay_ms = delay;
+        }
+    }
+}
+
 /// Per-state icon overrides for status display
 #[derive(Debug, Clone, Default, Deserialize, Serialize)]
 pub struct StatusIconOverrides {
@@ -553,6 +1129,12 @@ pub struct WhisperConfig {
     #[serde(default = "default_context_window_optimization")]
     pub context_window_optimization: bool,
 
+    /// Transcribe while recording and publish partial results (local backend only)
+    /// Most audio is already decoded when recording stops, so long dictations
+    /// finish much faster, at the cost of extra CPU/GPU use while speaking.
+    #[serde(default)]
+    pub streaming: bool,
+
     // --- Remote backend settings ---
     /// Remote server endpoint URL (e.g., "http://192.168.1.100:8080")
     /// Required when backend = "remote"
@@ -583,6 +1165,11 @@ pub struct TextConfig {
     /// Example: { "vox type" = "voxtype" }
     #[serde(default)]
     pub replacements: HashMap<String, String>,
+
+    /// Spoken phrases that undo the last dictation instead of being typed
+    /// Example: ["scratch that"]
+    #[serde(default)]
+    pub undo_phrases: Vec<String>,
 }
 
 /// Notification configuration
@@ -650,6 +1237,8 @@ pub struct OutputConfig {
 
     /// Delay before wtype starts typing (ms), allows virtual keyboard to initialize
     /// Helps prevent first character from being dropped on some compositors
+    /// Only used by the wtype fallback; the built-in virtual keyboard waits
+    /// for the compositor instead
     #[serde(default)]
     pub wtype_delay_ms: u32,
 
@@ -683,40 +1272,288 @@ pub struct OutputConfig {
     /// Defaults to "ctrl+v" if not specified
     #[serde(default)]
     pub paste_keys: Option<String>,
+
+    /// Selection paste mode copies the text to: "clipboard" (default) or
+    /// "primary" (pair with a keystroke that pastes the primary selection)
+    #[serde(default)]
+    pub paste_selection: PasteSelection,
+
+    /// Put back what was in the selection after paste mode has pasted
+    #[serde(default)]
+    pub restore_clipboard: bool,
+
+    /// Delay after the paste keystroke before restoring the selection (ms),
+    /// long enough for the application to read the pasted text
+    #[serde(default = "default_restore_clipboard_delay_ms")]
+    pub restore_clipboard_delay_ms: u32,
+
+    /// XKB keyboard layout for the built-in uinput typing, e.g. "de" or
+    /// "de(nodeadkeys)". Detected from the session if not set
+    #[serde(default)]
+    pub keyboard_layout: Option<String>,
+
+    /// Backends to try in order, replacing the built-in chain of `mode`
+    /// e.g. ["uinput", { backend = "ydotool", type_delay_ms = 5 }, "clipboard"]
+    #[serde(default)]
+    pub chain: Option<Vec<OutputChainEntry>>,
+
+    /// File the "file" output appends to
+    #[serde(default)]
+    pub file: Option<FileOutputConfig>,
+
+    /// Named pipe the "fifo" output writes to
+    #[serde(default)]
+    pub fifo: Option<FifoOutputConfig>,
+
+    /// Command the "command" output pipes the text into
+    #[serde(default)]
+    pub command: Option<CommandOutputConfig>,
 }
 
-/// Output mode selection
-#[derive(Debug, Clone, Deserialize, Serialize, PartialEq, Eq)]
+/// Settings of the "file" output
+#[derive(Debug, Clone, Deserialize, Serialize, PartialEq)]
+pub struct FileOutputConfig {
+    /// File to append to, with `~` and strftime patterns expanded
+    /// (e.g. "~/notes/%Y-%m-%d.md" for one file a day)
+    pub path: String,
+
+    /// What to append: strftime patterns are expanded, then {text} is
+    /// replaced by the transcription
+    #[serde(default = "default_file_template")]
+    pub template: String,
+}
+
+fn default_file_template() -> String {
+    "{text}\n".to_string()
+}
+
+/// Settings of the "fifo" output
+#[derive(Debug, Clone, Deserialize, Serialize, PartialEq)]
+pub struct FifoOutputConfig {
+    /// Named pipe to write to, created if it doesn't exist
+    pub path: String,
+}
+
+/// Settings of the "command" output
+#[derive(Debug, Clone, Deserialize, Serialize, PartialEq)]
+pub struct CommandOutputConfig {
+    /// Shell command to run, receives the text on stdin
+    pub command: String,
+
+    /// Timeout in milliseconds (default: 10000 = 10 seconds)
+    #[serde(default = "default_output_command_timeout")]
+    pub timeout_ms: u64,
+}
+
+fn default_output_command_timeout() -> u64 {
+    10000
+}
+
+fn default_restore_clipboard_delay_ms() -> u32 {
+    300
+}
+
+/// Selection used by paste mode
+#[derive(Debug, Clone, Copy, Default, Deserialize, Serialize, PartialEq, Eq)]
 #[serde(rename_all = "lowercase")]
-pub enum OutputMode {
-    /// Simulate keyboard input (requires ydotool)
-    Type,
-    /// Copy to clipboard (requires wl-copy)
+pub enum PasteSelection {
+    /// The clipboard (Ctrl+C / Ctrl+V)
+    #[default]
     Clipboard,
-    /// Copy to clipboard then paste with Ctrl+V (requires wl-copy and ydotool)
+    /// The primary selection (select / middle click)
+    Primary,
+}
+
+impl OutputConfig {
+    /// Switch to the built-in chain of `mode`, dropping any custom chain
+    /// (for explicit mode overrides like --clipboard)
+    pub fn set_mode(&mut self, mode: OutputMode) {
+        self.mode = mode;
+        self.chain = None;
+    }
+}
+
+/// Output backend of a custom chain
+#[derive(Debug, Clone, Copy, Deserialize, Serialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum OutputBackend {
+    /// Wayland virtual keyboard protocol, built in
+    VirtualKeyboard,
+    /// wtype (Wayland)
+    Wtype,
+    /// XTEST extension (X11), built in
+    Xtest,
+    /// Built-in uinput keyboard
+    Uinput,
+    /// ydotool (requires daemon)
+    Ydotool,
+    /// Copy to clipboard
+    Clipboard,
+    /// Copy to clipboard, then press the paste keystroke
     Paste,
+    /// Wayland input method protocol
+    InputMethod,
+    /// Append to the file of [output.file]
+    File,
+    /// Write to the named pipe of [output.fifo]
+    Fifo,
+    /// Pipe into the command of [output.command]
+    Command,
+    /// Print from a waiting `voxtype record start --wait`
+    Stdout,
 }
 
-fn default_true() -> bool {
-    true
+impl std::fmt::Display for OutputBackend {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let name = match self {
+            OutputBackend::VirtualKeyboard => "virtual_keyboard",
+            OutputBackend::Wtype => "wtype",
+            OutputBackend::Xtest => "xtest",
+            OutputBackend::Uinput => "uinput",
+            OutputBackend::Ydotool => "ydotool",
+            OutputBackend::Clipboard => "clipboard",
+            OutputBackend::Paste => "paste",
+            OutputBackend::InputMethod => "input_method",
+            OutputBackend::File => "file",
+            OutputBackend::Fifo => "fifo",
+            OutputBackend::Command => "command",
+            OutputBackend::Stdout => "stdout",
+        };
+        write!(f, "{}", name)
+    }
 }
 
-impl Default for Config {
-    fn default() -> Self {
-        Self {
-            hotkey: HotkeyConfig {
-                key: "SCROLLLOCK".to_string(),
-                modifiers: vec![],
-                mode: ActivationMode::default(),
-                enabled: true,
-                cancel_key: None,
-            },
+/// Entry of `output.chain`
+#[derive(Debug, Clone, Deserialize, Serialize, PartialEq)]
+#[serde(untagged)]
+pub enum OutputChainEntry {
+    /// `"ydotool"`: the backend with the [output] settings
+    Backend(OutputBackend),
+    /// `{ backend = "ydotool", type_delay_ms = 5 }`: with its own options
+    Configured(OutputBackendConfig),
+}
+
+/// Chain backend with options overriding the [output] settings
+#[derive(Debug, Clone, Deserialize, Serialize, PartialEq)]
+#[serde(deny_unknown_fields)]
+pub struct OutputBackendConfig {
+    pub backend: OutputBackend,
+
+    /// Overrides `type_delay_ms`
+    #[serde(default)]
+    pub type_delay_ms: Option<u32>,
+
+    /// Overrides `wtype_delay_ms`
+    #[serde(default)]
+    pub wtype_delay_ms: Option<u32>,
+
+    /// Overrides `paste_keys`
+    #[serde(default)]
+    pub paste_keys: Option<String>,
+
+    /// Overrides `keyboard_layout`
+    #[serde(default)]
+    pub keyboard_layout: Option<String>,
+
+    /// Overrides `auto_submit`
+    #[serde(default)]
+    pub auto_submit: Option<bool>,
+}
+
+impl OutputChainEntry {
+    pub fn backend(&self) -> OutputBackend {
+        match self {
+            OutputChainEntry::Backend(backend) => *backend,
+            OutputChainEntry::Configured(configured) => configured.backend,
+        }
+    }
+
+    /// The [output] settings with this entry's options applied
+    pub fn apply(&self, config: &OutputConfig) -> OutputConfig {
+        let mut config = config.clone();
+        if let OutputChainEntry::Configured(options) = self {
+            if let Some(delay) = options.type_delay_ms {
+                config.type_delay_ms = delay;
+            }
+            if let Some(delay) = options.wtype_delay_ms {
+                config.wtype_delay_ms = delay;
+            }
+            if options.paste_keys.is_some() {
+                config.paste_keys = options.paste_keys.clone();
+            }
+            if options.keyboard_layout.is_some() {
+                config.keyboard_layout = options.keyboard_layout.clone();
+            }
+            if let Some(auto_submit) = options.auto_submit {
+                config.auto_submit = auto_submit;
+            }
+        }
+        config
+    }
+}
+
+/// Output mode selection
+#[derive(Debug, Clone, Deserialize, Serialize, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum OutputMode {
+    /// Simulate keyboard input (requires ydotool)
+    Type,
+    /// Copy to clipboard (requires wl-copy)
+    Clipboard,
+    /// Copy to clipboard then paste with Ctrl+V (requires wl-copy and ydotool)
+    Paste,
+    /// Commit text as a Wayland input method, typing where that isn't supported
+    #[serde(rename = "input_method")]
+    InputMethod,
+    /// Append to a file (see [output.file])
+    File,
+    /// Write to a named pipe (see [output.fifo])
+    Fifo,
+    /// Pipe into a command (see [output.command])
+    Command,
+    /// Print from a waiting `voxtype record start --wait`
+    Stdout,
+}
+
+impl From<crate::cli::OutputModeOverride> for OutputMode {
+    fn from(mode: crate::cli::OutputModeOverride) -> Self {
+        match mode {
+            crate::cli::OutputModeOverride::Type => OutputMode::Type,
+            crate::cli::OutputModeOverride::Clipboard => OutputMode::Clipboard,
+            crate::cli::OutputModeOverride::Paste => OutputMode::Paste,
+        }
+    }
+}
+
+fn default_true() -> bool {
+    true
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Self {
+            hotkey: HotkeyConfig {
+                key: "SCROLLLOCK".to_string(),
+                modifiers: vec![],
+                mode: ActivationMode::default(),
+                tap_threshold_ms: default_tap_threshold_ms(),
+                double_tap_window_ms: default_double_tap_window_ms(),
+                chord_window_ms: default_chord_window_ms(),
+                enabled: true,
+                cancel_key: None,
+                undo_key: None,
+                bindings: Vec::new(),
+                devices: Vec::new(),
+                grab: GrabMode::default(),
+            },
             audio: AudioConfig {
                 device: "default".to_string(),
                 sample_rate: 16000,
                 max_duration_secs: 60,
                 transcribe_on_timeout: false,
                 feedback: AudioFeedbackConfig::default(),
+                vad: VadConfig::default(),
+                trim: TrimConfig::default(),
             },
             whisper: WhisperConfig {
                 backend: WhisperBackend::default(),
@@ -727,6 +1564,7 @@ impl Default for Config {
                 on_demand_loading: default_on_demand_loading(),
                 gpu_isolation: false,
                 context_window_optimization: default_context_window_optimization(),
+                streaming: false,
                 remote_endpoint: None,
                 remote_model: None,
                 remote_api_key: None,
@@ -744,14 +1582,57 @@ impl Default for Config {
                 post_output_command: None,
                 post_process: None,
                 paste_keys: None,
+                paste_selection: PasteSelection::default(),
+                restore_clipboard: false,
+                restore_clipboard_delay_ms: default_restore_clipboard_delay_ms(),
+                keyboard_layout: None,
+                chain: None,
+                file: None,
+                fifo: None,
+                command: None,
             },
             text: TextConfig::default(),
             status: StatusConfig::default(),
+            history: HistoryConfig::default(),
+            profiles: HashMap::new(),
+            app_rules: Vec::new(),
             state_file: Some("auto".to_string()),
         }
     }
 }
 
+/// Apply a table of overrides to a config section
+/// Nested tables are merged key by key; other values are replaced
+fn merge_section<T>(base: &T, overrides: &toml::Table) -> Result<T, String>
+where
+    T: Serialize + serde::de::DeserializeOwned + Clone,
+{
+    if overrides.is_empty() {
+        return Ok(base.clone());
+    }
+
+    let mut value = toml::Value::try_from(base).map_err(|e| e.to_string())?;
+    if let toml::Value::Table(ref mut table) = value {
+        merge_tables(table, overrides);
+    }
+    value
+        .try_into()
+        .map_err(|e: toml::de::Error| e.message().to_string())
+}
+
+fn merge_tables(base: &mut toml::Table, overrides: &toml::Table) {
+    for (key, value) in overrides {
+        match (base.get_mut(key), value) {
+            (Some(toml::Value::Table(base)), toml::Value::Table(overrides)) => {
+                merge_tables(base, overrides)
+            }
+            _ => {
+                base.insert(key.clone(), value.clone());
+            }
+        }
+    }
+}
+
 impl Config {
     /// Get the default config file path
     pub fn default_path() -> Option<PathBuf> {
@@ -781,6 +1662,130 @@ impl Config {
             })
     }
 
+    /// Configuration with the named profile applied
+    pub fn with_profile(&self, name: &str) -> Result<Config, VoxtypeError> {
+        let profile = self.profiles.get(name).ok_or_else(|| {
+            let mut names: Vec<&str> = self.profiles.keys().map(String::as_str).collect();
+            names.sort();
+            VoxtypeError::Config(if names.is_empty() {
+                format!("Unknown profile '{}' (no profiles configured)", name)
+            } else {
+                format!(
+                    "Unknown profile '{}' (available: {})",
+                    name,
+                    names.join(", ")
+                )
+            })
+        })?;
+        let invalid = |section: &str, e: String| {
+            VoxtypeError::Config(format!("Invalid profile '{}' [{}]: {}", name, section, e))
+        };
+
+        let mut config = self.clone();
+        config.whisper =
+            merge_section(&self.whisper, &profile.whisper).map_err(|e| invalid("whisper", e))?;
+        config.text = merge_section(&self.text, &profile.text).map_err(|e| invalid("text", e))?;
+        config.output =
+            merge_section(&self.output, &profile.output).map_err(|e| invalid("output", e))?;
+
+        match profile.post_process {
+            None => {}
+            Some(PostProcessOverride::Enabled(false)) => config.output.post_process = None,
+            Some(PostProcessOverride::Enabled(true)) => {
+                return Err(invalid(
+                    "post_process",
+                    "expected a table of settings or false".to_string(),
+                ))
+            }
+            Some(PostProcessOverride::Settings(ref overrides)) => {
+                config.output.post_process = Some(
+                    match config.output.post_process {
+                        Some(ref base) => merge_section(base, overrides),
+                        None => toml::Value::Table(overrides.clone())
+                            .try_into()
+                            .map_err(|e: toml::de::Error| e.message().to_string()),
+                    }
+                    .map_err(|e| invalid("post_process", e))?,
+                );
+            }
+        }
+
+        Ok(config)
+    }
+
+    /// Check that every profile applies cleanly
+    pub fn validate_profiles(&self) -> Result<(), VoxtypeError> {
+        for name in self.profiles.keys() {
+            self.with_profile(name)?;
+        }
+        Ok(())
+    }
+
+    /// Check that hotkey bindings only use options that fit their action
+    /// and refer to existing profiles, and that device filters select something
+    pub fn validate_hotkeys(&self) -> Result<(), VoxtypeError> {
+        if self.hotkey.devices.contains(&DeviceFilter::default()) {
+            return Err(VoxtypeError::Config(
+                "Invalid hotkey device: set name, vendor_id, product_id or path".to_string(),
+            ));
+        }
+        if self.hotkey.grab == GrabMode::Exclusive && self.hotkey.devices.is_empty() {
+            return Err(VoxtypeError::Config(
+                "hotkey.grab = \"exclusive\" needs [[hotkey.devices]], or it would grab the keyboard; \
+                 use \"passthrough\" to grab a keyboard"
+                    .to_string(),
+            ));
+        }
+        for binding in &self.hotkey.bindings {
+            let invalid = |e: String| {
+                VoxtypeError::Config(format!("Invalid hotkey binding '{}': {}", binding, e))
+            };
+
+            if binding.action != HotkeyAction::Record {
+                let record_options = [
+                    ("mode", binding.mode.is_some()),
+                    ("output_mode", binding.output_mode.is_some()),
+                    ("language", binding.language.is_some()),
+                    ("profile", binding.profile.is_some()),
+                ];
+                if let Some((option, _)) = record_options.iter().find(|(_, set)| *set) {
+                    return Err(invalid(format!(
+                        "{} only applies to the record action",
+                        option
+                    )));
+                }
+            }
+            if binding.double_tap && !binding.chord.is_empty() {
+                return Err(invalid(
+                    "double_tap can't be combined with chord".to_string(),
+                ));
+            }
+            if let Some(ref profile) = binding.profile {
+                self.with_profile(profile)
+                    .map_err(|e| invalid(e.to_string()))?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Check that app rules refer to existing profiles and compile their patterns
+    pub fn validate_app_rules(&mut self) -> Result<(), VoxtypeError> {
+        let mut rules = std::mem::take(&mut self.app_rules);
+        let result = rules.iter_mut().try_for_each(|rule| {
+            let app = rule.app.clone();
+            let invalid =
+                |e: String| VoxtypeError::Config(format!("Invalid app rule '{}': {}", app, e));
+
+            if let Some(ref profile) = rule.profile {
+                self.with_profile(profile)
+                    .map_err(|e| invalid(e.to_string()))?;
+            }
+            rule.compile().map_err(|e| invalid(e.to_string()))
+        });
+        self.app_rules = rules;
+        result
+    }
+
     /// Get the config directory path
     pub fn config_dir() -> Option<PathBuf> {
         directories::ProjectDirs::from("", "", "voxtype")
@@ -834,6 +1839,9 @@ pub fn load_config(path: Option<&Path>) -> Result<Config, VoxtypeError> {
 
             config = toml::from_str(&contents)
                 .map_err(|e| VoxtypeError::Config(format!("Invalid config: {}", e)))?;
+            config.validate_profiles()?;
+            config.validate_hotkeys()?;
+            config.validate_app_rules()?;
         } else {
             tracing::debug!("Config file not found at {:?}, using defaults", path);
         }
@@ -847,11 +1855,11 @@ pub fn load_config(path: Option<&Path>) -> Result<Config, VoxtypeError> {
         config.whisper.model = model;
     }
     if let Ok(mode) = std::env::var("VOXTYPE_OUTPUT_MODE") {
-        config.output.mode = match mode.to_lowercase().as_str() {
+        config.output.set_mode(match mode.to_lowercase().as_str() {
             "clipboard" => OutputMode::Clipboard,
             "paste" => OutputMode::Paste,
             _ => OutputMode::Type,
-        };
+        });
     }
 
     Ok(config)
@@ -986,6 +1994,447 @@ mod tests {
         assert_eq!(config.audio.feedback.volume, 0.5);
     }
 
+    #[test]
+    fn test_parse_hybrid_mode() {
+        let toml_str = r#"
+            [hotkey]
+            key = "F13"
+            mode = "hybrid"
+            tap_threshold_ms = 250
+
+            [audio]
+            device = "default"
+            sample_rate = 16000
+            max_duration_secs = 60
+
+            [whisper]
+            model = "base.en"
+            language = "en"
+
+            [output]
+            mode = "type"
+        "#;
+
+        let config: Config = toml::from_str(toml_str).unwrap();
+        assert_eq!(config.hotkey.mode, ActivationMode::Hybrid);
+        assert_eq!(config.hotkey.tap_threshold_ms, 250);
+        assert_eq!(Config::default().hotkey.tap_threshold_ms, 300);
+    }
+
+    #[test]
+    fn test_parse_input_method_output_mode() {
+        let toml_str = r#"
+            [hotkey]
+            key = "F13"
+
+            [audio]
+            device = "default"
+            sample_rate = 16000
+            max_duration_secs = 60
+
+            [whisper]
+            model = "base.en"
+            language = "en"
+
+            [output]
+            mode = "input_method"
+        "#;
+
+        let config: Config = toml::from_str(toml_str).unwrap();
+        assert_eq!(config.output.mode, OutputMode::InputMethod);
+    }
+
+    #[test]
+    fn test_parse_output_chain() {
+        let toml_str = r#"
+            [hotkey]
+            key = "F13"
+
+            [audio]
+            device = "default"
+            sample_rate = 16000
+            max_duration_secs = 60
+
+            [whisper]
+            model = "base.en"
+            language = "en"
+
+            [output]
+            mode = "type"
+            type_delay_ms = 1
+            chain = [
+                "uinput",
+                { backend = "ydotool", type_delay_ms = 5, auto_submit = true },
+                "clipboard",
+            ]
+        "#;
+
+        let config: Config = toml::from_str(toml_str).unwrap();
+        let chain = config.output.chain.as_ref().unwrap();
+        let backends: Vec<_> = chain.iter().map(|entry| entry.backend()).collect();
+        assert_eq!(
+            backends,
+            vec![
+                OutputBackend::Uinput,
+                OutputBackend::Ydotool,
+                OutputBackend::Clipboard
+            ]
+        );
+
+        // Bare names keep the [output] settings, tables override them
+        let uinput = chain[0].apply(&config.output);
+        assert_eq!(uinput.type_delay_ms, 1);
+        assert!(!uinput.auto_submit);
+        let ydotool = chain[1].apply(&config.output);
+        assert_eq!(ydotool.type_delay_ms, 5);
+        assert!(ydotool.auto_submit);
+    }
+
+    #[test]
+    fn test_parse_output_chain_rejects_unknown_entries() {
+        for chain in [r#"["xdotool"]"#, r#"[{ backend = "wtype", delay = 5 }]"#] {
+            let toml_str = format!(
+                r#"
+                [hotkey]
+                key = "F13"
+
+                [audio]
+                device = "default"
+                sample_rate = 16000
+                max_duration_secs = 60
+
+                [whisper]
+                model = "base.en"
+                language = "en"
+
+                [output]
+                mode = "type"
+                chain = {}
+            "#,
+                chain
+            );
+            assert!(toml::from_str::<Config>(&toml_str).is_err(), "{}", chain);
+        }
+    }
+
+    #[test]
+    fn test_set_mode_drops_custom_chain() {
+        let mut config = Config::default();
+        config.output.chain = Some(vec![OutputChainEntry::Backend(OutputBackend::Ydotool)]);
+        config.output.set_mode(OutputMode::Clipboard);
+        assert_eq!(config.output.mode, OutputMode::Clipboard);
+        assert!(config.output.chain.is_none());
+    }
+
+    #[test]
+    fn test_parse_app_rules() {
+        let toml_str = r#"
+            [hotkey]
+            key = "F13"
+
+            [audio]
+            device = "default"
+            sample_rate = 16000
+            max_duration_secs = 60
+
+            [whisper]
+            model = "base.en"
+            language = "en"
+
+            [output]
+            mode = "type"
+
+            [profiles.chat.text]
+            spoken_punctuation = true
+
+            [[app_rules]]
+            app = "kitty|foot"
+            mode = "paste"
+            paste_keys = "ctrl+shift+v"
+
+            [[app_rules]]
+            app = "Slack"
+            auto_submit = true
+            profile = "chat"
+        "#;
+
+        let mut config: Config = toml::from_str(toml_str).unwrap();
+        assert!(!config.app_rules[0].matches("kitty"));
+        config.validate_app_rules().unwrap();
+        assert_eq!(config.app_rules.len(), 2);
+        assert!(config.app_rules[0].matches("Kitty"));
+        assert!(!config.app_rules[0].matches("kitty2"));
+
+        let mut output = config.output.clone();
+        config.app_rules[0].apply(&mut output);
+        assert_eq!(output.mode, OutputMode::Paste);
+        assert_eq!(output.paste_keys.as_deref(), Some("ctrl+shift+v"));
+        assert!(!output.auto_submit);
+
+        let mut output = config.output.clone();
+        config.app_rules[1].apply(&mut output);
+        assert_eq!(output.mode, OutputMode::Type);
+        assert!(output.auto_submit);
+    }
+
+    #[test]
+    fn test_validate_app_rules() {
+        let rule = |app: &str, profile: Option<&str>| AppRule {
+            app: app.to_string(),
+            auto_submit: Some(true),
+            profile: profile.map(str::to_string),
+            ..Default::default()
+        };
+
+        let mut config = Config {
+            app_rules: vec![rule("firefox", None)],
+            ..Default::default()
+        };
+        assert!(config.validate_app_rules().is_ok());
+
+        // Broken regex
+        config.app_rules = vec![rule("kitty(", None)];
+        assert!(config.validate_app_rules().is_err());
+
+        // Unknown profile
+        config.app_rules = vec![rule("firefox", Some("missing"))];
+        assert!(config.validate_app_rules().is_err());
+    }
+
+    #[test]
+    fn test_parse_vad_config() {
+        let toml_str = r#"
+            [hotkey]
+            key = "F13"
+            mode = "toggle"
+
+            [audio]
+            device = "default"
+            sample_rate = 16000
+            max_duration_secs = 60
+
+            [audio.vad]
+            enabled = true
+            silence_ms = 800
+
+            [whisper]
+            model = "base.en"
+            language = "en"
+
+            [output]
+            mode = "type"
+        "#;
+
+        let config: Config = toml::from_str(toml_str).unwrap();
+        assert!(config.audio.vad.enabled);
+        assert_eq!(config.audio.vad.silence_ms, 800);
+        assert_eq!(config.audio.vad.threshold, 0.01); // default
+        assert!(!config.audio.trim.enabled); // default
+    }
+
+    #[test]
+    fn test_parse_history_config() {
+        let toml_str = r#"
+            [hotkey]
+            key = "SCROLLLOCK"
+
+            [audio]
+            device = "default"
+            sample_rate = 16000
+            max_duration_secs = 60
+
+            [whisper]
+            model = "base.en"
+            language = "en"
+
+            [output]
+            mode = "type"
+
+            [history]
+            max_entries = 50
+        "#;
+
+        let config: Config = toml::from_str(toml_str).unwrap();
+        assert!(config.history.enabled); // default
+        assert_eq!(config.history.max_entries, 50);
+        assert_eq!(config.history.max_age_days, 30); // default
+    }
+
+    #[test]
+    fn test_profiles() {
+        let toml_str = r#"
+            [hotkey]
+            key = "SCROLLLOCK"
+
+            [audio]
+            device = "default"
+            sample_rate = 16000
+            max_duration_secs = 60
+
+            [whisper]
+            model = "base.en"
+            language = "en"
+
+            [output]
+            mode = "type"
+
+            [output.post_process]
+            command = "cleanup"
+
+            [profiles.german.whisper]
+            model = "base"
+            language = "de"
+
+            [profiles.german]
+            post_process = false
+
+            [profiles.email.output]
+            mode = "paste"
+
+            [profiles.email.post_process]
+            timeout_ms = 60000
+        "#;
+
+        let config: Config = toml::from_str(toml_str).unwrap();
+        config.validate_profiles().unwrap();
+
+        let german = config.with_profile("german").unwrap();
+        assert_eq!(german.whisper.model, "base");
+        assert_eq!(german.whisper.language, "de");
+        assert!(german.output.post_process.is_none());
+        assert_eq!(german.output.mode, OutputMode::Type);
+
+        let email = config.with_profile("email").unwrap();
+        assert_eq!(email.whisper.model, "base.en");
+        assert_eq!(email.output.mode, OutputMode::Paste);
+        let post_process = email.output.post_process.unwrap();
+        assert_eq!(post_process.command, "cleanup");
+        assert_eq!(post_process.timeout_ms, 60000);
+
+        let err = config.with_profile("french").unwrap_err().to_string();
+        assert!(err.contains("available: email, german"), "{}", err);
+    }
+
+    #[test]
+    fn test_invalid_profiles_rejected() {
+        let mut config = Config::default();
+        let profile: ProfileConfig = toml::from_str("[whisper]\nmodel = 5\n").unwrap();
+        config.profiles.insert("broken".to_string(), profile);
+        assert!(config.validate_profiles().is_err());
+
+        // Profiles can't override sections other than whisper, text and output
+        assert!(toml::from_str::<ProfileConfig>("[audio]\ndevice = \"mic\"\n").is_err());
+
+        // A post_process table needs a command if the base config has none
+        let profile: ProfileConfig = toml::from_str("[post_process]\ntimeout_ms = 100\n").unwrap();
+        config.profiles.insert("broken".to_string(), profile);
+        assert!(config.validate_profiles().is_err());
+    }
+
+    #[test]
+    fn test_hotkey_bindings() {
+        let toml_str = r#"
+            [hotkey]
+            key = "SCROLLLOCK"
+            mode = "toggle"
+            cancel_key = "ESC"
+            grab = "passthrough"
+
+            [[hotkey.bindings]]
+            key = "F13"
+            modifiers = ["LEFTCTRL"]
+            output_mode = "clipboard"
+            language = "de"
+
+            [[hotkey.bindings]]
+            key = "F14"
+            action = "undo"
+
+            [[hotkey.bindings]]
+            key = "RIGHTCTRL"
+            double_tap = true
+
+            [[hotkey.bindings]]
+            key = "J"
+            chord = ["K"]
+
+            [[hotkey.devices]]
+            name = "FootSwitch"
+            vendor_id = 0x0c45
+            product_id = 0x7403
+
+            [audio]
+            device = "default"
+            sample_rate = 16000
+            max_duration_secs = 60
+
+            [whisper]
+            model = "base.en"
+            language = "en"
+
+            [output]
+            mode = "type"
+        "#;
+
+        let config: Config = toml::from_str(toml_str).unwrap();
+        config.validate_hotkeys().unwrap();
+
+        let bindings = config.hotkey.all_bindings();
+        assert_eq!(bindings.len(), 6);
+        assert_eq!(bindings[0].key, "SCROLLLOCK");
+        assert_eq!(bindings[0].action, HotkeyAction::Record);
+        assert_eq!(bindings[1].key, "ESC");
+        assert_eq!(bindings[1].action, HotkeyAction::Cancel);
+        assert_eq!(bindings[2].to_string(), "LEFTCTRL+F13");
+        assert_eq!(bindings[2].mode, None);
+        assert_eq!(bindings[2].output_mode, Some(OutputMode::Clipboard));
+        assert_eq!(bindings[2].language.as_deref(), Some("de"));
+        assert_eq!(bindings[3].action, HotkeyAction::Undo);
+        assert!(bindings[4].double_tap);
+        assert_eq!(bindings[4].to_string(), "double-tap RIGHTCTRL");
+        assert_eq!(bindings[5].to_string(), "J+K");
+        assert_eq!(config.hotkey.double_tap_window_ms, 300);
+        assert_eq!(config.hotkey.grab, GrabMode::Passthrough);
+
+        let device = &config.hotkey.devices[0];
+        assert_eq!(device.name.as_deref(), Some("FootSwitch"));
+        assert_eq!(device.vendor_id, Some(0x0c45));
+        assert_eq!(device.product_id, Some(0x7403));
+        assert!(device.path.is_none());
+    }
+
+    #[test]
+    fn test_invalid_hotkey_bindings_rejected() {
+        let binding = |toml_str: &str| toml::from_str::<HotkeyBinding>(toml_str).unwrap();
+
+        let mut config = Config::default();
+        config.hotkey.bindings = vec![binding(
+            "key = \"ESC\"\naction = \"cancel\"\nlanguage = \"de\"",
+        )];
+        assert!(config.validate_hotkeys().is_err());
+
+        config.hotkey.bindings = vec![binding("key = \"F13\"\nprofile = \"missing\"")];
+        assert!(config.validate_hotkeys().is_err());
+
+        config.hotkey.bindings = vec![binding("key = \"J\"\nchord = [\"K\"]\ndouble_tap = true")];
+        assert!(config.validate_hotkeys().is_err());
+
+        // Typos in option names are caught when parsing
+        assert!(toml::from_str::<HotkeyBinding>("key = \"F13\"\nlangauge = \"de\"").is_err());
+
+        // A device filter without options would select every device
+        config.hotkey.bindings.clear();
+        config.hotkey.devices = vec![DeviceFilter::default()];
+        assert!(config.validate_hotkeys().is_err());
+
+        // Exclusive grab of every device with the hotkey would take the keyboard
+        config.hotkey.devices.clear();
+        config.hotkey.grab = GrabMode::Exclusive;
+        assert!(config.validate_hotkeys().is_err());
+        config.hotkey.grab = GrabMode::Passthrough;
+        assert!(config.validate_hotkeys().is_ok());
+    }
+
     #[test]
     fn test_parse_auto_submit() {
         let toml_str = r#"
@@ -1033,6 +2482,101 @@ mod tests {
         assert!(!config.output.auto_submit);
     }
 
+    #[test]
+    fn test_parse_clipboard_restore() {
+        let toml_str = r#"
+            [hotkey]
+            key = "SCROLLLOCK"
+
+            [audio]
+            device = "default"
+            sample_rate = 16000
+            max_duration_secs = 60
+
+            [whisper]
+            model = "base.en"
+            language = "en"
+
+            [output]
+            mode = "paste"
+        "#;
+
+        let config: Config = toml::from_str(toml_str).unwrap();
+        assert!(!config.output.restore_clipboard);
+        assert_eq!(config.output.restore_clipboard_delay_ms, 300);
+        assert_eq!(config.output.paste_selection, PasteSelection::Clipboard);
+
+        let toml_str = toml_str.replace(
+            "mode = \"paste\"",
+            "mode = \"paste\"\nrestore_clipboard = true\nrestore_clipboard_delay_ms = 500\npaste_selection = \"primary\"",
+        );
+        let config: Config = toml::from_str(&toml_str).unwrap();
+        assert!(config.output.restore_clipboard);
+        assert_eq!(config.output.restore_clipboard_delay_ms, 500);
+        assert_eq!(config.output.paste_selection, PasteSelection::Primary);
+    }
+
+    #[test]
+    fn test_parse_output_sinks() {
+        let toml_str = r#"
+            [hotkey]
+            key = "SCROLLLOCK"
+
+            [audio]
+            device = "default"
+            sample_rate = 16000
+            max_duration_secs = 60
+
+            [whisper]
+            model = "base.en"
+            language = "en"
+
+            [output]
+            mode = "file"
+            chain = ["fifo", "command", "stdout"]
+
+            [output.file]
+            path = "~/notes/%Y-%m-%d.md"
+
+            [output.fifo]
+            path = "/tmp/voxtype.fifo"
+
+            [output.command]
+            command = "cat >> /tmp/log"
+        "#;
+
+        let config: Config = toml::from_str(toml_str).unwrap();
+        assert_eq!(config.output.mode, OutputMode::File);
+        let backends: Vec<_> = config
+            .output
+            .chain
+            .as_ref()
+            .unwrap()
+            .iter()
+            .map(|e| e.backend())
+            .collect();
+        assert_eq!(
+            backends,
+            [
+                OutputBackend::Fifo,
+                OutputBackend::Command,
+                OutputBackend::Stdout
+            ]
+        );
+
+        let file = config.output.file.unwrap();
+        assert_eq!(file.path, "~/notes/%Y-%m-%d.md");
+        assert_eq!(file.template, "{text}\n");
+        assert_eq!(config.output.fifo.unwrap().path, "/tmp/voxtype.fifo");
+        let command = config.output.command.unwrap();
+        assert_eq!(command.command, "cat >> /tmp/log");
+        assert_eq!(command.timeout_ms, 10000);
+
+        // Each table needs its path or command
+        let toml_str = toml_str.replace("command = \"cat >> /tmp/log\"", "timeout_ms = 5");
+        assert!(toml::from_str::<Config>(&toml_str).is_err());
+    }
+
     #[test]
     fn test_builtin_icon_themes() {
         // Test all built-in themes load correctly
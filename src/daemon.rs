@@ -4,15 +4,30 @@
 //! and text output components.
 
 use crate::audio::feedback::{AudioFeedback, SoundEvent};
+use crate::audio::vad::VoiceActivityDetector;
 use crate::audio::{self, AudioCapture};
-use crate::config::{ActivationMode, Config, OutputMode};
-use crate::error::Result;
-use crate::hotkey::{self, HotkeyEvent};
+use crate::config::{
+    ActivationMode, AppRule, Config, HotkeyAction, HotkeyBinding, OutputBackend, OutputConfig,
+    OutputMode, TrimConfig, WhisperBackend,
+};
+use crate::dbus::{self, DbusService};
+use crate::error::{Result, TranscribeError};
+use crate::history::{self, History, HistoryEntry};
+use crate::hotkey::{self, HotkeyEvent, HotkeyListener};
+use crate::ipc::events::{self, Event};
+use crate::ipc::server::ControlServer;
+use crate::ipc::{self, Request, Response};
 use crate::output;
+use crate::output::focus::FocusQuery;
 use crate::output::post_process::PostProcessor;
+use crate::output::sink::{StdoutOutput, TextWaiter};
+use crate::output::TextOutput;
+use crate::reload::{self, ConfigLoader, ConfigWatcher};
 use crate::state::State;
 use crate::text::TextProcessor;
-use crate::transcribe;
+use crate::transcribe::streaming::{StreamingTranscription, Window};
+use crate::transcribe::{self, Segment};
+use std::collections::HashMap;
 use std::path::PathBuf;
 use std::process::Stdio;
 use std::sync::Arc;
@@ -155,16 +170,279 @@ fn cleanup_output_mode_override() {
 }
 
 /// Result type for transcription task
-type TranscriptionResult = std::result::Result<String, crate::error::TranscribeError>;
+type TranscriptionResult = std::result::Result<String, TranscribeError>;
+
+/// Result of transcribing a window while recording (streaming mode)
+type PartialResult = (Window, std::result::Result<Vec<Segment>, TranscribeError>);
+
+/// Minimum interval between audio level events
+const AUDIO_LEVEL_INTERVAL: Duration = Duration::from_millis(50);
+
+/// Transcriber shared between the daemon loop and blocking transcription tasks
+type SharedTranscriber = Arc<Box<dyn crate::transcribe::Transcriber>>;
+
+/// Initialize audio feedback if enabled
+fn create_audio_feedback(config: &Config) -> Option<AudioFeedback> {
+    if !config.audio.feedback.enabled {
+        return None;
+    }
+    match AudioFeedback::new(&config.audio.feedback) {
+        Ok(feedback) => {
+            tracing::info!(
+                "Audio feedback enabled (theme: {}, volume: {:.0}%)",
+                config.audio.feedback.theme,
+                config.audio.feedback.volume * 100.0
+            );
+            Some(feedback)
+        }
+        Err(e) => {
+            tracing::warn!("Failed to initialize audio feedback: {}", e);
+            None
+        }
+    }
+}
+
+/// Initialize the text processor
+fn create_text_processor(config: &Config) -> TextProcessor {
+    if config.text.spoken_punctuation {
+        tracing::info!("Spoken punctuation enabled");
+    }
+    if !config.text.replacements.is_empty() {
+        tracing::info!(
+            "Word replacements configured: {} rules",
+            config.text.replacements.len()
+        );
+    }
+    TextProcessor::new(&config.text)
+}
+
+/// Initialize the post-processor if configured
+fn create_post_processor(config: &Config) -> Option<PostProcessor> {
+    config.output.post_process.as_ref().map(|cfg| {
+        tracing::info!(
+            "Post-processing enabled: command={:?}, timeout={}ms",
+            cfg.command,
+            cfg.timeout_ms
+        );
+        PostProcessor::new(cfg)
+    })
+}
+
+/// Open the transcription history if enabled
+fn create_history(config: &Config) -> Option<History> {
+    config
+        .history
+        .enabled
+        .then(|| History::new(&config.history))
+}
+
+/// A profile with its components ready to use
+/// Created for each named profile and for hotkey bindings that change the language
+struct Profile {
+    /// What the profile is for (in log messages)
+    label: String,
+    /// Base configuration with the profile applied
+    config: Config,
+    text_processor: TextProcessor,
+    post_processor: Option<PostProcessor>,
+    /// Whether the profile's [whisper] settings differ from the base config
+    own_model: bool,
+    /// Model kept loaded for this profile (own model without on-demand loading)
+    transcriber: Option<SharedTranscriber>,
+}
+
+/// Resolve all configured profiles against the base configuration
+/// Models are not loaded here, see `load_profile_models`
+fn create_profiles(config: &Config) -> HashMap<String, Profile> {
+    let mut profiles = HashMap::new();
+    for name in config.profiles.keys() {
+        match config.with_profile(name) {
+            Ok(profile_config) => {
+                let profile = Profile::new(format!("profile '{}'", name), profile_config, config);
+                profiles.insert(name.clone(), profile);
+            }
+            Err(e) => tracing::warn!("Skipping profile: {}", e),
+        }
+    }
+    if !profiles.is_empty() {
+        let mut names: Vec<&str> = profiles.keys().map(String::as_str).collect();
+        names.sort();
+        tracing::info!("Profiles: {}", names.join(", "));
+    }
+    profiles
+}
+
+/// Resolve the hotkey bindings that set a transcription language
+/// Keyed by the binding's index in `HotkeyConfig::all_bindings`
+fn create_binding_profiles(config: &Config) -> HashMap<usize, Profile> {
+    let mut profiles = HashMap::new();
+    for (index, binding) in config.hotkey.all_bindings().iter().enumerate() {
+        let Some(ref language) = binding.language else {
+            continue;
+        };
+        let base = match binding.profile {
+            Some(ref name) => match config.with_profile(name) {
+                Ok(profile_config) => profile_config,
+                Err(e) => {
+                    tracing::warn!("Skipping hotkey {}: {}", binding, e);
+                    continue;
+                }
+            },
+            None => config.clone(),
+        };
+        let mut binding_config = base;
+        binding_config.whisper.language = language.clone();
+        let profile = Profile::new(format!("hotkey {}", binding), binding_config, config);
+        profiles.insert(index, profile);
+    }
+    profiles
+}
+
+impl Profile {
+    fn new(label: String, config: Config, base: &Config) -> Self {
+        Self {
+            label,
+            text_processor: TextProcessor::new(&config.text),
+            post_processor: config.output.post_process.as_ref().map(PostProcessor::new),
+            own_model: whisper_settings(&config) != whisper_settings(base),
+            transcriber: None,
+            config,
+        }
+    }
+}
+
+/// Short description of what a hotkey binding does (for the startup log)
+fn describe_binding(binding: &HotkeyBinding, default_mode: ActivationMode) -> String {
+    let mut options = Vec::new();
+    if binding.action == HotkeyAction::Record {
+        options.push(match binding.mode.unwrap_or(default_mode) {
+            ActivationMode::PushToTalk => "push to talk".to_string(),
+            ActivationMode::Toggle => "toggle".to_string(),
+            ActivationMode::Hybrid => "hybrid".to_string(),
+        });
+    }
+    if let Some(ref mode) = binding.output_mode {
+        options.push(format!("output {:?}", mode).to_lowercase());
+    }
+    if let Some(ref language) = binding.language {
+        options.push(format!("language {}", language));
+    }
+    if let Some(ref profile) = binding.profile {
+        options.push(format!("profile {}", profile));
+    }
+
+    let action = format!("{:?}", binding.action).to_lowercase();
+    if options.is_empty() {
+        action
+    } else {
+        format!("{} ({})", action, options.join(", "))
+    }
+}
+
+/// [whisper] settings in comparable form
+fn whisper_settings(config: &Config) -> Option<toml::Value> {
+    toml::Value::try_from(&config.whisper).ok()
+}
+
+/// Load the models of profiles that need their own preloaded model
+/// Models already loaded with the same settings (by another profile, or
+/// before a config reload) are shared instead of loaded again.
+async fn load_profile_models<'a>(
+    profiles: impl Iterator<Item = &'a mut Profile>,
+    previous: impl Iterator<Item = &'a Profile>,
+) -> std::result::Result<(), TranscribeError> {
+    let mut loaded: Vec<(Option<toml::Value>, SharedTranscriber)> = previous
+        .filter_map(|p| Some((whisper_settings(&p.config), p.transcriber.clone()?)))
+        .collect();
+
+    for profile in profiles {
+        if !profile.own_model || profile.config.whisper.on_demand_loading {
+            continue;
+        }
+
+        let settings = whisper_settings(&profile.config);
+        if let Some((_, transcriber)) = loaded.iter().find(|(s, _)| *s == settings) {
+            profile.transcriber = Some(transcriber.clone());
+            continue;
+        }
+
+        tracing::info!(
+            "Loading transcription model for {}: {}",
+            profile.label,
+            profile.config.whisper.model
+        );
+        let whisper = profile.config.whisper.clone();
+        let transcriber = Arc::new(
+            tokio::task::spawn_blocking(move || transcribe::create_transcriber(&whisper))
+                .await
+                .unwrap_or_else(|e| Err(TranscribeError::InitFailed(e.to_string())))?,
+        );
+        loaded.push((settings, transcriber.clone()));
+        profile.transcriber = Some(transcriber);
+    }
+    Ok(())
+}
+
+/// Text delivered by the most recent output (for undo)
+struct LastOutput {
+    /// Output backend that delivered it
+    backend: OutputBackend,
+    /// Its name (for logging)
+    method: &'static str,
+    /// Number of characters output
+    chars: usize,
+    /// Whether Enter was sent after the text
+    submitted: bool,
+    /// Output settings it was typed with (profile and app rule applied)
+    config: OutputConfig,
+}
 
 /// Main daemon that orchestrates all components
 pub struct Daemon {
     config: Config,
+    // Config file to watch for changes (hot reload)
+    config_path: Option<PathBuf>,
+    // Re-reads the configuration on reload (None disables hot reload)
+    config_loader: Option<ConfigLoader>,
+    // Contents of the config file last loaded successfully (for diffs)
+    config_text: String,
     state_file_path: Option<PathBuf>,
     pid_file_path: Option<PathBuf>,
     audio_feedback: Option<AudioFeedback>,
     text_processor: TextProcessor,
     post_processor: Option<PostProcessor>,
+    // Named profiles from [profiles]
+    profiles: HashMap<String, Profile>,
+    // Profile selected for the current recording
+    active_profile: Option<String>,
+    // Hotkey bindings that set a transcription language, by binding index
+    binding_profiles: HashMap<usize, Profile>,
+    // Hotkey binding that started the current recording
+    active_binding: Option<usize>,
+    // Transcription history (None if disabled)
+    history: Option<History>,
+    // Final text of the most recent transcription (for replay)
+    last_text: Option<String>,
+    // What the most recent output delivered (for undo)
+    last_output: Option<LastOutput>,
+    // Output mode requested for the current recording (via control socket)
+    output_mode_override: Option<OutputMode>,
+    // Client of `record start --wait` waiting for the current recording's text
+    text_waiter: Option<TextWaiter>,
+    // Finds the focused application for app rules (None if unsupported)
+    focus: Option<Box<dyn FocusQuery>>,
+    // Events published to control socket subscribers
+    events: tokio::sync::broadcast::Sender<Event>,
+    // Audio chunks from the active recording (for level events and VAD)
+    audio_chunks: Option<tokio::sync::mpsc::Receiver<Vec<f32>>>,
+    // Silence detector for the active recording (if auto-stop applies)
+    vad: Option<VoiceActivityDetector>,
+    // Partial transcription state for the active recording (streaming mode)
+    streaming: Option<StreamingTranscription>,
+    // Window being transcribed while recording (streaming mode)
+    partial_task: Option<tokio::task::JoinHandle<PartialResult>>,
+    // When the current transcription started (for latency reporting)
+    transcription_started: Option<std::time::Instant>,
     // Background task for loading model on-demand
     model_load_task: Option<
         tokio::task::JoinHandle<
@@ -181,62 +459,48 @@ pub struct Daemon {
 impl Daemon {
     /// Create a new daemon with the given configuration
     pub fn new(config: Config) -> Self {
-        let state_file_path = config.resolve_state_file();
-
-        // Initialize audio feedback if enabled
-        let audio_feedback = if config.audio.feedback.enabled {
-            match AudioFeedback::new(&config.audio.feedback) {
-                Ok(feedback) => {
-                    tracing::info!(
-                        "Audio feedback enabled (theme: {}, volume: {:.0}%)",
-                        config.audio.feedback.theme,
-                        config.audio.feedback.volume * 100.0
-                    );
-                    Some(feedback)
-                }
-                Err(e) => {
-                    tracing::warn!("Failed to initialize audio feedback: {}", e);
-                    None
-                }
-            }
-        } else {
-            None
-        };
-
-        // Initialize text processor
-        let text_processor = TextProcessor::new(&config.text);
-        if config.text.spoken_punctuation {
-            tracing::info!("Spoken punctuation enabled");
-        }
-        if !config.text.replacements.is_empty() {
-            tracing::info!(
-                "Word replacements configured: {} rules",
-                config.text.replacements.len()
-            );
-        }
-
-        // Initialize post-processor if configured
-        let post_processor = config.output.post_process.as_ref().map(|cfg| {
-            tracing::info!(
-                "Post-processing enabled: command={:?}, timeout={}ms",
-                cfg.command,
-                cfg.timeout_ms
-            );
-            PostProcessor::new(cfg)
-        });
-
         Self {
-            config,
-            state_file_path,
+            state_file_path: config.resolve_state_file(),
             pid_file_path: None,
-            audio_feedback,
-            text_processor,
-            post_processor,
+            audio_feedback: create_audio_feedback(&config),
+            text_processor: create_text_processor(&config),
+            post_processor: create_post_processor(&config),
+            profiles: create_profiles(&config),
+            active_profile: None,
+            binding_profiles: create_binding_profiles(&config),
+            active_binding: None,
+            history: create_history(&config),
+            config,
+            config_path: None,
+            config_loader: None,
+            config_text: String::new(),
+            last_text: None,
+            last_output: None,
+            output_mode_override: None,
+            text_waiter: None,
+            focus: output::focus::detect(),
+            events: events::channel(),
+            audio_chunks: None,
+            vad: None,
+            streaming: None,
+            partial_task: None,
+            transcription_started: None,
             model_load_task: None,
             transcription_task: None,
         }
     }
 
+    /// Reload the configuration on SIGHUP and when the config file changes
+    /// `loader` re-reads the configuration, including any command line overrides
+    pub fn with_config_reload(mut self, path: Option<PathBuf>, loader: ConfigLoader) -> Self {
+        if let Some(ref path) = path {
+            self.config_text = std::fs::read_to_string(path).unwrap_or_default();
+        }
+        self.config_path = path;
+        self.config_loader = Some(loader);
+        self
+    }
+
     /// Play audio feedback sound if enabled
     fn play_feedback(&self, event: SoundEvent) {
         if let Some(ref feedback) = self.audio_feedback {
@@ -244,17 +508,146 @@ impl Daemon {
         }
     }
 
-    /// Update the state file if configured
+    /// Update the state file if configured and notify subscribers
     fn update_state(&self, state_name: &str) {
         if let Some(ref path) = self.state_file_path {
             write_state_file(path, state_name);
         }
+        self.emit(Event::StateChanged {
+            state: state_name.to_string(),
+            profile: self.active_profile.clone(),
+        });
+    }
+
+    /// Publish an event to control socket subscribers (if any)
+    fn emit(&self, event: Event) {
+        let _ = self.events.send(event);
+    }
+
+    /// Hand the waiting client (if any) the current recording's text,
+    /// or the reason there is none
+    fn resolve_text_waiter(&mut self, outcome: std::result::Result<String, String>) {
+        if let Some(waiter) = self.text_waiter.take() {
+            let _ = waiter.send(outcome);
+        }
+    }
+
+    /// Profile selected for the current recording
+    /// (by the hotkey that started it, or by name)
+    fn active(&self) -> Option<&Profile> {
+        if let Some(profile) = self
+            .active_binding
+            .and_then(|index| self.binding_profiles.get(&index))
+        {
+            return Some(profile);
+        }
+        self.active_profile
+            .as_ref()
+            .and_then(|name| self.profiles.get(name))
+    }
+
+    /// Configuration for the current recording (with its profile applied)
+    fn active_config(&self) -> &Config {
+        self.active()
+            .map_or(&self.config, |profile| &profile.config)
+    }
+
+    /// Text processor for the current recording
+    fn active_text_processor(&self) -> &TextProcessor {
+        self.active()
+            .map_or(&self.text_processor, |profile| &profile.text_processor)
+    }
+
+    /// Post-processor for the current recording
+    fn active_post_processor(&self) -> Option<&PostProcessor> {
+        match self.active() {
+            Some(profile) => profile.post_processor.as_ref(),
+            None => self.post_processor.as_ref(),
+        }
+    }
+
+    /// App rule for the application with the keyboard focus
+    async fn focused_app_rule(&self) -> Option<AppRule> {
+        if self.config.app_rules.is_empty() {
+            return None;
+        }
+        let query = self.focus.as_deref()?;
+        output::focus::focused_rule(query, &self.config.app_rules)
+            .await
+            .cloned()
+    }
+
+    /// Profile named by an app rule, replacing the recording's profile for
+    /// text processing and output
+    fn rule_profile(&self, rule: Option<&AppRule>) -> Option<&Profile> {
+        let name = rule?.profile.as_ref()?;
+        self.profiles.get(name)
+    }
+
+    /// Preloaded transcriber for the current recording
+    /// Profiles with their own model use it instead of the default one
+    fn active_transcriber<'a>(
+        &'a self,
+        transcriber_preloaded: Option<&'a SharedTranscriber>,
+    ) -> Option<&'a SharedTranscriber> {
+        match self.active() {
+            Some(profile) if profile.own_model => profile.transcriber.as_ref(),
+            _ => transcriber_preloaded,
+        }
+    }
+
+    /// Check that a profile can be selected
+    fn check_profile(&self, name: &str) -> std::result::Result<(), String> {
+        if self.profiles.contains_key(name) {
+            return Ok(());
+        }
+        Err(match self.config.with_profile(name) {
+            Err(e) => e.to_string(),
+            Ok(_) => format!("Profile '{}' is not available", name),
+        })
+    }
+
+    /// Name of the model used for transcription
+    fn model_name(&self) -> &str {
+        let whisper = &self.active_config().whisper;
+        match whisper.backend {
+            WhisperBackend::Remote => whisper.remote_model.as_deref().unwrap_or("whisper-1"),
+            WhisperBackend::Local => &whisper.model,
+        }
+    }
+
+    /// Store a transcription in the history (if enabled)
+    /// The file is written on a blocking thread, off the daemon loop
+    fn record_history(&self, entry: HistoryEntry) {
+        if let Some(history) = self.history.clone() {
+            tokio::task::spawn_blocking(move || {
+                if let Err(e) = history.add(entry) {
+                    tracing::warn!("Failed to write history {:?}: {}", history.path(), e);
+                }
+            });
+        }
+    }
+
+    /// Play the error sound and publish an error event
+    fn report_error(&self, message: &str) {
+        self.play_feedback(SoundEvent::Error);
+        self.emit(Event::Error {
+            message: message.to_string(),
+        });
     }
 
     /// Reset state to idle and run post_output_command to reset compositor submap
     /// Call this when exiting from recording/transcribing without normal output flow
-    async fn reset_to_idle(&self, state: &mut State) {
+    async fn reset_to_idle(&mut self, state: &mut State) {
         cleanup_output_mode_override();
+        self.output_mode_override = None;
+        self.resolve_text_waiter(Err("Nothing was transcribed".to_string()));
+        self.active_profile = None;
+        self.active_binding = None;
+        self.audio_chunks = None;
+        self.vad = None;
+        self.streaming = None;
+        self.partial_task = None;
         *state = State::Idle;
         self.update_state("idle");
 
@@ -266,14 +659,384 @@ impl Daemon {
         }
     }
 
+    /// Whether recordings started externally (control socket, D-Bus, SIGUSR1)
+    /// end on trailing silence: only in toggle mode, like hotkey recordings
+    fn external_auto_stop(&self) -> bool {
+        self.config.hotkey.mode == ActivationMode::Toggle
+    }
+
+    /// Start audio capture and enter the recording state
+    /// Model loading (or worker preparation) is started here so it overlaps with speech.
+    /// With `auto_stop`, the recording ends on trailing silence if VAD is enabled.
+    async fn start_recording(
+        &mut self,
+        state: &mut State,
+        audio_capture: &mut Option<Box<dyn AudioCapture>>,
+        transcriber_preloaded: Option<&SharedTranscriber>,
+        auto_stop: bool,
+    ) -> std::result::Result<(), String> {
+        // Start model loading in background if on-demand loading is enabled
+        let transcriber_preloaded = self.active_transcriber(transcriber_preloaded).cloned();
+        if self.active_config().whisper.on_demand_loading {
+            let config = self.active_config().whisper.clone();
+            self.model_load_task = Some(tokio::task::spawn_blocking(move || {
+                transcribe::create_transcriber(&config)
+            }));
+            tracing::debug!("Started background model loading");
+        } else if let Some(ref t) = transcriber_preloaded {
+            // For gpu_isolation mode: prepare the subprocess now
+            // (spawns worker and loads model while user speaks)
+            let transcriber = t.clone();
+            tokio::task::spawn_blocking(move || {
+                transcriber.prepare();
+            });
+        }
+
+        // Create and start audio capture
+        tracing::debug!(
+            "Creating audio capture with device: {}",
+            self.config.audio.device
+        );
+        let started = match audio::create_capture(&self.config.audio) {
+            Ok(mut capture) => match capture.start().await {
+                Ok(chunks) => Ok((capture, chunks)),
+                Err(e) => Err(format!("Failed to start audio: {}", e)),
+            },
+            Err(e) => Err(format!("Failed to create audio capture: {}", e)),
+        };
+
+        let (capture, chunks) = match started {
+            Ok(started) => started,
+            Err(e) => {
+                tracing::error!("{}", e);
+                self.report_error(&e);
+                if let Some(task) = self.model_load_task.take() {
+                    task.abort();
+                }
+                return Err(e);
+            }
+        };
+
+        tracing::debug!("Audio capture started successfully");
+        *audio_capture = Some(capture);
+        self.audio_chunks = Some(chunks);
+        if auto_stop && self.config.audio.vad.enabled {
+            self.vad = Some(VoiceActivityDetector::new(&self.config.audio.vad));
+        }
+        if self.active_config().whisper.streaming {
+            if transcriber_preloaded.is_some_and(|t| t.supports_streaming()) {
+                self.streaming = Some(StreamingTranscription::new());
+            } else {
+                tracing::debug!(
+                    "Streaming needs a preloaded local model, transcribing in one pass"
+                );
+            }
+        }
+        *state = State::Recording {
+            started_at: std::time::Instant::now(),
+        };
+        self.update_state("recording");
+        self.emit(Event::RecordingStarted {
+            device: self.config.audio.device.clone(),
+        });
+        self.play_feedback(SoundEvent::RecordingStart);
+
+        // Run pre-recording hook (e.g., enter compositor submap for cancel)
+        if let Some(cmd) = &self.config.output.pre_recording_command {
+            if let Err(e) = output::run_hook(cmd, "pre_recording").await {
+                tracing::warn!("{}", e);
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Get the transcriber for the current recording
+    /// Waits for the background model load when on-demand loading is enabled
+    async fn wait_for_transcriber(
+        &mut self,
+        transcriber_preloaded: Option<&SharedTranscriber>,
+    ) -> std::result::Result<Option<SharedTranscriber>, String> {
+        if !self.active_config().whisper.on_demand_loading {
+            return Ok(self.active_transcriber(transcriber_preloaded).cloned());
+        }
+
+        match self.model_load_task.take() {
+            Some(task) => match task.await {
+                Ok(Ok(transcriber)) => {
+                    tracing::info!("Model loaded successfully");
+                    Ok(Some(Arc::new(transcriber)))
+                }
+                Ok(Err(e)) => Err(format!("Model loading failed: {}", e)),
+                Err(e) => Err(format!("Model loading task panicked: {}", e)),
+            },
+            None => Err("No model loading task found".to_string()),
+        }
+    }
+
+    /// Stop the current recording and start transcribing it
+    async fn stop_recording(
+        &mut self,
+        state: &mut State,
+        audio_capture: &mut Option<Box<dyn AudioCapture>>,
+        transcriber_preloaded: Option<&SharedTranscriber>,
+    ) -> std::result::Result<(), String> {
+        let transcriber = match self.wait_for_transcriber(transcriber_preloaded).await {
+            Ok(transcriber) => transcriber,
+            Err(e) => {
+                tracing::error!("{}", e);
+                self.report_error(&e);
+                if let Some(mut capture) = audio_capture.take() {
+                    let _ = capture.stop().await;
+                }
+                self.reset_to_idle(state).await;
+                return Err(e);
+            }
+        };
+
+        self.start_transcription_task(state, audio_capture, transcriber)
+            .await
+    }
+
+    /// Cancel the current recording or transcription, discarding the audio
+    /// Returns false if there was nothing to cancel
+    async fn cancel(
+        &mut self,
+        state: &mut State,
+        audio_capture: &mut Option<Box<dyn AudioCapture>>,
+    ) -> bool {
+        let notification = if state.is_recording() {
+            tracing::info!("Recording cancelled");
+
+            // Stop recording and discard audio
+            if let Some(mut capture) = audio_capture.take() {
+                let _ = capture.stop().await;
+            }
+
+            // Cancel any pending model load task
+            if let Some(task) = self.model_load_task.take() {
+                task.abort();
+            }
+
+            "Recording discarded"
+        } else if matches!(state, State::Transcribing { .. }) {
+            tracing::info!("Transcription cancelled");
+
+            // Abort the transcription task
+            if let Some(task) = self.transcription_task.take() {
+                task.abort();
+            }
+
+            "Transcription aborted"
+        } else {
+            return false;
+        };
+
+        self.play_feedback(SoundEvent::Cancelled);
+        self.emit(Event::Cancelled);
+        self.resolve_text_waiter(Err("Recording was cancelled".to_string()));
+        self.reset_to_idle(state).await;
+
+        if self.config.output.notification.on_recording_stop {
+            send_notification("Cancelled", notification).await;
+        }
+
+        true
+    }
+
+    /// Start a recording from a hotkey, with the binding's profile,
+    /// language and output mode
+    async fn start_hotkey_recording(
+        &mut self,
+        index: usize,
+        binding: &HotkeyBinding,
+        mode: ActivationMode,
+        state: &mut State,
+        audio_capture: &mut Option<Box<dyn AudioCapture>>,
+        transcriber_preloaded: Option<&SharedTranscriber>,
+    ) {
+        if let Some(ref name) = binding.profile {
+            if let Err(e) = self.check_profile(name) {
+                tracing::warn!("{}", e);
+                self.report_error(&e);
+                return;
+            }
+        }
+
+        match mode {
+            ActivationMode::PushToTalk => tracing::info!("Recording started ({})", binding),
+            ActivationMode::Toggle => {
+                tracing::info!("Recording started ({}, toggle mode)", binding)
+            }
+            ActivationMode::Hybrid => {
+                tracing::info!("Recording started ({}, hybrid mode)", binding)
+            }
+        }
+        self.active_binding = Some(index);
+        self.active_profile = binding.profile.clone();
+        self.output_mode_override = binding.output_mode.clone();
+
+        // Send notification if enabled
+        if self.active_config().output.notification.on_recording_start {
+            match mode {
+                ActivationMode::PushToTalk => {
+                    send_notification("Push to Talk Active", "Recording...").await
+                }
+                ActivationMode::Toggle => {
+                    send_notification("Recording Started", "Press hotkey again to stop").await
+                }
+                ActivationMode::Hybrid => {
+                    send_notification(
+                        "Recording Started",
+                        "Release to stop, or tap to keep recording",
+                    )
+                    .await
+                }
+            }
+        }
+
+        // Hybrid recordings get auto-stop once a tap latches them
+        let auto_stop = mode == ActivationMode::Toggle;
+        if self
+            .start_recording(state, audio_capture, transcriber_preloaded, auto_stop)
+            .await
+            .is_err()
+        {
+            self.output_mode_override = None;
+            self.active_profile = None;
+            self.active_binding = None;
+        }
+    }
+
+    /// Handle a request received on the control socket
+    /// A start request's `text_waiter` stays with the recording it starts.
+    async fn handle_control_request(
+        &mut self,
+        request: Request,
+        text_waiter: Option<TextWaiter>,
+        state: &mut State,
+        audio_capture: &mut Option<Box<dyn AudioCapture>>,
+        transcriber_preloaded: Option<&SharedTranscriber>,
+    ) -> Response {
+        let request = match request {
+            Request::Toggle {
+                output_mode,
+                profile,
+            } => {
+                if state.is_recording() {
+                    Request::Stop { output_mode }
+                } else {
+                    Request::Start {
+                        output_mode,
+                        profile,
+                    }
+                }
+            }
+            other => other,
+        };
+
+        match request {
+            Request::Start {
+                output_mode,
+                profile,
+            } => {
+                if !state.is_idle() {
+                    return Response::error(format!(
+                        "Cannot start recording while {}",
+                        state.name()
+                    ));
+                }
+                if let Some(ref name) = profile {
+                    if let Err(e) = self.check_profile(name) {
+                        return Response::error(e);
+                    }
+                }
+
+                match profile {
+                    Some(ref name) => {
+                        tracing::info!("Recording started (control socket, profile '{}')", name)
+                    }
+                    None => tracing::info!("Recording started (control socket)"),
+                }
+                self.active_profile = profile;
+                self.active_binding = None;
+                if self.active_config().output.notification.on_recording_start {
+                    send_notification("Recording Started", "External trigger").await;
+                }
+
+                self.output_mode_override = output_mode;
+                self.text_waiter = text_waiter;
+                match self
+                    .start_recording(
+                        state,
+                        audio_capture,
+                        transcriber_preloaded,
+                        self.external_auto_stop(),
+                    )
+                    .await
+                {
+                    Ok(()) => {
+                        Response::success(state.name()).with_profile(self.active_profile.clone())
+                    }
+                    Err(e) => {
+                        self.output_mode_override = None;
+                        self.text_waiter = None;
+                        self.active_profile = None;
+                        Response::error(e)
+                    }
+                }
+            }
+            Request::Stop { output_mode } => {
+                if !state.is_recording() {
+                    return Response::error("Not recording");
+                }
+
+                if output_mode.is_some() {
+                    self.output_mode_override = output_mode;
+                }
+                match self
+                    .stop_recording(state, audio_capture, transcriber_preloaded)
+                    .await
+                {
+                    Ok(()) => Response::success(state.name()),
+                    Err(e) => Response::error(e),
+                }
+            }
+            Request::Cancel => {
+                if self.cancel(state, audio_capture).await {
+                    Response::success(state.name())
+                } else {
+                    Response::error("Nothing to cancel (not recording or transcribing)")
+                }
+            }
+            Request::Undo => {
+                if !state.is_idle() {
+                    return Response::error(format!("Cannot undo while {}", state.name()));
+                }
+                match self.undo().await {
+                    Ok(()) => Response::success(state.name()),
+                    Err(e) => Response::error(e),
+                }
+            }
+            Request::Replay { output_mode } => match self.replay(state, output_mode).await {
+                Ok(()) => Response::success(state.name()),
+                Err(e) => Response::error(e),
+            },
+            Request::Status | Request::Subscribe => {
+                Response::success(state.name()).with_profile(self.active_profile.clone())
+            }
+            Request::Toggle { .. } => unreachable!(), // Resolved to start/stop above
+        }
+    }
+
     /// Start transcription task (non-blocking, stores JoinHandle for later completion)
-    /// Returns true if transcription was started, false if skipped (too short)
+    /// Returns an error if the recording was discarded instead (too short, no transcriber)
     async fn start_transcription_task(
         &mut self,
         state: &mut State,
         audio_capture: &mut Option<Box<dyn AudioCapture>>,
-        transcriber: Option<Arc<Box<dyn crate::transcribe::Transcriber>>>,
-    ) -> bool {
+        transcriber: Option<SharedTranscriber>,
+    ) -> std::result::Result<(), String> {
         let duration = state.recording_duration().unwrap_or_default();
         tracing::info!("Recording stopped ({:.1}s)", duration.as_secs_f32());
 
@@ -281,116 +1044,351 @@ impl Daemon {
         self.play_feedback(SoundEvent::RecordingStop);
 
         // Send notification if enabled
-        if self.config.output.notification.on_recording_stop {
+        if self.active_config().output.notification.on_recording_stop {
             send_notification("Recording Stopped", "Transcribing...").await;
         }
 
         // Stop recording and get samples
-        if let Some(mut capture) = audio_capture.take() {
-            match capture.stop().await {
-                Ok(samples) => {
-                    let audio_duration = samples.len() as f32 / 16000.0;
-
-                    // Skip if too short (likely accidental press)
-                    if audio_duration < 0.3 {
-                        tracing::debug!("Recording too short ({:.2}s), ignoring", audio_duration);
-                        self.reset_to_idle(state).await;
-                        return false;
-                    }
+        self.audio_chunks = None;
+        self.vad = None;
+        let streaming = self.streaming.take();
+        let partial_task = self.partial_task.take();
+        let Some(mut capture) = audio_capture.take() else {
+            self.reset_to_idle(state).await;
+            return Err("No active audio capture".to_string());
+        };
 
-                    tracing::info!("Transcribing {:.1}s of audio...", audio_duration);
-                    *state = State::Transcribing {
-                        audio: samples.clone(),
-                    };
-                    self.update_state("transcribing");
+        let samples = match capture.stop().await {
+            Ok(samples) => samples,
+            Err(e) => {
+                tracing::warn!("Recording error: {}", e);
+                let message = format!("Recording error: {}", e);
+                self.emit(Event::Error {
+                    message: message.clone(),
+                });
+                self.reset_to_idle(state).await;
+                return Err(message);
+            }
+        };
 
-                    // Spawn transcription task (non-blocking)
-                    if let Some(t) = transcriber {
-                        self.transcription_task =
-                            Some(tokio::task::spawn_blocking(move || t.transcribe(&samples)));
-                        return true;
-                    } else {
-                        tracing::error!("No transcriber available");
-                        self.play_feedback(SoundEvent::Error);
-                        self.reset_to_idle(state).await;
-                        return false;
-                    }
-                }
-                Err(e) => {
-                    tracing::warn!("Recording error: {}", e);
-                    self.reset_to_idle(state).await;
-                    return false;
-                }
+        let audio_duration = samples.len() as f32 / 16000.0;
+
+        // Skip if too short (likely accidental press)
+        if audio_duration < 0.3 {
+            tracing::debug!("Recording too short ({:.2}s), ignoring", audio_duration);
+            self.reset_to_idle(state).await;
+            return Err(format!(
+                "Recording too short ({:.2}s), discarded",
+                audio_duration
+            ));
+        }
+
+        // Cut dead air before it reaches the transcriber
+        // (streaming trims only the untranscribed tail, see finish_streaming)
+        let samples = if self.config.audio.trim.enabled && streaming.is_none() {
+            let trimmed = audio::trim::trim_silence(&samples, &self.config.audio.trim);
+            if trimmed.is_empty() {
+                tracing::debug!("No speech detected in {:.2}s of audio", audio_duration);
+                self.reset_to_idle(state).await;
+                return Err("No speech detected, discarded".to_string());
             }
+            tracing::debug!(
+                "Trimmed silence: {:.2}s -> {:.2}s",
+                audio_duration,
+                trimmed.len() as f32 / 16000.0
+            );
+            trimmed
         } else {
+            samples
+        };
+        let audio_duration = samples.len() as f32 / 16000.0;
+
+        let Some(t) = transcriber else {
+            tracing::error!("No transcriber available");
+            self.report_error("No transcriber available");
             self.reset_to_idle(state).await;
-            return false;
+            return Err("No transcriber available".to_string());
+        };
+
+        tracing::info!("Transcribing {:.1}s of audio...", audio_duration);
+        *state = State::Transcribing {
+            audio: samples.clone(),
+        };
+        self.update_state("transcribing");
+        self.emit(Event::TranscriptionStarted {
+            audio_secs: audio_duration,
+        });
+        self.transcription_started = Some(std::time::Instant::now());
+
+        // Spawn transcription task (non-blocking)
+        self.transcription_task = Some(match streaming {
+            Some(streaming) => tokio::spawn(finish_streaming(
+                streaming,
+                partial_task,
+                samples,
+                t,
+                self.config.audio.trim.clone(),
+            )),
+            None => tokio::task::spawn_blocking(move || t.transcribe(&samples)),
+        });
+        Ok(())
+    }
+
+    /// Output text through the configured chain (or the given mode instead)
+    /// Runs the pre/post output hooks and publishes the outcome.
+    /// Returns the output method used, or the error message.
+    async fn output_text(
+        &mut self,
+        state: &mut State,
+        text: &str,
+        mode_override: Option<OutputMode>,
+        app_rule: Option<&AppRule>,
+    ) -> std::result::Result<&'static str, String> {
+        let mut output_config = match self.rule_profile(app_rule) {
+            Some(profile) => profile.config.output.clone(),
+            None => self.active_config().output.clone(),
+        };
+        if let Some(rule) = app_rule {
+            rule.apply(&mut output_config);
+        }
+        if let Some(mode_override) = mode_override {
+            output_config.set_mode(mode_override);
+        }
+        let mut output_chain = output::create_output_chain(&output_config);
+
+        // Only a client still waiting for this recording can take the text
+        if self
+            .text_waiter
+            .as_ref()
+            .is_some_and(|waiter| !waiter.is_closed())
+        {
+            for output in output_chain
+                .iter_mut()
+                .filter(|output| output.backend() == OutputBackend::Stdout)
+            {
+                *output = Box::new(StdoutOutput::new(true));
+            }
+        }
+
+        *state = State::Outputting {
+            text: text.to_string(),
+        };
+
+        let output_options = output::OutputOptions {
+            pre_output_command: output_config.pre_output_command.as_deref(),
+            post_output_command: output_config.post_output_command.as_deref(),
+        };
+
+        match output::output_with_fallback(&output_chain, text, output_options).await {
+            Ok(output) => {
+                self.emit(Event::OutputCompleted {
+                    method: output.name().to_string(),
+                });
+                self.resolve_text_waiter(match output.backend() {
+                    OutputBackend::Stdout => Ok(text.to_string()),
+                    _ => Err(format!("Text was output via {} instead", output.name())),
+                });
+                self.record_output(output, text, &output_config);
+                Ok(output.name())
+            }
+            Err(e) => {
+                tracing::error!("Output failed: {}", e);
+                let message = format!("Output failed: {}", e);
+                self.emit(Event::Error {
+                    message: message.clone(),
+                });
+                self.resolve_text_waiter(Err(message.clone()));
+                Err(message)
+            }
+        }
+    }
+
+    /// Remember what an output delivered, for undo
+    fn record_output(&mut self, output: &dyn TextOutput, text: &str, config: &OutputConfig) {
+        let backend = output.backend();
+        self.last_output = Some(LastOutput {
+            backend,
+            method: output.name(),
+            chars: text.chars().count(),
+            submitted: config.auto_submit && backend != OutputBackend::Clipboard,
+            config: config.clone(),
+        });
+    }
+
+    /// Erase the most recent output with the backend that typed it
+    async fn undo(&mut self) -> std::result::Result<(), String> {
+        let last = self.last_output.take().ok_or("Nothing to undo")?;
+        if last.backend == OutputBackend::Clipboard {
+            return Err("Last transcription was only copied to the clipboard".to_string());
+        }
+        if last.submitted {
+            return Err("Last transcription was already submitted with Enter".to_string());
+        }
+
+        tracing::info!(
+            "Undoing last transcription ({} chars via {})",
+            last.chars,
+            last.method
+        );
+
+        // Erase with the settings that typed it (e.g. a profile's chain)
+        let output_config = &last.config;
+        if let Some(cmd) = &output_config.pre_output_command {
+            if let Err(e) = output::run_hook(cmd, "pre_output").await {
+                tracing::warn!("{}", e);
+            }
+        }
+        let result = output::erase_with(output_config, last.backend, last.chars).await;
+        if let Some(cmd) = &output_config.post_output_command {
+            if let Err(e) = output::run_hook(cmd, "post_output").await {
+                tracing::warn!("{}", e);
+            }
+        }
+
+        match result {
+            Ok(()) => {
+                self.emit(Event::Undone { chars: last.chars });
+                Ok(())
+            }
+            Err(e) => Err(format!("Undo failed: {}", e)),
+        }
+    }
+
+    /// Output the most recent transcription again
+    /// Falls back to the newest history entry after a daemon restart
+    async fn replay(
+        &mut self,
+        state: &mut State,
+        mode_override: Option<OutputMode>,
+    ) -> std::result::Result<(), String> {
+        if !state.is_idle() {
+            return Err(format!("Cannot replay while {}", state.name()));
         }
+
+        let text = match (self.last_text.clone(), self.history.clone()) {
+            (Some(text), _) => text,
+            (None, Some(history)) => tokio::task::spawn_blocking(move || history.get(None))
+                .await
+                .ok()
+                .and_then(|entry| entry.ok().flatten())
+                .map(|entry| entry.final_text)
+                .ok_or("Nothing to replay")?,
+            (None, None) => return Err("Nothing to replay".to_string()),
+        };
+
+        tracing::info!("Replaying last transcription");
+        let app_rule = self.focused_app_rule().await;
+        let result = self
+            .output_text(state, &text, mode_override, app_rule.as_ref())
+            .await;
+        *state = State::Idle;
+        result.map(|_| ())
     }
 
     /// Handle transcription completion (called when transcription_task completes)
     async fn handle_transcription_result(
-        &self,
+        &mut self,
         state: &mut State,
         result: std::result::Result<TranscriptionResult, tokio::task::JoinError>,
     ) {
         match result {
             Ok(Ok(text)) => {
+                let duration_secs = match state {
+                    State::Transcribing { audio } => audio.len() as f32 / 16000.0,
+                    _ => 0.0,
+                };
+
                 if text.is_empty() {
                     tracing::debug!("Transcription was empty");
                     self.reset_to_idle(state).await;
+                } else if self.active_text_processor().is_undo_command(&text) {
+                    tracing::info!("Undo command: {:?}", text);
+                    if let Err(e) = self.undo().await {
+                        tracing::warn!("{}", e);
+                        self.report_error(&e);
+                    }
+                    self.reset_to_idle(state).await;
                 } else {
                     tracing::info!("Transcribed: {:?}", text);
 
+                    // An app rule may pick another profile for the focused app
+                    let app_rule = self.focused_app_rule().await;
+                    let rule_profile = self.rule_profile(app_rule.as_ref());
+
                     // Apply text processing (replacements, punctuation)
-                    let processed_text = self.text_processor.process(&text);
+                    let processed_text = match rule_profile {
+                        Some(profile) => profile.text_processor.process(&text),
+                        None => self.active_text_processor().process(&text),
+                    };
                     if processed_text != text {
                         tracing::debug!("After text processing: {:?}", processed_text);
                     }
 
                     // Apply post-processing command if configured
-                    let final_text = if let Some(ref post_processor) = self.post_processor {
+                    let post_processor = match rule_profile {
+                        Some(profile) => profile.post_processor.as_ref(),
+                        None => self.active_post_processor(),
+                    };
+                    let final_text = if let Some(post_processor) = post_processor {
                         tracing::info!("Post-processing: {:?}", processed_text);
                         let result = post_processor.process(&processed_text).await;
                         tracing::info!("Post-processed: {:?}", result);
                         result
                     } else {
-                        processed_text
-                    };
-
-                    // Create output chain with potential override
-                    let output_config = if let Some(mode_override) = read_output_mode_override() {
-                        let mut config = self.config.output.clone();
-                        config.mode = mode_override;
-                        config
-                    } else {
-                        self.config.output.clone()
+                        processed_text.clone()
                     };
-                    let output_chain = output::create_output_chain(&output_config);
 
-                    // Output the text
-                    *state = State::Outputting {
+                    let latency = self
+                        .transcription_started
+                        .take()
+                        .map(|started| started.elapsed())
+                        .unwrap_or_default();
+                    self.emit(Event::TranscriptionFinished {
                         text: final_text.clone(),
-                    };
+                        latency_ms: latency.as_millis() as u64,
+                    });
 
-                    let output_options = output::OutputOptions {
-                        pre_output_command: output_config.pre_output_command.as_deref(),
-                        post_output_command: output_config.post_output_command.as_deref(),
+                    // Create output chain with potential override
+                    // (control socket request first, then legacy override file)
+                    let mode_override = match self.output_mode_override.take() {
+                        Some(mode) => {
+                            cleanup_output_mode_override();
+                            tracing::info!("Using output mode override: {:?}", mode);
+                            Some(mode)
+                        }
+                        None => read_output_mode_override(),
                     };
-
-                    if let Err(e) =
-                        output::output_with_fallback(&output_chain, &final_text, output_options)
-                            .await
-                    {
-                        tracing::error!("Output failed: {}", e);
-                    }
-
+                    let output_method = self
+                        .output_text(state, &final_text, mode_override, app_rule.as_ref())
+                        .await
+                        .ok()
+                        .map(str::to_string);
+                    self.last_text = Some(final_text.clone());
+
+                    self.record_history(HistoryEntry {
+                        id: 0,
+                        timestamp: history::unix_now(),
+                        raw_text: text,
+                        processed_text,
+                        final_text,
+                        model: self.model_name().to_string(),
+                        duration_secs,
+                        output_method,
+                    });
+
+                    self.active_profile = None;
+                    self.active_binding = None;
                     *state = State::Idle;
                     self.update_state("idle");
                 }
             }
             Ok(Err(e)) => {
                 tracing::error!("Transcription failed: {}", e);
+                let message = format!("Transcription failed: {}", e);
+                self.emit(Event::Error {
+                    message: message.clone(),
+                });
+                self.resolve_text_waiter(Err(message));
                 self.reset_to_idle(state).await;
             }
             Err(e) => {
@@ -405,6 +1403,198 @@ impl Daemon {
         }
     }
 
+    /// Re-read the configuration and apply it without restarting
+    /// The hotkey listener is only recreated if `[hotkey]` changed and the
+    /// model is only reloaded if `[whisper]` changed. If anything in the new
+    /// configuration is invalid, it is rejected and the current one kept.
+    async fn reload_config(
+        &mut self,
+        state: &State,
+        hotkey_listener: &mut Option<Box<dyn HotkeyListener>>,
+        hotkey_rx: &mut Option<tokio::sync::mpsc::Receiver<HotkeyEvent>>,
+        transcriber_preloaded: &mut Option<SharedTranscriber>,
+    ) {
+        let Some(ref loader) = self.config_loader else {
+            tracing::debug!("Config reload not available");
+            return;
+        };
+
+        let new_text = self
+            .config_path
+            .as_ref()
+            .and_then(|path| std::fs::read_to_string(path).ok())
+            .unwrap_or_default();
+        let config = match loader() {
+            Ok(config) => config,
+            Err(e) => {
+                self.reject_config(&e.to_string(), &new_text);
+                return;
+            }
+        };
+
+        let changed = reload::changed_sections(&self.config, &config);
+        if changed.is_empty() {
+            tracing::info!("Configuration unchanged");
+            self.config_text = new_text;
+            return;
+        }
+        let has_changed = |section: &str| changed.iter().any(|s| s == section);
+
+        // Prepare the parts that can fail before replacing anything
+        let mut new_transcriber = None;
+        if has_changed("whisper") && !config.whisper.on_demand_loading {
+            tracing::info!("Loading transcription model: {}", config.whisper.model);
+            let whisper = config.whisper.clone();
+            let loaded =
+                tokio::task::spawn_blocking(move || transcribe::create_transcriber(&whisper))
+                    .await
+                    .unwrap_or_else(|e| Err(TranscribeError::InitFailed(e.to_string())));
+            match loaded {
+                Ok(transcriber) => new_transcriber = Some(Arc::new(transcriber)),
+                Err(e) => {
+                    self.reject_config(&e.to_string(), &new_text);
+                    return;
+                }
+            }
+        }
+
+        let mut profiles = create_profiles(&config);
+        let mut binding_profiles = create_binding_profiles(&config);
+        let loaded = load_profile_models(
+            profiles.values_mut().chain(binding_profiles.values_mut()),
+            self.profiles.values().chain(self.binding_profiles.values()),
+        )
+        .await;
+        if let Err(e) = loaded {
+            self.reject_config(&e.to_string(), &new_text);
+            return;
+        }
+
+        // The hotkey goes last: the old listener has to release its devices
+        // (and their grabs) before the new one can open them
+        let mut new_hotkey = None;
+        if has_changed("hotkey") {
+            let mut old_listener = hotkey_listener.take();
+            if let Some(listener) = old_listener.as_mut() {
+                if let Err(e) = listener.stop().await {
+                    tracing::warn!("Failed to stop hotkey listener: {}", e);
+                }
+            }
+            *hotkey_rx = None;
+
+            if config.hotkey.enabled {
+                let started = match hotkey::create_listener(&config.hotkey) {
+                    Ok(mut listener) => listener.start().await.map(|rx| (listener, rx)),
+                    Err(e) => Err(e),
+                };
+                match started {
+                    Ok(hotkey) => new_hotkey = Some(hotkey),
+                    Err(e) => {
+                        // Bring the old hotkeys back
+                        if let Some(mut listener) = old_listener {
+                            match listener.start().await {
+                                Ok(rx) => {
+                                    *hotkey_listener = Some(listener);
+                                    *hotkey_rx = Some(rx);
+                                }
+                                Err(e) => tracing::error!("Failed to restart hotkey: {}", e),
+                            }
+                        }
+                        self.reject_config(&e.to_string(), &new_text);
+                        return;
+                    }
+                }
+            }
+        }
+
+        // Apply the new configuration
+        self.profiles = profiles;
+        self.binding_profiles = binding_profiles;
+        if has_changed("hotkey") {
+            self.remap_active_binding(&config);
+            if let Some((listener, rx)) = new_hotkey {
+                tracing::info!("Hotkey: {}", config.hotkey.key);
+                *hotkey_listener = Some(listener);
+                *hotkey_rx = Some(rx);
+            } else {
+                tracing::info!("Built-in hotkey disabled");
+            }
+        }
+        if has_changed("whisper") {
+            *transcriber_preloaded = new_transcriber;
+            if transcriber_preloaded.is_none() {
+                tracing::info!(
+                    "On-demand loading enabled, model will be loaded when recording starts"
+                );
+            }
+        }
+        if has_changed("audio") {
+            self.audio_feedback = create_audio_feedback(&config);
+        }
+        if has_changed("text") {
+            self.text_processor = create_text_processor(&config);
+        }
+        if has_changed("output") {
+            self.post_processor = create_post_processor(&config);
+        }
+        if has_changed("history") {
+            self.history = create_history(&config);
+        }
+        if has_changed("state_file") {
+            if let Some(ref path) = self.state_file_path {
+                cleanup_state_file(path);
+            }
+            self.state_file_path = config.resolve_state_file();
+            if let Some(ref path) = self.state_file_path {
+                write_state_file(path, state.name());
+            }
+        }
+
+        self.config = config;
+        self.config_text = new_text;
+        tracing::info!("Configuration reloaded ({} changed)", changed.join(", "));
+    }
+
+    /// Point the current recording's hotkey binding into the new configuration
+    /// Binding indices shift when [[hotkey.bindings]] change; a binding that
+    /// was removed or edited no longer controls the recording.
+    fn remap_active_binding(&mut self, config: &Config) {
+        let Some(index) = self.active_binding else {
+            return;
+        };
+        let binding = self.config.hotkey.all_bindings().into_iter().nth(index);
+        self.active_binding = binding.as_ref().and_then(|binding| {
+            config
+                .hotkey
+                .all_bindings()
+                .iter()
+                .position(|new| new == binding)
+        });
+        if self.active_binding.is_none() {
+            tracing::warn!(
+                "Hotkey of the current recording was changed, stop it with `voxtype record stop`"
+            );
+        }
+    }
+
+    /// Log why a new configuration was rejected, with the changes that caused it
+    fn reject_config(&self, error: &str, new_text: &str) {
+        tracing::error!(
+            "Config reload failed, keeping current configuration: {}",
+            error
+        );
+        let diff = reload::line_diff(&self.config_text, new_text);
+        if !diff.is_empty() {
+            tracing::error!("Changes since the last good configuration:");
+            for line in diff {
+                tracing::error!("  {}", line);
+            }
+        }
+        self.emit(Event::Error {
+            message: format!("Config reload failed: {}", error),
+        });
+    }
+
     /// Run the daemon main loop
     pub async fn run(&mut self) -> Result<()> {
         tracing::info!("Starting voxtype daemon");
@@ -425,12 +1615,40 @@ impl Daemon {
         let mut sigterm = signal(SignalKind::terminate()).map_err(|e| {
             crate::error::VoxtypeError::Config(format!("Failed to set up SIGTERM handler: {}", e))
         })?;
+        let mut sighup = signal(SignalKind::hangup()).map_err(|e| {
+            crate::error::VoxtypeError::Config(format!("Failed to set up SIGHUP handler: {}", e))
+        })?;
 
         // Ensure required directories exist
         Config::ensure_directories().map_err(|e| {
             crate::error::VoxtypeError::Config(format!("Failed to create directories: {}", e))
         })?;
 
+        // Requests from the control socket and D-Bus are handled in the main loop
+        let (request_tx, mut request_rx) = ipc::server::request_channel();
+
+        // Listen on the control socket (used by `voxtype record`)
+        let mut control_server = ControlServer::new(ipc::socket_path(), self.events.clone());
+        match control_server.start(request_tx.clone()).await {
+            Ok(()) => tracing::info!("Control socket: {:?}", control_server.path()),
+            Err(e) => tracing::warn!(
+                "Failed to start control socket: {} (only signals will work)",
+                e
+            ),
+        }
+
+        // Register on the session bus (for desktop integrations)
+        let dbus_service = match DbusService::start(request_tx.clone(), &self.events).await {
+            Ok(service) => {
+                tracing::info!("D-Bus service: {}", dbus::BUS_NAME);
+                Some(service)
+            }
+            Err(e) => {
+                tracing::warn!("Failed to start D-Bus service: {}", e);
+                None
+            }
+        };
+
         tracing::info!("Output mode: {:?}", self.config.output.mode);
 
         // Log state file if configured
@@ -472,6 +1690,13 @@ impl Daemon {
         } else {
             tracing::info!("On-demand loading enabled, model will be loaded when recording starts");
         }
+        load_profile_models(
+            self.profiles
+                .values_mut()
+                .chain(self.binding_profiles.values_mut()),
+            std::iter::empty(),
+        )
+        .await?;
 
         // Start hotkey listener (if enabled)
         let mut hotkey_rx = if let Some(ref mut listener) = hotkey_listener {
@@ -480,28 +1705,51 @@ impl Daemon {
             None
         };
 
+        // Reload the configuration when the file is saved
+        let mut config_watcher = match self.config_path {
+            Some(ref path) if self.config_loader.is_some() => match ConfigWatcher::new(path) {
+                Ok(watcher) => {
+                    tracing::info!("Watching {:?} for changes", path);
+                    Some(watcher)
+                }
+                Err(e) => {
+                    tracing::warn!("Failed to watch config file: {} (reload with SIGHUP)", e);
+                    None
+                }
+            },
+            _ => None,
+        };
+
         // Current state
         let mut state = State::Idle;
 
         // Audio capture (created fresh for each recording)
         let mut audio_capture: Option<Box<dyn AudioCapture>> = None;
 
-        // Recording timeout
-        let max_duration = Duration::from_secs(self.config.audio.max_duration_secs as u64);
-
-        let activation_mode = self.config.hotkey.mode;
         if self.config.hotkey.enabled {
-            let mode_desc = match activation_mode {
+            let mode_desc = match self.config.hotkey.mode {
                 ActivationMode::PushToTalk => "hold to record, release to transcribe",
                 ActivationMode::Toggle => "press to start/stop recording",
+                ActivationMode::Hybrid => "hold to record, or tap to start/stop recording",
             };
             tracing::info!(
                 "Listening for hotkey: {} ({})",
                 self.config.hotkey.key,
                 mode_desc
             );
+            for binding in &self.config.hotkey.bindings {
+                tracing::info!(
+                    "Hotkey {}: {}",
+                    binding,
+                    describe_binding(binding, self.config.hotkey.mode)
+                );
+            }
         }
 
+        // Input level reporting (peak RMS since the last level event)
+        let mut level_peak = 0.0f32;
+        let mut last_level_event = std::time::Instant::now();
+
         // Write initial state
         self.update_state("idle");
 
@@ -515,313 +1763,233 @@ impl Daemon {
                         None => std::future::pending().await,
                     }
                 } => {
-                    match (hotkey_event, activation_mode) {
+                    let (index, pressed, held) = match hotkey_event {
+                        HotkeyEvent::Pressed { binding } => (binding, true, Duration::ZERO),
+                        HotkeyEvent::Released { binding, held } => (binding, false, held),
+                    };
+                    let Some(binding) = self.config.hotkey.all_bindings().into_iter().nth(index) else {
+                        tracing::debug!("Ignoring event for unknown hotkey binding {}", index);
+                        continue;
+                    };
+                    let mode = binding.mode.unwrap_or(self.config.hotkey.mode);
+
+                    match (binding.action, mode, pressed) {
                         // === PUSH-TO-TALK MODE ===
-                        (HotkeyEvent::Pressed, ActivationMode::PushToTalk) => {
-                            tracing::debug!("Received HotkeyEvent::Pressed (push-to-talk), state.is_idle() = {}", state.is_idle());
+                        (HotkeyAction::Record, ActivationMode::PushToTalk, true) => {
+                            tracing::debug!("Hotkey {} pressed (push-to-talk), state.is_idle() = {}", binding, state.is_idle());
                             if state.is_idle() {
-                                tracing::info!("Recording started");
-
-                                // Send notification if enabled
-                                if self.config.output.notification.on_recording_start {
-                                    send_notification("Push to Talk Active", "Recording...").await;
-                                }
-
-                                // Start model loading in background if on-demand loading is enabled
-                                if self.config.whisper.on_demand_loading {
-                                    let config = self.config.whisper.clone();
-                                    self.model_load_task = Some(tokio::task::spawn_blocking(move || {
-                                        transcribe::create_transcriber(&config)
-                                    }));
-                                    tracing::debug!("Started background model loading");
-                                } else if let Some(ref t) = transcriber_preloaded {
-                                    // For gpu_isolation mode: prepare the subprocess now
-                                    // (spawns worker and loads model while user speaks)
-                                    let transcriber = t.clone();
-                                    tokio::task::spawn_blocking(move || {
-                                        transcriber.prepare();
-                                    });
-                                }
-
-                                // Create and start audio capture
-                                tracing::debug!("Creating audio capture with device: {}", self.config.audio.device);
-                                match audio::create_capture(&self.config.audio) {
-                                    Ok(mut capture) => {
-                                        tracing::debug!("Audio capture created, starting...");
-                                        if let Err(e) = capture.start().await {
-                                            tracing::error!("Failed to start audio: {}", e);
-                                            continue;
-                                        }
-                                        tracing::debug!("Audio capture started successfully");
-                                        audio_capture = Some(capture);
-                                        state = State::Recording {
-                                            started_at: std::time::Instant::now(),
-                                        };
-                                        self.update_state("recording");
-                                        self.play_feedback(SoundEvent::RecordingStart);
-
-                                        // Run pre-recording hook (e.g., enter compositor submap for cancel)
-                                        if let Some(cmd) = &self.config.output.pre_recording_command {
-                                            if let Err(e) = output::run_hook(cmd, "pre_recording").await {
-                                                tracing::warn!("{}", e);
-                                            }
-                                        }
-                                    }
-                                    Err(e) => {
-                                        tracing::error!("Failed to create audio capture: {}", e);
-                                        self.play_feedback(SoundEvent::Error);
-                                    }
-                                }
+                                self.start_hotkey_recording(
+                                    index,
+                                    &binding,
+                                    mode,
+                                    &mut state,
+                                    &mut audio_capture,
+                                    transcriber_preloaded.as_ref(),
+                                ).await;
                             }
                         }
 
-                        (HotkeyEvent::Released, ActivationMode::PushToTalk) => {
-                            tracing::debug!("Received HotkeyEvent::Released (push-to-talk), state.is_recording() = {}", state.is_recording());
-                            if state.is_recording() {
-                                // Wait for model loading task if on-demand loading is enabled
-                                let transcriber = if self.config.whisper.on_demand_loading {
-                                    if let Some(task) = self.model_load_task.take() {
-                                        match task.await {
-                                            Ok(Ok(transcriber)) => {
-                                                tracing::info!("Model loaded successfully");
-                                                Some(Arc::new(transcriber))
-                                            }
-                                            Ok(Err(e)) => {
-                                                tracing::error!("Model loading failed: {}", e);
-                                                self.play_feedback(SoundEvent::Error);
-                                                state = State::Idle;
-                                                self.update_state("idle");
-                                                continue;
-                                            }
-                                            Err(e) => {
-                                                tracing::error!("Model loading task panicked: {}", e);
-                                                self.play_feedback(SoundEvent::Error);
-                                                state = State::Idle;
-                                                self.update_state("idle");
-                                                continue;
-                                            }
-                                        }
-                                    } else {
-                                        tracing::error!("No model loading task found");
-                                        self.play_feedback(SoundEvent::Error);
-                                        state = State::Idle;
-                                        self.update_state("idle");
-                                        continue;
-                                    }
-                                } else {
-                                    transcriber_preloaded.clone()
-                                };
-
-                                self.start_transcription_task(
+                        (HotkeyAction::Record, ActivationMode::PushToTalk, false) => {
+                            tracing::debug!("Hotkey {} released (push-to-talk), state.is_recording() = {}", binding, state.is_recording());
+                            // Only the hotkey held for this recording ends it
+                            if state.is_recording() && self.active_binding == Some(index) {
+                                let _ = self.stop_recording(
                                     &mut state,
                                     &mut audio_capture,
-                                    transcriber,
+                                    transcriber_preloaded.as_ref(),
                                 ).await;
                             }
                         }
 
                         // === TOGGLE MODE ===
-                        (HotkeyEvent::Pressed, ActivationMode::Toggle) => {
-                            tracing::debug!("Received HotkeyEvent::Pressed (toggle), state.is_idle() = {}, state.is_recording() = {}",
-                                state.is_idle(), state.is_recording());
+                        (HotkeyAction::Record, ActivationMode::Toggle, true) => {
+                            tracing::debug!("Hotkey {} pressed (toggle), state.is_idle() = {}, state.is_recording() = {}",
+                                binding, state.is_idle(), state.is_recording());
 
                             if state.is_idle() {
-                                // Start recording
-                                tracing::info!("Recording started (toggle mode)");
-
-                                if self.config.output.notification.on_recording_start {
-                                    send_notification("Recording Started", "Press hotkey again to stop").await;
-                                }
-
-                                // Start model loading in background if on-demand loading is enabled
-                                if self.config.whisper.on_demand_loading {
-                                    let config = self.config.whisper.clone();
-                                    self.model_load_task = Some(tokio::task::spawn_blocking(move || {
-                                        transcribe::create_transcriber(&config)
-                                    }));
-                                    tracing::debug!("Started background model loading");
-                                } else if let Some(ref t) = transcriber_preloaded {
-                                    // For gpu_isolation mode: prepare the subprocess now
-                                    // (spawns worker and loads model while user speaks)
-                                    let transcriber = t.clone();
-                                    tokio::task::spawn_blocking(move || {
-                                        transcriber.prepare();
-                                    });
-                                }
-
-                                match audio::create_capture(&self.config.audio) {
-                                    Ok(mut capture) => {
-                                        if let Err(e) = capture.start().await {
-                                            tracing::error!("Failed to start audio: {}", e);
-                                            self.play_feedback(SoundEvent::Error);
-                                            continue;
-                                        }
-                                        audio_capture = Some(capture);
-                                        state = State::Recording {
-                                            started_at: std::time::Instant::now(),
-                                        };
-                                        self.update_state("recording");
-                                        self.play_feedback(SoundEvent::RecordingStart);
-
-                                        // Run pre-recording hook (e.g., enter compositor submap for cancel)
-                                        if let Some(cmd) = &self.config.output.pre_recording_command {
-                                            if let Err(e) = output::run_hook(cmd, "pre_recording").await {
-                                                tracing::warn!("{}", e);
-                                            }
-                                        }
-                                    }
-                                    Err(e) => {
-                                        tracing::error!("Failed to create audio capture: {}", e);
-                                        self.play_feedback(SoundEvent::Error);
-                                    }
-                                }
+                                self.start_hotkey_recording(
+                                    index,
+                                    &binding,
+                                    mode,
+                                    &mut state,
+                                    &mut audio_capture,
+                                    transcriber_preloaded.as_ref(),
+                                ).await;
                             } else if state.is_recording() {
-                                // Wait for model loading task if on-demand loading is enabled
-                                let transcriber = if self.config.whisper.on_demand_loading {
-                                    if let Some(task) = self.model_load_task.take() {
-                                        match task.await {
-                                            Ok(Ok(transcriber)) => {
-                                                tracing::info!("Model loaded successfully");
-                                                Some(Arc::new(transcriber))
-                                            }
-                                            Ok(Err(e)) => {
-                                                tracing::error!("Model loading failed: {}", e);
-                                                self.play_feedback(SoundEvent::Error);
-                                                state = State::Idle;
-                                                self.update_state("idle");
-                                                continue;
-                                            }
-                                            Err(e) => {
-                                                tracing::error!("Model loading task panicked: {}", e);
-                                                self.play_feedback(SoundEvent::Error);
-                                                state = State::Idle;
-                                                self.update_state("idle");
-                                                continue;
-                                            }
-                                        }
-                                    } else {
-                                        tracing::error!("No model loading task found");
-                                        self.play_feedback(SoundEvent::Error);
-                                        state = State::Idle;
-                                        self.update_state("idle");
-                                        continue;
-                                    }
-                                } else {
-                                    transcriber_preloaded.clone()
-                                };
-
                                 // Stop recording and start transcription
-                                self.start_transcription_task(
+                                let _ = self.stop_recording(
                                     &mut state,
                                     &mut audio_capture,
-                                    transcriber,
+                                    transcriber_preloaded.as_ref(),
                                 ).await;
                             }
                         }
 
-                        (HotkeyEvent::Released, ActivationMode::Toggle) => {
+                        (HotkeyAction::Record, ActivationMode::Toggle, false) => {
                             // In toggle mode, we ignore key release events
-                            tracing::trace!("Ignoring HotkeyEvent::Released in toggle mode");
+                            tracing::trace!("Ignoring release of hotkey {} in toggle mode", binding);
                         }
 
-                        // === CANCEL KEY (works in both modes) ===
-                        (HotkeyEvent::Cancel, _) => {
-                            tracing::debug!("Received HotkeyEvent::Cancel");
-
-                            if state.is_recording() {
-                                tracing::info!("Recording cancelled via hotkey");
-
-                                // Stop recording and discard audio
-                                if let Some(mut capture) = audio_capture.take() {
-                                    let _ = capture.stop().await;
-                                }
-
-                                // Cancel any pending model load task
-                                if let Some(task) = self.model_load_task.take() {
-                                    task.abort();
-                                }
+                        // === HYBRID MODE ===
+                        (HotkeyAction::Record, ActivationMode::Hybrid, true) => {
+                            tracing::debug!("Hotkey {} pressed (hybrid), state.is_idle() = {}, state.is_recording() = {}",
+                                binding, state.is_idle(), state.is_recording());
 
-                                cleanup_output_mode_override();
-                                state = State::Idle;
-                                self.update_state("idle");
-                                self.play_feedback(SoundEvent::Cancelled);
+                            if state.is_idle() {
+                                self.start_hotkey_recording(
+                                    index,
+                                    &binding,
+                                    mode,
+                                    &mut state,
+                                    &mut audio_capture,
+                                    transcriber_preloaded.as_ref(),
+                                ).await;
+                            } else if state.is_recording() {
+                                // The tap that ends a latched recording
+                                let _ = self.stop_recording(
+                                    &mut state,
+                                    &mut audio_capture,
+                                    transcriber_preloaded.as_ref(),
+                                ).await;
+                            }
+                        }
 
-                                // Run post_output_command to reset compositor submap
-                                if let Some(cmd) = &self.config.output.post_output_command {
-                                    if let Err(e) = output::run_hook(cmd, "post_output").await {
-                                        tracing::warn!("{}", e);
+                        (HotkeyAction::Record, ActivationMode::Hybrid, false) => {
+                            tracing::debug!("Hotkey {} released after {:?} (hybrid), state.is_recording() = {}",
+                                binding, held, state.is_recording());
+                            if state.is_recording() && self.active_binding == Some(index) {
+                                let tap_threshold = Duration::from_millis(self.config.hotkey.tap_threshold_ms as u64);
+                                if held < tap_threshold {
+                                    // Short tap: keep recording until the next tap
+                                    tracing::info!("Hotkey tapped, recording until the next tap");
+                                    if self.config.audio.vad.enabled && self.vad.is_none() {
+                                        self.vad = Some(VoiceActivityDetector::new(&self.config.audio.vad));
                                     }
+                                } else {
+                                    // Held: push-to-talk
+                                    let _ = self.stop_recording(
+                                        &mut state,
+                                        &mut audio_capture,
+                                        transcriber_preloaded.as_ref(),
+                                    ).await;
                                 }
+                            }
+                        }
 
-                                if self.config.output.notification.on_recording_stop {
-                                    send_notification("Cancelled", "Recording discarded").await;
-                                }
-                            } else if matches!(state, State::Transcribing { .. }) {
-                                tracing::info!("Transcription cancelled via hotkey");
-
-                                // Abort the transcription task
-                                if let Some(task) = self.transcription_task.take() {
-                                    task.abort();
-                                }
-
-                                cleanup_output_mode_override();
-                                state = State::Idle;
-                                self.update_state("idle");
-                                self.play_feedback(SoundEvent::Cancelled);
-
-                                // Run post_output_command to reset compositor submap
-                                if let Some(cmd) = &self.config.output.post_output_command {
-                                    if let Err(e) = output::run_hook(cmd, "post_output").await {
-                                        tracing::warn!("{}", e);
-                                    }
+                        // === UNDO KEY (works in both modes) ===
+                        (HotkeyAction::Undo, _, true) => {
+                            tracing::debug!("Undo hotkey {} pressed", binding);
+                            if state.is_idle() {
+                                if let Err(e) = self.undo().await {
+                                    tracing::warn!("{}", e);
+                                    self.report_error(&e);
                                 }
+                            }
+                        }
 
-                                if self.config.output.notification.on_recording_stop {
-                                    send_notification("Cancelled", "Transcription aborted").await;
-                                }
-                            } else {
+                        // === CANCEL KEY (works in both modes) ===
+                        (HotkeyAction::Cancel, _, true) => {
+                            tracing::debug!("Cancel hotkey {} pressed", binding);
+                            if !self.cancel(&mut state, &mut audio_capture).await {
                                 tracing::trace!("Cancel ignored - not recording or transcribing");
                             }
                         }
+
+                        (HotkeyAction::Undo | HotkeyAction::Cancel, _, false) => {}
                     }
                 }
 
-                // Check for recording timeout and cancel requests
-                _ = tokio::time::sleep(Duration::from_millis(100)), if state.is_recording() => {
-                    // Check for cancel request first
-                    if check_cancel_requested() {
-                        tracing::info!("Recording cancelled");
+                // Handle requests from the control socket and D-Bus
+                Some(mut pending) = request_rx.recv() => {
+                    let text_waiter = pending.take_waiter();
+                    let response = self.handle_control_request(
+                        pending.request.clone(),
+                        text_waiter,
+                        &mut state,
+                        &mut audio_capture,
+                        transcriber_preloaded.as_ref(),
+                    ).await;
+                    pending.respond(response);
+                }
 
-                        // Stop recording and discard audio
-                        if let Some(mut capture) = audio_capture.take() {
-                            let _ = capture.stop().await;
-                        }
+                // Publish input levels and run VAD while recording
+                Some(chunk) = async {
+                    match self.audio_chunks.as_mut() {
+                        Some(rx) => rx.recv().await,
+                        None => std::future::pending().await,
+                    }
+                } => {
+                    level_peak = level_peak.max(audio::rms_level(&chunk));
+                    if last_level_event.elapsed() >= AUDIO_LEVEL_INTERVAL {
+                        self.emit(Event::AudioLevel { level: level_peak });
+                        level_peak = 0.0;
+                        last_level_event = std::time::Instant::now();
+                    }
 
-                        // Cancel any pending model load task
-                        if let Some(task) = self.model_load_task.take() {
-                            task.abort();
+                    // Transcribe the recording so far in the background (streaming mode)
+                    let transcriber = self.active_transcriber(transcriber_preloaded.as_ref()).cloned();
+                    if let (Some(streaming), Some(t)) = (self.streaming.as_mut(), transcriber) {
+                        streaming.push(&chunk);
+                        if self.partial_task.is_none() {
+                            if let Some(window) = streaming.next_window() {
+                                let t = t.clone();
+                                self.partial_task = Some(tokio::task::spawn_blocking(move || {
+                                    let result = t.transcribe_segments(&window.samples);
+                                    (window, result)
+                                }));
+                            }
                         }
+                    }
 
-                        cleanup_output_mode_override();
-                        state = State::Idle;
-                        self.update_state("idle");
-                        self.play_feedback(SoundEvent::Cancelled);
+                    // End the recording on trailing silence (hands-free mode)
+                    let silence = self.vad.as_mut().is_some_and(|vad| vad.process(&chunk));
+                    if silence && state.is_recording() {
+                        tracing::info!("Silence detected, stopping recording");
+                        let _ = self.stop_recording(
+                            &mut state,
+                            &mut audio_capture,
+                            transcriber_preloaded.as_ref(),
+                        ).await;
+                    }
+                }
 
-                        // Run post_output_command to reset compositor submap
-                        if let Some(cmd) = &self.config.output.post_output_command {
-                            if let Err(e) = output::run_hook(cmd, "post_output").await {
-                                tracing::warn!("{}", e);
-                            }
+                // Publish partial transcriptions (streaming mode)
+                result = async {
+                    match self.partial_task.as_mut() {
+                        Some(task) => task.await,
+                        None => std::future::pending().await,
+                    }
+                }, if self.partial_task.is_some() => {
+                    self.partial_task = None;
+                    let partial = self.streaming.as_mut().and_then(|streaming| match result {
+                        Ok((window, Ok(segments))) => Some(streaming.apply(&window, &segments)),
+                        Ok((_, Err(e))) => {
+                            tracing::debug!("Partial transcription failed: {}", e);
+                            streaming.abandon();
+                            None
                         }
-
-                        if self.config.output.notification.on_recording_stop {
-                            send_notification("Cancelled", "Recording discarded").await;
+                        Err(e) => {
+                            tracing::debug!("Partial transcription task failed: {}", e);
+                            streaming.abandon();
+                            None
                         }
+                    });
+                    if let Some(text) = partial.filter(|text| !text.is_empty()) {
+                        tracing::debug!("Partial: {:?}", text);
+                        self.emit(Event::PartialTranscription { text });
+                    }
+                }
 
+                // Check for recording timeout and cancel requests
+                _ = tokio::time::sleep(Duration::from_millis(100)), if state.is_recording() => {
+                    // Check for cancel request first
+                    if check_cancel_requested() {
+                        self.cancel(&mut state, &mut audio_capture).await;
                         continue;
                     }
 
                     // Check for recording timeout
+                    let max_duration = Duration::from_secs(self.config.audio.max_duration_secs as u64);
                     if let Some(duration) = state.recording_duration() {
                         if duration > max_duration {
                             if self.config.audio.transcribe_on_timeout {
@@ -830,54 +1998,11 @@ impl Daemon {
                                     max_duration.as_secs_f32()
                                 );
 
-                                // Get transcriber (same logic as normal stop)
-                                let transcriber = if self.config.whisper.on_demand_loading {
-                                    if let Some(task) = self.model_load_task.take() {
-                                        match task.await {
-                                            Ok(Ok(transcriber)) => {
-                                                tracing::info!("Model loaded successfully");
-                                                Some(Arc::new(transcriber))
-                                            }
-                                            Ok(Err(e)) => {
-                                                tracing::error!("Model loading failed: {}", e);
-                                                self.play_feedback(SoundEvent::Error);
-                                                if let Some(mut capture) = audio_capture.take() {
-                                                    let _ = capture.stop().await;
-                                                }
-                                                state = State::Idle;
-                                                self.update_state("idle");
-                                                continue;
-                                            }
-                                            Err(e) => {
-                                                tracing::error!("Model loading task panicked: {}", e);
-                                                self.play_feedback(SoundEvent::Error);
-                                                if let Some(mut capture) = audio_capture.take() {
-                                                    let _ = capture.stop().await;
-                                                }
-                                                state = State::Idle;
-                                                self.update_state("idle");
-                                                continue;
-                                            }
-                                        }
-                                    } else {
-                                        tracing::error!("No model loading task found");
-                                        self.play_feedback(SoundEvent::Error);
-                                        if let Some(mut capture) = audio_capture.take() {
-                                            let _ = capture.stop().await;
-                                        }
-                                        state = State::Idle;
-                                        self.update_state("idle");
-                                        continue;
-                                    }
-                                } else {
-                                    transcriber_preloaded.clone()
-                                };
-
                                 // Stop recording and start transcription
-                                self.start_transcription_task(
+                                let _ = self.stop_recording(
                                     &mut state,
                                     &mut audio_capture,
-                                    transcriber,
+                                    transcriber_preloaded.as_ref(),
                                 ).await;
                             } else {
                                 // Default behavior: discard audio on timeout
@@ -889,22 +2014,13 @@ impl Daemon {
                                 if let Some(mut capture) = audio_capture.take() {
                                     let _ = capture.stop().await;
                                 }
-                                cleanup_output_mode_override();
-                                state = State::Idle;
-                                self.update_state("idle");
-
-                                // Run post_output_command to reset compositor submap
-                                if let Some(cmd) = &self.config.output.post_output_command {
-                                    if let Err(e) = output::run_hook(cmd, "post_output").await {
-                                        tracing::warn!("{}", e);
-                                    }
-                                }
+                                self.reset_to_idle(&mut state).await;
                             }
                         }
                     }
                 }
 
-                // Handle SIGUSR1 - start recording (for compositor keybindings)
+                // Handle SIGUSR1 - start recording (legacy external trigger)
                 _ = sigusr1.recv() => {
                     tracing::debug!("Received SIGUSR1 (start recording)");
                     if state.is_idle() {
@@ -914,95 +2030,55 @@ impl Daemon {
                             send_notification("Recording Started", "External trigger").await;
                         }
 
-                        // Start model loading in background if on-demand loading is enabled
-                        if self.config.whisper.on_demand_loading {
-                            let config = self.config.whisper.clone();
-                            self.model_load_task = Some(tokio::task::spawn_blocking(move || {
-                                transcribe::create_transcriber(&config)
-                            }));
-                        } else if let Some(ref t) = transcriber_preloaded {
-                            // For gpu_isolation mode: prepare the subprocess now
-                            // (spawns worker and loads model while user speaks)
-                            let transcriber = t.clone();
-                            tokio::task::spawn_blocking(move || {
-                                transcriber.prepare();
-                            });
-                        }
-
-                        match audio::create_capture(&self.config.audio) {
-                            Ok(mut capture) => {
-                                if let Err(e) = capture.start().await {
-                                    tracing::error!("Failed to start audio: {}", e);
-                                } else {
-                                    audio_capture = Some(capture);
-                                    state = State::Recording {
-                                        started_at: std::time::Instant::now(),
-                                    };
-                                    self.update_state("recording");
-                                    self.play_feedback(SoundEvent::RecordingStart);
-
-                                    // Run pre-recording hook (e.g., enter compositor submap for cancel)
-                                    if let Some(cmd) = &self.config.output.pre_recording_command {
-                                        if let Err(e) = output::run_hook(cmd, "pre_recording").await {
-                                            tracing::warn!("{}", e);
-                                        }
-                                    }
-                                }
-                            }
-                            Err(e) => {
-                                tracing::error!("Failed to create audio capture: {}", e);
-                                self.play_feedback(SoundEvent::Error);
-                            }
-                        }
+                        let auto_stop = self.external_auto_stop();
+                        let _ = self.start_recording(
+                            &mut state,
+                            &mut audio_capture,
+                            transcriber_preloaded.as_ref(),
+                            auto_stop,
+                        ).await;
                     }
                 }
 
-                // Handle SIGUSR2 - stop recording (for compositor keybindings)
+                // Handle SIGUSR2 - stop recording (legacy external trigger)
                 _ = sigusr2.recv() => {
                     tracing::debug!("Received SIGUSR2 (stop recording)");
                     if state.is_recording() {
-                        // Wait for model loading task if on-demand loading is enabled
-                        let transcriber = if self.config.whisper.on_demand_loading {
-                            if let Some(task) = self.model_load_task.take() {
-                                match task.await {
-                                    Ok(Ok(transcriber)) => {
-                                        tracing::info!("Model loaded successfully");
-                                        Some(Arc::new(transcriber))
-                                    }
-                                    Ok(Err(e)) => {
-                                        tracing::error!("Model loading failed: {}", e);
-                                        self.play_feedback(SoundEvent::Error);
-                                        state = State::Idle;
-                                        self.update_state("idle");
-                                        continue;
-                                    }
-                                    Err(e) => {
-                                        tracing::error!("Model loading task panicked: {}", e);
-                                        self.play_feedback(SoundEvent::Error);
-                                        state = State::Idle;
-                                        self.update_state("idle");
-                                        continue;
-                                    }
-                                }
-                            } else {
-                                tracing::error!("No model loading task found");
-                                self.play_feedback(SoundEvent::Error);
-                                state = State::Idle;
-                                self.update_state("idle");
-                                continue;
-                            }
-                        } else {
-                            transcriber_preloaded.clone()
-                        };
-
-                        self.start_transcription_task(
+                        let _ = self.stop_recording(
                             &mut state,
                             &mut audio_capture,
-                            transcriber,
+                            transcriber_preloaded.as_ref(),
                         ).await;
                     }
                 }
 
+                // Handle SIGHUP - reload configuration (systemctl reload)
+                _ = sighup.recv() => {
+                    tracing::info!("Received SIGHUP, reloading configuration");
+                    self.reload_config(
+                        &state,
+                        &mut hotkey_listener,
+                        &mut hotkey_rx,
+                        &mut transcriber_preloaded,
+                    ).await;
+                }
+
+                // Reload configuration when the config file changes
+                _ = async {
+                    match config_watcher.as_mut() {
+                        Some(watcher) => watcher.changed().await,
+                        None => std::future::pending().await,
+                    }
+                } => {
+                    tracing::info!("Config file changed, reloading configuration");
+                    self.reload_config(
+                        &state,
+                        &mut hotkey_listener,
+                        &mut hotkey_rx,
+                        &mut transcriber_preloaded,
+                    ).await;
+                }
+
                 // Handle transcription task completion
                 result = async {
                     match self.transcription_task.as_mut() {
@@ -1017,28 +2093,7 @@ impl Daemon {
                 // Check for cancel during transcription
                 _ = tokio::time::sleep(Duration::from_millis(100)), if matches!(state, State::Transcribing { .. }) => {
                     if check_cancel_requested() {
-                        tracing::info!("Transcription cancelled");
-
-                        // Abort the transcription task
-                        if let Some(task) = self.transcription_task.take() {
-                            task.abort();
-                        }
-
-                        cleanup_output_mode_override();
-                        state = State::Idle;
-                        self.update_state("idle");
-                        self.play_feedback(SoundEvent::Cancelled);
-
-                        // Run post_output_command to reset compositor submap
-                        if let Some(cmd) = &self.config.output.post_output_command {
-                            if let Err(e) = output::run_hook(cmd, "post_output").await {
-                                tracing::warn!("{}", e);
-                            }
-                        }
-
-                        if self.config.output.notification.on_recording_stop {
-                            send_notification("Cancelled", "Transcription aborted").await;
-                        }
+                        self.cancel(&mut state, &mut audio_capture).await;
                     }
                 }
 
@@ -1067,6 +2122,13 @@ impl Daemon {
             listener.stop().await?;
         }
 
+        // Stop accepting control requests and remove the socket
+        control_server.stop();
+        if let Some(service) = dbus_service {
+            service.stop().await;
+        }
+        drop(request_tx);
+
         // Abort any pending transcription task
         if let Some(task) = self.transcription_task.take() {
             task.abort();
@@ -1088,6 +2150,42 @@ impl Daemon {
     }
 }
 
+/// Finish a streaming transcription
+/// Waits for the window in flight, then transcribes only the audio that
+/// hasn't been committed yet
+async fn finish_streaming(
+    mut streaming: StreamingTranscription,
+    partial_task: Option<tokio::task::JoinHandle<PartialResult>>,
+    samples: Vec<f32>,
+    transcriber: SharedTranscriber,
+    trim: TrimConfig,
+) -> TranscriptionResult {
+    if let Some(task) = partial_task {
+        if let Ok((window, Ok(segments))) = task.await {
+            streaming.apply(&window, &segments);
+        }
+    }
+
+    let mut tail = streaming.tail(&samples).to_vec();
+    if trim.enabled {
+        tail = audio::trim::trim_silence(&tail, &trim);
+    }
+    if tail.is_empty() {
+        return Ok(streaming.finish(""));
+    }
+
+    tracing::debug!(
+        "Transcribing final {:.1}s of {:.1}s (streaming)",
+        tail.len() as f32 / 16000.0,
+        samples.len() as f32 / 16000.0
+    );
+    let text = tokio::task::spawn_blocking(move || transcriber.transcribe(&tail))
+        .await
+        .map_err(|e| TranscribeError::InferenceFailed(e.to_string()))??;
+
+    Ok(streaming.finish(&text))
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -1254,4 +2352,110 @@ mod tests {
             // Should not panic
         });
     }
+
+    #[tokio::test]
+    async fn test_undo_refuses_clipboard_output() {
+        let mut daemon = Daemon::new(Config::default());
+        assert_eq!(daemon.undo().await.unwrap_err(), "Nothing to undo");
+
+        // Named "clipboard (wl-copy)" or "clipboard (X11)", depending on the session
+        let clipboard = output::clipboard::ClipboardOutput::new(false);
+        let mut config = daemon.config.output.clone();
+        config.auto_submit = true;
+        daemon.record_output(&clipboard, "hello", &config);
+        assert_eq!(
+            daemon.undo().await.unwrap_err(),
+            "Last transcription was only copied to the clipboard"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_reload_hotkey_keeps_grab() {
+        use crate::config::{DeviceFilter, GrabMode};
+        use crate::hotkey::evdev_listener::pedal::Pedal;
+
+        let name = "voxtype reload test pedal";
+        let Some(pedal) = Pedal::create(name, evdev::Key::KEY_F13) else {
+            eprintln!("uinput not available, skipping");
+            return;
+        };
+
+        let mut config = Config::default();
+        config.hotkey.key = "F13".to_string();
+        config.hotkey.grab = GrabMode::Exclusive;
+        config.hotkey.devices = vec![DeviceFilter {
+            name: Some(name.to_string()),
+            ..Default::default()
+        }];
+        let mut listener = hotkey::create_listener(&config.hotkey).unwrap();
+        let mut hotkey_rx = Some(listener.start().await.unwrap());
+        let mut hotkey_listener = Some(listener);
+        assert!(pedal.grabbed());
+
+        // The new listener takes over the grab from the old one
+        let next = Arc::new(std::sync::Mutex::new(config.clone()));
+        let loader = next.clone();
+        let mut daemon = Daemon::new(config.clone())
+            .with_config_reload(None, Box::new(move || Ok(loader.lock().unwrap().clone())));
+        next.lock().unwrap().hotkey.key = "F14".to_string();
+        daemon
+            .reload_config(
+                &State::Idle,
+                &mut hotkey_listener,
+                &mut hotkey_rx,
+                &mut None,
+            )
+            .await;
+        assert_eq!(daemon.config.hotkey.key, "F14");
+        assert!(hotkey_rx.is_some());
+        assert!(pedal.grabbed());
+
+        // Without a matching device the old listener is brought back
+        next.lock().unwrap().hotkey.devices[0].name = Some("no such device".to_string());
+        daemon
+            .reload_config(
+                &State::Idle,
+                &mut hotkey_listener,
+                &mut hotkey_rx,
+                &mut None,
+            )
+            .await;
+        assert_eq!(daemon.config.hotkey.devices[0].name.as_deref(), Some(name));
+        assert!(hotkey_rx.is_some());
+        assert!(pedal.grabbed());
+
+        hotkey_listener.unwrap().stop().await.unwrap();
+        assert!(!pedal.grabbed());
+    }
+
+    #[test]
+    fn test_reload_remaps_active_binding() {
+        let binding = |key: &str| HotkeyBinding {
+            key: key.to_string(),
+            modifiers: Vec::new(),
+            chord: Vec::new(),
+            double_tap: false,
+            mode: None,
+            action: HotkeyAction::Record,
+            output_mode: None,
+            language: Some("de".to_string()),
+            profile: None,
+        };
+        let mut config = Config::default();
+        config.hotkey.bindings = vec![binding("F9"), binding("F10")];
+        let mut daemon = Daemon::new(config.clone());
+        let f10 = daemon.config.hotkey.all_bindings().len() - 1;
+        daemon.active_binding = Some(f10);
+
+        // A binding inserted before it shifts its index
+        config.hotkey.bindings.insert(0, binding("F8"));
+        daemon.remap_active_binding(&config);
+        daemon.config = config.clone();
+        assert_eq!(daemon.active_binding, Some(f10 + 1));
+
+        // Once removed, it no longer controls the recording
+        config.hotkey.bindings.pop();
+        daemon.remap_active_binding(&config);
+        assert_eq!(daemon.active_binding, None);
+    }
 }
@@ -0,0 +1,301 @@
+//! D-Bus service interface
+//!
+//! Exposes the daemon on the session bus as `io.voxtype.Daemon` so desktop
+//! tools (GNOME extensions, KDE widgets, scripts) can control recording
+//! without shelling out to `voxtype record`.
+//!
+//! Method calls are forwarded to the daemon's main loop through the same
+//! request channel the control socket uses, so both frontends behave
+//! identically. Daemon events are re-published as D-Bus signals.
+//!
+//! ```text
+//! busctl --user call io.voxtype.Daemon /io/voxtype/Daemon io.voxtype.Daemon Toggle
+//! ```
+
+use crate::ipc::events::Event;
+use crate::ipc::server::{dispatch, PendingRequest};
+use crate::ipc::{Request, Response};
+use tokio::sync::{broadcast, mpsc};
+use tokio::task::JoinHandle;
+use zbus::object_server::SignalEmitter;
+use zbus::{fdo, interface, Connection};
+
+/// Well-known bus name owned by the daemon
+pub const BUS_NAME: &str = "io.voxtype.Daemon";
+
+/// Object path of the daemon object
+pub const OBJECT_PATH: &str = "/io/voxtype/Daemon";
+
+/// The `io.voxtype.Daemon` interface
+struct DaemonInterface {
+    requests: mpsc::Sender<PendingRequest>,
+}
+
+impl DaemonInterface {
+    /// Forward a request to the daemon, turning failures into D-Bus errors
+    async fn call(&self, request: Request) -> fdo::Result<String> {
+        match dispatch(&self.requests, request).await {
+            Response {
+                ok: true, state, ..
+            } => Ok(state.unwrap_or_default()),
+            Response { error, .. } => Err(fdo::Error::Failed(
+                error.unwrap_or_else(|| "Request failed".to_string()),
+            )),
+        }
+    }
+}
+
+#[interface(name = "io.voxtype.Daemon")]
+impl DaemonInterface {
+    /// Start recording
+    async fn start_recording(&self) -> fdo::Result<()> {
+        self.call(Request::Start {
+            output_mode: None,
+            profile: None,
+        })
+        .await?;
+        Ok(())
+    }
+
+    /// Start recording with a named profile
+    async fn start_recording_with_profile(&self, profile: String) -> fdo::Result<()> {
+        self.call(Request::Start {
+            output_mode: None,
+            profile: Some(profile),
+        })
+        .await?;
+        Ok(())
+    }
+
+    /// Stop recording and transcribe
+    async fn stop_recording(&self) -> fdo::Result<()> {
+        self.call(Request::Stop { output_mode: None }).await?;
+        Ok(())
+    }
+
+    /// Start recording if idle, stop if recording
+    async fn toggle(&self) -> fdo::Result<()> {
+        self.call(Request::Toggle {
+            output_mode: None,
+            profile: None,
+        })
+        .await?;
+        Ok(())
+    }
+
+    /// Cancel the current recording or transcription
+    async fn cancel(&self) -> fdo::Result<()> {
+        self.call(Request::Cancel).await?;
+        Ok(())
+    }
+
+    /// Erase the most recently typed transcription
+    async fn undo(&self) -> fdo::Result<()> {
+        self.call(Request::Undo).await?;
+        Ok(())
+    }
+
+    /// Output the most recent transcription again
+    async fn replay(&self) -> fdo::Result<()> {
+        self.call(Request::Replay { output_mode: None }).await?;
+        Ok(())
+    }
+
+    /// Get the current state ("idle", "recording", "transcribing")
+    async fn get_state(&self) -> fdo::Result<String> {
+        self.call(Request::Status).await
+    }
+
+    /// Emitted whenever the daemon state changes
+    #[zbus(signal)]
+    async fn state_changed(emitter: &SignalEmitter<'_>, state: &str) -> zbus::Result<()>;
+
+    /// Emitted when a transcription is ready, before it is output
+    #[zbus(signal)]
+    async fn transcription_ready(emitter: &SignalEmitter<'_>, text: &str) -> zbus::Result<()>;
+}
+
+/// Running D-Bus service
+pub struct DbusService {
+    connection: Connection,
+    signal_task: JoinHandle<()>,
+}
+
+impl DbusService {
+    /// Connect to the session bus and start serving
+    pub async fn start(
+        requests: mpsc::Sender<PendingRequest>,
+        events: &broadcast::Sender<Event>,
+    ) -> zbus::Result<Self> {
+        Self::serve(Connection::session().await?, requests, events).await
+    }
+
+    /// Serve the daemon object on an existing bus connection
+    pub async fn serve(
+        connection: Connection,
+        requests: mpsc::Sender<PendingRequest>,
+        events: &broadcast::Sender<Event>,
+    ) -> zbus::Result<Self> {
+        connection
+            .object_server()
+            .at(OBJECT_PATH, DaemonInterface { requests })
+            .await?;
+        connection.request_name(BUS_NAME).await?;
+
+        let signal_task = tokio::spawn(forward_signals(connection.clone(), events.subscribe()));
+
+        Ok(Self {
+            connection,
+            signal_task,
+        })
+    }
+
+    /// Stop emitting signals and release the bus name
+    pub async fn stop(self) {
+        self.signal_task.abort();
+        if let Err(e) = self.connection.release_name(BUS_NAME).await {
+            tracing::debug!("Failed to release D-Bus name: {}", e);
+        }
+    }
+}
+
+/// Re-publish daemon events as D-Bus signals
+async fn forward_signals(connection: Connection, mut events: broadcast::Receiver<Event>) {
+    let emitter = match SignalEmitter::new(&connection, OBJECT_PATH) {
+        Ok(emitter) => emitter,
+        Err(e) => {
+            tracing::warn!("Failed to set up D-Bus signals: {}", e);
+            return;
+        }
+    };
+
+    loop {
+        let result = match events.recv().await {
+            Ok(Event::StateChanged { state, .. }) => {
+                DaemonInterface::state_changed(&emitter, &state).await
+            }
+            Ok(Event::TranscriptionFinished { text, .. }) => {
+                DaemonInterface::transcription_ready(&emitter, &text).await
+            }
+            Ok(_) => Ok(()),
+            Err(broadcast::error::RecvError::Lagged(n)) => {
+                tracing::debug!("D-Bus signal forwarder lagged, skipped {} events", n);
+                Ok(())
+            }
+            Err(broadcast::error::RecvError::Closed) => break,
+        };
+
+        if let Err(e) = result {
+            tracing::warn!("Failed to emit D-Bus signal: {}", e);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ipc::events;
+    use crate::ipc::server::request_channel;
+    use futures_util::StreamExt;
+    use std::io::{BufRead, BufReader};
+    use std::process::{Child, Command, Stdio};
+
+    /// A private bus, killed when dropped
+    struct TestBus {
+        child: Child,
+        address: String,
+    }
+
+    impl TestBus {
+        /// Start a private session bus, or None if dbus-daemon isn't installed
+        fn start() -> Option<Self> {
+            let mut child = Command::new("dbus-daemon")
+                .args(["--session", "--nofork", "--print-address=1"])
+                .stdout(Stdio::piped())
+                .stderr(Stdio::null())
+                .spawn()
+                .ok()?;
+
+            let mut address = String::new();
+            BufReader::new(child.stdout.take()?)
+                .read_line(&mut address)
+                .ok()?;
+
+            Some(Self {
+                child,
+                address: address.trim().to_string(),
+            })
+        }
+
+        async fn connect(&self) -> Connection {
+            zbus::connection::Builder::address(self.address.as_str())
+                .unwrap()
+                .build()
+                .await
+                .unwrap()
+        }
+    }
+
+    impl Drop for TestBus {
+        fn drop(&mut self) {
+            let _ = self.child.kill();
+            let _ = self.child.wait();
+        }
+    }
+
+    #[tokio::test]
+    async fn test_methods_and_signals() {
+        let Some(bus) = TestBus::start() else {
+            eprintln!("dbus-daemon not found, skipping");
+            return;
+        };
+
+        let events = events::channel();
+        let (tx, mut rx) = request_channel();
+
+        // Stand-in for the daemon loop: only idle <-> recording
+        let daemon_events = events.clone();
+        tokio::spawn(async move {
+            let mut state = "idle";
+            while let Some(pending) = rx.recv().await {
+                let response = match (&pending.request, state) {
+                    (Request::Start { .. }, "idle") => {
+                        state = "recording";
+                        let _ = daemon_events.send(Event::StateChanged {
+                            state: state.to_string(),
+                            profile: None,
+                        });
+                        Response::success(state)
+                    }
+                    (Request::Stop { .. }, _) => Response::error("Not recording"),
+                    _ => Response::success(state),
+                };
+                pending.respond(response);
+            }
+        });
+
+        let _service = DbusService::serve(bus.connect().await, tx, &events)
+            .await
+            .unwrap();
+
+        let client = bus.connect().await;
+        let proxy = zbus::Proxy::new(&client, BUS_NAME, OBJECT_PATH, "io.voxtype.Daemon")
+            .await
+            .unwrap();
+        let mut state_changes = proxy.receive_signal("StateChanged").await.unwrap();
+
+        let state: String = proxy.call("GetState", &()).await.unwrap();
+        assert_eq!(state, "idle");
+
+        let () = proxy.call("StartRecording", &()).await.unwrap();
+        let signal = state_changes.next().await.unwrap();
+        let state: String = signal.body().deserialize().unwrap();
+        assert_eq!(state, "recording");
+
+        let err = proxy
+            .call::<_, _, ()>("StopRecording", &())
+            .await
+            .unwrap_err();
+        assert!(err.to_string().contains("Not recording"));
+    }
+}
@@ -36,8 +36,8 @@ pub enum HotkeyError {
     #[error("Unknown key name: '{0}'. Use evtest or wev to find valid key names.")]
     UnknownKey(String),
 
-    #[error("No keyboard device found in /dev/input/")]
-    NoKeyboard,
+    #[error("No input device for the configured hotkeys found in /dev/input/ (check the key names and [[hotkey.devices]])")]
+    NoDevice,
 
     #[error("evdev error: {0}")]
     Evdev(String),
@@ -93,6 +93,9 @@ pub enum OutputError {
     #[error("ydotool daemon not running.\n  Start with: systemctl --user start ydotool\n  Enable at boot: systemctl --user enable ydotool")]
     YdotoolNotRunning,
 
+    #[error("X11 output unavailable: {0}")]
+    X11Unavailable(String),
+
     #[error("ydotool not found in PATH. Install via your package manager.")]
     YdotoolNotFound,
 
@@ -102,6 +105,12 @@ pub enum OutputError {
     #[error("wl-copy not found in PATH. Install wl-clipboard via your package manager.")]
     WlCopyNotFound,
 
+    #[error("Cannot create uinput keyboard: {0}\n  /dev/uinput must be writable: add yourself to the 'input' group and install the uinput udev rule")]
+    UinputUnavailable(String),
+
+    #[error("Keyboard layout {0} can't type {1:?}")]
+    NotInLayout(String, String),
+
     #[error("Text injection failed: {0}")]
     InjectionFailed(String),
 
@@ -110,6 +119,21 @@ pub enum OutputError {
 
     #[error("All output methods failed. Ensure wtype, ydotool, or wl-copy is available.")]
     AllMethodsFailed,
+
+    #[error("{0} cannot erase text")]
+    EraseNotSupported(String),
+
+    #[error("{0} output needs [output.{0}] in the config")]
+    SinkNotConfigured(&'static str),
+
+    #[error("Cannot write to {0}: {1}")]
+    SinkWriteFailed(String, String),
+
+    #[error("Output command failed: {0}")]
+    CommandFailed(String),
+
+    #[error("No `voxtype record start --wait` is waiting for the text")]
+    NoWaitingClient,
 }
 
 /// Result type alias using VoxtypeError
@@ -0,0 +1,410 @@
+//! Transcription history
+//!
+//! Every transcription the daemon outputs is appended to a JSON-lines file
+//! in the data directory (`~/.local/share/voxtype/history.jsonl`), so text
+//! that went to the wrong window can be found and reused with
+//! `voxtype history`. Each transcription appends one line; retention limits
+//! from `[history]` hide expired entries right away and are written back
+//! to the file only once in a while.
+
+use crate::config::{Config, HistoryConfig};
+use serde::{Deserialize, Serialize};
+use std::io::{self, BufRead, BufReader, Write};
+use std::os::unix::fs::OpenOptionsExt;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use std::time::{SystemTime, UNIX_EPOCH};
+use ureq::serde_json;
+
+/// A stored transcription
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct HistoryEntry {
+    /// Sequential entry number (assigned when stored)
+    pub id: u64,
+    /// When the transcription finished (Unix seconds)
+    pub timestamp: u64,
+    /// Text as returned by Whisper
+    pub raw_text: String,
+    /// Text after spoken punctuation and replacements
+    pub processed_text: String,
+    /// Text after post-processing (what was output)
+    pub final_text: String,
+    /// Model used for transcription
+    pub model: String,
+    /// Length of the recording in seconds
+    pub duration_secs: f32,
+    /// Output method that delivered the text (None if output failed)
+    #[serde(default)]
+    pub output_method: Option<String>,
+}
+
+/// How many entries past `max_entries` the file may hold before it is pruned
+const PRUNE_SLACK: usize = 50;
+
+/// How often expired entries are pruned from the file (seconds)
+const PRUNE_INTERVAL_SECS: u64 = 24 * 60 * 60;
+
+/// What `add` knows about the file, so it doesn't have to read it again
+#[derive(Debug, Default)]
+struct FileState {
+    /// Id of the newest entry, 0 if there is none
+    last_id: u64,
+    /// Entries in the file, including expired ones
+    lines: usize,
+    /// When the file was last pruned (Unix seconds, 0 = not yet)
+    pruned_at: u64,
+}
+
+/// History file with retention limits
+/// Clones share the same file state, so one can be moved to a blocking task.
+#[derive(Clone)]
+pub struct History {
+    path: PathBuf,
+    max_entries: usize,
+    max_age_secs: Option<u64>,
+    state: Arc<Mutex<Option<FileState>>>,
+}
+
+impl History {
+    /// Open the history at its default location
+    pub fn new(config: &HistoryConfig) -> Self {
+        Self::with_path(Self::default_path(), config)
+    }
+
+    /// Open a history file at a specific path
+    pub fn with_path(path: PathBuf, config: &HistoryConfig) -> Self {
+        Self {
+            path,
+            max_entries: config.max_entries,
+            max_age_secs: (config.max_age_days > 0)
+                .then_some(config.max_age_days as u64 * 24 * 60 * 60),
+            state: Arc::default(),
+        }
+    }
+
+    /// Default history file location
+    pub fn default_path() -> PathBuf {
+        Config::data_dir().join("history.jsonl")
+    }
+
+    /// Path of the history file
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Load all entries within the retention limits, oldest first
+    /// A missing file is an empty history; unreadable lines are skipped
+    pub fn entries(&self) -> io::Result<Vec<HistoryEntry>> {
+        let mut entries = self.read_file()?;
+        self.prune(&mut entries, unix_now());
+        Ok(entries)
+    }
+
+    /// Load every entry in the file, expired ones included
+    fn read_file(&self) -> io::Result<Vec<HistoryEntry>> {
+        let file = match std::fs::File::open(&self.path) {
+            Ok(file) => file,
+            Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(Vec::new()),
+            Err(e) => return Err(e),
+        };
+
+        let mut entries = Vec::new();
+        for line in BufReader::new(file).lines() {
+            let line = line?;
+            if line.trim().is_empty() {
+                continue;
+            }
+            match serde_json::from_str(&line) {
+                Ok(entry) => entries.push(entry),
+                Err(e) => tracing::warn!("Skipping invalid history entry: {}", e),
+            }
+        }
+        Ok(entries)
+    }
+
+    /// Find an entry by id, or the most recent entry if `id` is None
+    pub fn get(&self, id: Option<u64>) -> io::Result<Option<HistoryEntry>> {
+        let entries = self.entries()?;
+        Ok(match id {
+            Some(id) => entries.into_iter().find(|e| e.id == id),
+            None => entries.into_iter().last(),
+        })
+    }
+
+    /// Find entries containing `query` (case-insensitive), oldest first
+    pub fn search(&self, query: &str) -> io::Result<Vec<HistoryEntry>> {
+        let query = query.to_lowercase();
+        Ok(self
+            .entries()?
+            .into_iter()
+            .filter(|e| {
+                e.final_text.to_lowercase().contains(&query)
+                    || e.raw_text.to_lowercase().contains(&query)
+            })
+            .collect())
+    }
+
+    /// Store a new entry by appending it, assigning its id (blocking)
+    /// The file is pruned when it has grown well past `max_entries`, or
+    /// once a day for `max_age_days`. Returns the assigned id
+    pub fn add(&self, mut entry: HistoryEntry) -> io::Result<u64> {
+        let mut state = self.state.lock().unwrap_or_else(|e| e.into_inner());
+        let file = match state.as_mut() {
+            Some(file) => file,
+            None => {
+                let entries = self.read_file()?;
+                state.insert(FileState {
+                    last_id: entries.last().map_or(0, |last| last.id),
+                    lines: entries.len(),
+                    pruned_at: 0,
+                })
+            }
+        };
+
+        entry.id = file.last_id + 1;
+        self.append(&entry)?;
+        file.last_id = entry.id;
+        file.lines += 1;
+
+        let now = unix_now();
+        let too_long = file.lines > self.max_entries + PRUNE_SLACK;
+        let expiry_due = self.max_age_secs.is_some()
+            && now.saturating_sub(file.pruned_at) >= PRUNE_INTERVAL_SECS;
+        if too_long || expiry_due {
+            let mut entries = self.read_file()?;
+            self.prune(&mut entries, now);
+            self.write(&entries)?;
+            file.lines = entries.len();
+            file.pruned_at = now;
+        }
+        Ok(entry.id)
+    }
+
+    /// Delete all entries
+    pub fn clear(&self) -> io::Result<()> {
+        *self.state.lock().unwrap_or_else(|e| e.into_inner()) = None;
+        match std::fs::remove_file(&self.path) {
+            Err(e) if e.kind() != io::ErrorKind::NotFound => Err(e),
+            _ => Ok(()),
+        }
+    }
+
+    /// Append one entry to the file (readable only by the user)
+    fn append(&self, entry: &HistoryEntry) -> io::Result<()> {
+        if let Some(parent) = self.path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+
+        let mut json = serde_json::to_string(entry)
+            .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;
+        json.push('\n');
+        std::fs::OpenOptions::new()
+            .append(true)
+            .create(true)
+            .mode(0o600)
+            .open(&self.path)?
+            .write_all(json.as_bytes())
+    }
+
+    /// Drop entries that exceed the age or count limits
+    fn prune(&self, entries: &mut Vec<HistoryEntry>, now: u64) {
+        if let Some(max_age) = self.max_age_secs {
+            entries.retain(|e| now.saturating_sub(e.timestamp) <= max_age);
+        }
+        if entries.len() > self.max_entries {
+            entries.drain(..entries.len() - self.max_entries);
+        }
+    }
+
+    /// Replace the history file (atomically, readable only by the user)
+    fn write(&self, entries: &[HistoryEntry]) -> io::Result<()> {
+        let tmp_path = self.path.with_extension("jsonl.tmp");
+        let mut file = std::fs::OpenOptions::new()
+            .write(true)
+            .create(true)
+            .truncate(true)
+            .mode(0o600)
+            .open(&tmp_path)?;
+        for entry in entries {
+            let json = serde_json::to_string(entry)
+                .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;
+            writeln!(file, "{}", json)?;
+        }
+        file.sync_all()?;
+
+        std::fs::rename(&tmp_path, &self.path)
+    }
+}
+
+/// Current time as Unix seconds
+pub fn unix_now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0)
+}
+
+/// Format a Unix timestamp as local time ("2025-01-31 14:05")
+pub fn format_timestamp(timestamp: u64) -> String {
+    let time = timestamp as libc::time_t;
+    // SAFETY: localtime_r only writes to the tm struct we pass in
+    let tm = unsafe {
+        let mut tm: libc::tm = std::mem::zeroed();
+        if libc::localtime_r(&time, &mut tm).is_null() {
+            return timestamp.to_string();
+        }
+        tm
+    };
+
+    format!(
+        "{:04}-{:02}-{:02} {:02}:{:02}",
+        tm.tm_year + 1900,
+        tm.tm_mon + 1,
+        tm.tm_mday,
+        tm.tm_hour,
+        tm.tm_min
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    fn history(dir: &TempDir, max_entries: usize, max_age_days: u32) -> History {
+        History::with_path(
+            dir.path().join("history.jsonl"),
+            &HistoryConfig {
+                enabled: true,
+                max_entries,
+                max_age_days,
+            },
+        )
+    }
+
+    fn entry(text: &str, timestamp: u64) -> HistoryEntry {
+        HistoryEntry {
+            id: 0,
+            timestamp,
+            raw_text: text.to_string(),
+            processed_text: text.to_string(),
+            final_text: text.to_string(),
+            model: "base.en".to_string(),
+            duration_secs: 1.5,
+            output_method: Some("wtype".to_string()),
+        }
+    }
+
+    #[test]
+    fn test_missing_file_is_empty() {
+        let dir = TempDir::new().unwrap();
+        let history = history(&dir, 10, 0);
+        assert!(history.entries().unwrap().is_empty());
+        assert!(history.get(None).unwrap().is_none());
+    }
+
+    #[test]
+    fn test_add_assigns_sequential_ids() {
+        let dir = TempDir::new().unwrap();
+        let history = history(&dir, 10, 0);
+        assert_eq!(history.add(entry("one", unix_now())).unwrap(), 1);
+        assert_eq!(history.add(entry("two", unix_now())).unwrap(), 2);
+
+        assert_eq!(history.get(Some(1)).unwrap().unwrap().final_text, "one");
+        assert_eq!(history.get(None).unwrap().unwrap().final_text, "two");
+        assert!(history.get(Some(3)).unwrap().is_none());
+    }
+
+    #[test]
+    fn test_max_entries_keeps_newest() {
+        let dir = TempDir::new().unwrap();
+        let history = history(&dir, 2, 0);
+        for text in ["one", "two", "three"] {
+            history.add(entry(text, unix_now())).unwrap();
+        }
+
+        let entries = history.entries().unwrap();
+        assert_eq!(entries.len(), 2);
+        assert_eq!(entries[0].final_text, "two");
+        // Ids keep counting after old entries are dropped
+        assert_eq!(entries[1].id, 3);
+    }
+
+    #[test]
+    fn test_max_age_drops_old_entries() {
+        let dir = TempDir::new().unwrap();
+        let history = history(&dir, 10, 1);
+        history.add(entry("ancient", 1_000)).unwrap();
+        history.add(entry("fresh", unix_now())).unwrap();
+
+        let entries = history.entries().unwrap();
+        assert_eq!(entries.len(), 1);
+        assert_eq!(entries[0].final_text, "fresh");
+    }
+
+    #[test]
+    fn test_add_appends_and_prunes_past_slack() {
+        let dir = TempDir::new().unwrap();
+        let store = history(&dir, 2, 0);
+        let lines = || {
+            std::fs::read_to_string(store.path())
+                .unwrap()
+                .lines()
+                .count()
+        };
+
+        for i in 0..2 + PRUNE_SLACK {
+            store.add(entry(&i.to_string(), unix_now())).unwrap();
+        }
+        assert_eq!(lines(), 2 + PRUNE_SLACK);
+        assert_eq!(store.entries().unwrap().len(), 2);
+
+        // One more is past the slack: the file is cut back to the limit
+        store.add(entry("last", unix_now())).unwrap();
+        assert_eq!(lines(), 2);
+
+        // A fresh handle (e.g. after a restart) continues the ids
+        let id = history(&dir, 2, 0).add(entry("next", unix_now())).unwrap();
+        assert_eq!(id, 2 + PRUNE_SLACK as u64 + 2);
+    }
+
+    #[test]
+    fn test_search_is_case_insensitive() {
+        let dir = TempDir::new().unwrap();
+        let history = history(&dir, 10, 0);
+        history
+            .add(entry("Meeting notes for Monday", unix_now()))
+            .unwrap();
+        history.add(entry("Grocery list", unix_now())).unwrap();
+
+        let results = history.search("meeting").unwrap();
+        assert_eq!(results.len(), 1);
+        assert_eq!(results[0].id, 1);
+    }
+
+    #[test]
+    fn test_skips_invalid_lines() {
+        let dir = TempDir::new().unwrap();
+        let history = history(&dir, 10, 0);
+        history.add(entry("good", unix_now())).unwrap();
+
+        let mut file = std::fs::OpenOptions::new()
+            .append(true)
+            .open(history.path())
+            .unwrap();
+        writeln!(file, "not json").unwrap();
+
+        assert_eq!(history.entries().unwrap().len(), 1);
+    }
+
+    #[test]
+    fn test_clear() {
+        let dir = TempDir::new().unwrap();
+        let history = history(&dir, 10, 0);
+        history.add(entry("secret", unix_now())).unwrap();
+        history.clear().unwrap();
+        assert!(history.entries().unwrap().is_empty());
+        // Clearing an empty history is fine
+        history.clear().unwrap();
+    }
+}
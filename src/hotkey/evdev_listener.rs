@@ -2,62 +2,95 @@
 //!
 //! Uses the Linux evdev interface to detect key presses at the kernel level.
 //! This works on all Wayland compositors because it bypasses the display server.
+//! Any input device can be the trigger: keyboards, mouse buttons, foot pedals.
 //!
 //! Uses inotify to detect device changes (hotplug, screenlock, suspend/resume)
 //! and automatically re-enumerates devices when needed.
 //!
+//! Devices and inotify are waited on with epoll (through the tokio runtime),
+//! so the listener is idle until a key is pressed or a device changes.
+//!
+//! With `grab` set, the hotkey devices are grabbed (EVIOCGRAB) so their input
+//! no longer reaches applications. In passthrough mode everything except the
+//! hotkey presses is re-emitted through a uinput device.
+//!
 //! The user must be in the 'input' group to access /dev/input/* devices.
 
+use super::gesture::{GestureRecognizer, GestureTiming, KeyBinding};
 use super::{HotkeyEvent, HotkeyListener};
-use crate::config::HotkeyConfig;
+use crate::config::{DeviceFilter, GrabMode, HotkeyConfig};
 use crate::error::HotkeyError;
-use evdev::{Device, InputEventKind, Key};
+use evdev::uinput::{VirtualDevice, VirtualDeviceBuilder};
+use evdev::{Device, InputEvent, InputEventKind, Key, Synchronization};
 use inotify::{Inotify, WatchMask};
 use std::collections::{HashMap, HashSet};
 use std::os::unix::io::AsRawFd;
-use std::path::PathBuf;
-use std::time::{Duration, Instant};
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+use tokio::io::unix::AsyncFd;
 use tokio::sync::{mpsc, oneshot};
 
+/// Name of the passthrough device; devices named like this are never opened
+const PASSTHROUGH_NAME: &str = "voxtype passthrough";
+
+/// Longest wait for held keys to be released before grabbing a device
+const GRAB_WAIT: Duration = Duration::from_millis(500);
+
 /// evdev-based hotkey listener
 pub struct EvdevListener {
-    /// The key to listen for
-    target_key: Key,
-    /// Modifier keys that must be held
-    modifier_keys: HashSet<Key>,
-    /// Optional cancel key
-    cancel_key: Option<Key>,
+    /// Key combinations to listen for, indexed like `HotkeyConfig::all_bindings`
+    bindings: Vec<KeyBinding>,
+    /// Timing windows for double taps and chords
+    timing: GestureTiming,
+    /// Devices to read from (empty: any device with one of the keys)
+    devices: Vec<DeviceFilter>,
+    /// Whether to keep the devices' input away from applications
+    grab: GrabMode,
     /// Signal to stop the listener task
     stop_signal: Option<oneshot::Sender<()>>,
+    /// The listener task, which closes its devices before it ends
+    task: Option<tokio::task::JoinHandle<()>>,
 }
 
 impl EvdevListener {
-    /// Create a new evdev listener for the configured hotkey
+    /// Create a new evdev listener for the configured hotkeys
     pub fn new(config: &HotkeyConfig) -> Result<Self, HotkeyError> {
-        let target_key = parse_key_name(&config.key)?;
-
-        let modifier_keys = config
-            .modifiers
+        let bindings = config
+            .all_bindings()
             .iter()
-            .map(|k| parse_key_name(k))
-            .collect::<Result<HashSet<_>, _>>()?;
-
-        // Parse optional cancel key
-        let cancel_key = config
-            .cancel_key
-            .as_ref()
-            .map(|k| parse_key_name(k))
-            .transpose()?;
+            .map(|binding| {
+                Ok(KeyBinding {
+                    key: parse_key_name(&binding.key)?,
+                    modifiers: binding
+                        .modifiers
+                        .iter()
+                        .map(|k| parse_key_name(k))
+                        .collect::<Result<HashSet<_>, _>>()?,
+                    chord: binding
+                        .chord
+                        .iter()
+                        .map(|k| parse_key_name(k))
+                        .collect::<Result<Vec<_>, _>>()?,
+                    double_tap: binding.double_tap,
+                })
+            })
+            .collect::<Result<Vec<_>, HotkeyError>>()?;
+        let timing = GestureTiming {
+            double_tap_window: Duration::from_millis(config.double_tap_window_ms as u64),
+            chord_window: Duration::from_millis(config.chord_window_ms as u64),
+        };
 
         // Verify we can access /dev/input (permission check)
         std::fs::read_dir("/dev/input")
             .map_err(|e| HotkeyError::DeviceAccess(format!("/dev/input: {}", e)))?;
 
         Ok(Self {
-            target_key,
-            modifier_keys,
-            cancel_key,
+            bindings,
+            timing,
+            devices: config.devices.clone(),
+            grab: config.grab,
             stop_signal: None,
+            task: None,
         })
     }
 }
@@ -65,48 +98,122 @@ impl EvdevListener {
 #[async_trait::async_trait]
 impl HotkeyListener for EvdevListener {
     async fn start(&mut self) -> Result<mpsc::Receiver<HotkeyEvent>, HotkeyError> {
+        let keys = self
+            .bindings
+            .iter()
+            .flat_map(|b| std::iter::once(&b.key).chain(&b.modifiers).chain(&b.chord))
+            .copied()
+            .collect();
+
+        // Open and grab the devices here, so a missing device or a failed
+        // grab is reported to the caller
+        let (events_tx, events_rx) = mpsc::unbounded_channel();
+        let manager = DeviceManager::new(self.devices.clone(), keys, self.grab, events_tx).await?;
+
         let (tx, rx) = mpsc::channel(32);
         let (stop_tx, stop_rx) = oneshot::channel();
         self.stop_signal = Some(stop_tx);
 
-        let target_key = self.target_key;
-        let modifier_keys = self.modifier_keys.clone();
-        let cancel_key = self.cancel_key;
-
-        // Spawn the listener task
-        tokio::task::spawn_blocking(move || {
-            if let Err(e) = evdev_listener_loop(target_key, modifier_keys, cancel_key, tx, stop_rx)
-            {
-                tracing::error!("Hotkey listener error: {}", e);
-            }
-        });
+        let recognizer = GestureRecognizer::new(self.bindings.clone(), self.timing);
+        self.task = Some(tokio::spawn(evdev_listener_loop(
+            recognizer, manager, events_rx, tx, stop_rx,
+        )));
 
         Ok(rx)
     }
 
+    /// Stop listening, returning once the devices (and their grabs) are released
     async fn stop(&mut self) -> Result<(), HotkeyError> {
         if let Some(stop) = self.stop_signal.take() {
             let _ = stop.send(());
         }
+        if let Some(task) = self.task.take() {
+            let _ = task.await;
+        }
         Ok(())
     }
 }
 
+/// Identity of an input device, for matching against `[[hotkey.devices]]`
+struct DeviceInfo<'a> {
+    path: &'a Path,
+    name: &'a str,
+    vendor: u16,
+    product: u16,
+}
+
+/// Whether a device matches all options set in a filter
+fn device_matches(filter: &DeviceFilter, device: &DeviceInfo) -> bool {
+    let name_matches = filter
+        .name
+        .as_ref()
+        .is_none_or(|name| device.name.to_lowercase().contains(&name.to_lowercase()));
+    // Paths may be symlinks (/dev/input/by-id/...) to the event device
+    let path_matches = filter
+        .path
+        .as_ref()
+        .is_none_or(|path| std::fs::canonicalize(path).is_ok_and(|path| path == device.path));
+
+    name_matches
+        && path_matches
+        && filter.vendor_id.is_none_or(|id| id == device.vendor)
+        && filter.product_id.is_none_or(|id| id == device.product)
+}
+
+/// Something that happened on one of the open devices
+enum DeviceEvent {
+    /// Events read from the device with this id: key events, plus (for
+    /// passthrough) relative motion and the SYN_REPORTs that end each batch
+    Input(u64, Vec<InputEvent>),
+    /// The device with this path and id can no longer be read
+    Gone(PathBuf, u64),
+}
+
+/// An open device, read by its own task
+struct OpenDevice {
+    /// Tells stale `DeviceEvent::Gone` apart from a device re-added at the same path
+    id: u64,
+    reader: tokio::task::JoinHandle<()>,
+    /// Re-emits the input of a grabbed device (passthrough mode)
+    passthrough: Option<VirtualDevice>,
+}
+
+impl Drop for OpenDevice {
+    fn drop(&mut self) {
+        // Dropping the reader task closes the device
+        self.reader.abort();
+    }
+}
+
 /// Manages input devices with hotplug detection via inotify
 struct DeviceManager {
     /// Map of device path to opened device
-    devices: HashMap<PathBuf, Device>,
+    devices: HashMap<PathBuf, OpenDevice>,
+    /// Devices to open (empty: any device that can send one of `keys`)
+    filters: Vec<DeviceFilter>,
+    /// Keys used by the hotkey bindings
+    keys: HashSet<Key>,
+    /// Whether to grab the opened devices
+    grab: GrabMode,
     /// inotify instance watching /dev/input
-    inotify: Inotify,
+    inotify: AsyncFd<Inotify>,
     /// Buffer for inotify events
     inotify_buffer: [u8; 1024],
-    /// Last time we did a full validation
-    last_validation: Instant,
+    /// Where device reader tasks send their events
+    events_tx: mpsc::UnboundedSender<DeviceEvent>,
+    /// Id for the next opened device
+    next_id: u64,
 }
 
 impl DeviceManager {
     /// Create a new device manager with inotify watcher
-    fn new() -> Result<Self, HotkeyError> {
+    /// Must be called from within the tokio runtime
+    async fn new(
+        filters: Vec<DeviceFilter>,
+        keys: HashSet<Key>,
+        grab: GrabMode,
+        events_tx: mpsc::UnboundedSender<DeviceEvent>,
+    ) -> Result<Self, HotkeyError> {
         let inotify = Inotify::init().map_err(|e| {
             HotkeyError::DeviceAccess(format!("Failed to initialize inotify: {}", e))
         })?;
@@ -117,28 +224,41 @@ impl DeviceManager {
             .add("/dev/input", WatchMask::CREATE | WatchMask::DELETE)
             .map_err(|e| HotkeyError::DeviceAccess(format!("Failed to watch /dev/input: {}", e)))?;
 
+        let inotify = AsyncFd::new(inotify)
+            .map_err(|e| HotkeyError::DeviceAccess(format!("Failed to register inotify: {}", e)))?;
+
         let mut manager = Self {
             devices: HashMap::new(),
+            filters,
+            keys,
+            grab,
             inotify,
             inotify_buffer: [0u8; 1024],
-            last_validation: Instant::now(),
+            events_tx,
+            next_id: 0,
         };
 
         // Initial device enumeration
-        manager.enumerate_devices()?;
-
-        if manager.devices.is_empty() {
-            return Err(HotkeyError::NoKeyboard);
+        let result = match manager.enumerate_devices().await {
+            Ok(()) if manager.devices.is_empty() => Err(HotkeyError::NoDevice),
+            result => result,
+        };
+        if let Err(e) = result {
+            manager.close().await;
+            return Err(e);
         }
 
         Ok(manager)
     }
 
-    /// Enumerate all keyboard devices and open them
-    fn enumerate_devices(&mut self) -> Result<(), HotkeyError> {
+    /// Enumerate all input devices and open the ones we want
+    /// A device that can't be grabbed is left closed; the first such
+    /// failure is returned once all other devices are open.
+    async fn enumerate_devices(&mut self) -> Result<(), HotkeyError> {
         let input_dir = std::fs::read_dir("/dev/input")
             .map_err(|e| HotkeyError::DeviceAccess(format!("/dev/input: {}", e)))?;
 
+        let mut result = Ok(());
         for entry in input_dir.flatten() {
             let path = entry.path();
 
@@ -158,30 +278,47 @@ impl DeviceManager {
                 continue;
             }
 
-            // Try to open and check if it's a keyboard
-            self.try_open_device(&path);
+            // Try to open and check if we want it
+            if let Err(e) = self.try_open_device(&path).await {
+                if result.is_ok() {
+                    result = Err(e);
+                }
+            }
         }
 
-        Ok(())
+        result
     }
 
-    /// Try to open a device and add it if it's a keyboard
-    fn try_open_device(&mut self, path: &PathBuf) {
+    /// Try to open a device and add it if it matches the device filters,
+    /// or (without filters) if it can send one of the hotkeys
+    /// Fails if a wanted device can't be grabbed.
+    async fn try_open_device(&mut self, path: &PathBuf) -> Result<(), HotkeyError> {
         match Device::open(path) {
-            Ok(device) => {
-                // Check if device has keyboard capabilities
-                let has_keys = device
-                    .supported_keys()
-                    .map(|keys| {
-                        // A keyboard should have at least some letter keys
-                        keys.contains(Key::KEY_A)
-                            && keys.contains(Key::KEY_Z)
-                            && keys.contains(Key::KEY_ENTER)
-                    })
-                    .unwrap_or(false);
-
-                if has_keys {
-                    // Set device to non-blocking mode
+            Ok(mut device) => {
+                // Never read back our own virtual devices
+                if device.name().is_some_and(|name| {
+                    name == PASSTHROUGH_NAME || name == crate::output::uinput::KEYBOARD_NAME
+                }) {
+                    return Ok(());
+                }
+
+                let wanted = if self.filters.is_empty() {
+                    device
+                        .supported_keys()
+                        .is_some_and(|keys| self.keys.iter().any(|k| keys.contains(*k)))
+                } else {
+                    let info = DeviceInfo {
+                        path,
+                        name: device.name().unwrap_or(""),
+                        vendor: device.input_id().vendor(),
+                        product: device.input_id().product(),
+                    };
+                    self.filters.iter().any(|f| device_matches(f, &info))
+                };
+
+                if wanted {
+                    // Set device to non-blocking mode, so readiness can be
+                    // handled by the runtime
                     let fd = device.as_raw_fd();
                     unsafe {
                         let flags = libc::fcntl(fd, libc::F_GETFL);
@@ -191,11 +328,60 @@ impl DeviceManager {
                     }
 
                     tracing::info!(
-                        "Opened keyboard: {:?} ({:?})",
+                        "Opened input device: {:?} ({:?})",
                         path,
                         device.name().unwrap_or("unknown")
                     );
-                    self.devices.insert(path.clone(), device);
+
+                    let mut passthrough = None;
+                    if self.grab != GrabMode::None {
+                        match grab_device(&mut device, self.grab).await {
+                            Ok(virtual_device) => {
+                                tracing::info!("Grabbed {:?} ({:?} mode)", path, self.grab);
+                                passthrough = virtual_device;
+                            }
+                            // Passthrough can't re-emit every kind of device
+                            Err(e) if e.kind() == std::io::ErrorKind::Unsupported => {
+                                tracing::warn!(
+                                    "Not grabbing {:?}, hotkeys will also reach applications: {}",
+                                    path,
+                                    e
+                                )
+                            }
+                            Err(e) => {
+                                return Err(HotkeyError::DeviceAccess(format!(
+                                    "Failed to grab {}: {}",
+                                    path.display(),
+                                    e
+                                )))
+                            }
+                        }
+                    }
+
+                    let device = match AsyncFd::new(device) {
+                        Ok(device) => device,
+                        Err(e) => {
+                            tracing::warn!("Failed to register {:?}: {}", path, e);
+                            return Ok(());
+                        }
+                    };
+                    let id = self.next_id;
+                    self.next_id += 1;
+                    let reader = tokio::spawn(read_device(
+                        path.clone(),
+                        id,
+                        device,
+                        passthrough.is_some(),
+                        self.events_tx.clone(),
+                    ));
+                    self.devices.insert(
+                        path.clone(),
+                        OpenDevice {
+                            id,
+                            reader,
+                            passthrough,
+                        },
+                    );
                 }
             }
             Err(e) => {
@@ -204,27 +390,30 @@ impl DeviceManager {
                 }
             }
         }
+        Ok(())
     }
 
-    /// Check inotify for device changes (non-blocking)
-    /// Returns true if devices changed
-    fn check_for_device_changes(&mut self) -> bool {
-        // Set inotify to non-blocking for this check
-        let fd = self.inotify.as_raw_fd();
-        unsafe {
-            let flags = libc::fcntl(fd, libc::F_GETFL);
-            if flags != -1 {
-                libc::fcntl(fd, libc::F_SETFL, flags | libc::O_NONBLOCK);
+    /// Wait for inotify to report changes in /dev/input
+    /// Returns true if devices changed. Cancel safe.
+    async fn device_changes(&mut self) -> bool {
+        let mut guard = match self.inotify.readable_mut().await {
+            Ok(guard) => guard,
+            Err(e) => {
+                tracing::warn!("inotify poll error: {}", e);
+                // Hotplug is lost, but the open devices keep working
+                return std::future::pending().await;
             }
-        }
+        };
 
-        let events = match self.inotify.read_events(&mut self.inotify_buffer) {
+        let events = match guard.get_inner_mut().read_events(&mut self.inotify_buffer) {
             Ok(events) => events,
             Err(ref e) if e.kind() == std::io::ErrorKind::WouldBlock => {
+                guard.clear_ready();
                 return false;
             }
             Err(e) => {
                 tracing::warn!("inotify read error: {}", e);
+                guard.clear_ready();
                 return false;
             }
         };
@@ -252,219 +441,273 @@ impl DeviceManager {
     }
 
     /// Handle device changes - wait for settle and re-enumerate
-    fn handle_device_changes(&mut self) {
+    async fn handle_device_changes(&mut self) {
         // Wait for devices to settle (USB enumeration can be slow)
-        std::thread::sleep(Duration::from_millis(150));
+        tokio::time::sleep(Duration::from_millis(150)).await;
 
         // Re-enumerate to pick up new devices
-        if let Err(e) = self.enumerate_devices() {
+        if let Err(e) = self.enumerate_devices().await {
             tracing::warn!("Device enumeration failed: {}", e);
         }
 
-        tracing::info!("Devices updated: {} keyboard(s) active", self.devices.len());
+        tracing::info!(
+            "Devices updated: {} input device(s) active",
+            self.devices.len()
+        );
     }
 
-    /// Validate that all devices are still accessible
-    /// Returns true if any device was removed
-    fn validate_devices(&mut self) -> bool {
-        let mut stale_paths = Vec::new();
-
-        for (path, device) in &self.devices {
-            let fd = device.as_raw_fd();
-            let link_path = format!("/proc/self/fd/{}", fd);
+    /// Forget a device whose reader stopped
+    /// Returns true if it was still open
+    fn remove_device(&mut self, path: &Path, id: u64) -> bool {
+        if self.devices.get(path).is_some_and(|device| device.id == id) {
+            self.devices.remove(path);
+            return true;
+        }
+        false
+    }
 
-            // Check if the symlink still points to a valid device
-            let is_valid = std::fs::read_link(&link_path)
-                .map(|target| target.exists())
-                .unwrap_or(false);
+    /// The passthrough device of the device with this id, if it has one
+    fn passthrough(&mut self, id: u64) -> Option<&mut VirtualDevice> {
+        self.devices
+            .values_mut()
+            .find(|device| device.id == id)
+            .and_then(|device| device.passthrough.as_mut())
+    }
 
-            if !is_valid {
-                tracing::debug!("Device no longer valid: {:?}", path);
-                stale_paths.push(path.clone());
-            }
-        }
+    /// Check if we have any devices
+    fn has_devices(&self) -> bool {
+        !self.devices.is_empty()
+    }
 
-        for path in &stale_paths {
-            self.devices.remove(path);
+    /// Close all devices, returning once their readers have let go of them
+    async fn close(&mut self) {
+        for (_, mut device) in self.devices.drain() {
+            device.reader.abort();
+            let _ = (&mut device.reader).await;
         }
-
-        !stale_paths.is_empty()
     }
+}
 
-    /// Poll all devices for events, handling errors gracefully
-    fn poll_events(&mut self) -> Vec<(Key, i32)> {
-        let mut events = Vec::new();
-        let mut error_paths = Vec::new();
-
-        for (path, device) in &mut self.devices {
-            match device.fetch_events() {
-                Ok(device_events) => {
-                    for event in device_events {
-                        if let InputEventKind::Key(key) = event.kind() {
-                            events.push((key, event.value()));
-                        }
-                    }
-                }
-                Err(ref e) if e.raw_os_error() == Some(libc::ENODEV) => {
-                    tracing::debug!("Device gone (ENODEV): {:?}", path);
-                    error_paths.push(path.clone());
-                }
-                Err(ref e) if e.kind() == std::io::ErrorKind::WouldBlock => {
-                    // No events available, this is normal for non-blocking
-                }
-                Err(e) => {
-                    tracing::trace!("Device read error on {:?}: {}", path, e);
-                }
-            }
+/// Grab a device so its input no longer reaches applications
+/// In passthrough mode, returns a virtual device to re-emit the input through
+async fn grab_device(
+    device: &mut Device,
+    mode: GrabMode,
+) -> std::io::Result<Option<VirtualDevice>> {
+    let passthrough = if mode == GrabMode::Passthrough {
+        // Absolute axes need their ranges copied over; keyboards, mice and
+        // pedals don't have any
+        if device
+            .supported_absolute_axes()
+            .is_some_and(|axes| axes.iter().next().is_some())
+        {
+            return Err(std::io::Error::new(
+                std::io::ErrorKind::Unsupported,
+                "passthrough doesn't support touchpads, tablets or joysticks",
+            ));
         }
 
-        // Remove devices that returned errors
-        for path in error_paths {
-            self.devices.remove(&path);
+        let mut builder = VirtualDeviceBuilder::new()?.name(PASSTHROUGH_NAME);
+        if let Some(keys) = device.supported_keys() {
+            builder = builder.with_keys(keys)?;
         }
+        if let Some(axes) = device.supported_relative_axes() {
+            builder = builder.with_relative_axes(axes)?;
+        }
+        Some(builder.build()?)
+    } else {
+        None
+    };
 
-        events
+    // A key held while grabbing never gets its release to the compositor,
+    // so it would look stuck (e.g. Enter after starting voxtype from a terminal)
+    let start = std::time::Instant::now();
+    while device.get_key_state()?.iter().next().is_some() && start.elapsed() < GRAB_WAIT {
+        tokio::time::sleep(Duration::from_millis(20)).await;
     }
 
-    /// Check if we have any devices
-    fn has_devices(&self) -> bool {
-        !self.devices.is_empty()
-    }
+    device.grab()?;
+    Ok(passthrough)
 }
 
-/// Main listener loop running in a blocking task
-fn evdev_listener_loop(
-    target_key: Key,
-    modifier_keys: HashSet<Key>,
-    cancel_key: Option<Key>,
-    tx: mpsc::Sender<HotkeyEvent>,
-    mut stop_rx: oneshot::Receiver<()>,
-) -> Result<(), HotkeyError> {
-    let mut manager = DeviceManager::new()?;
-
-    // Track currently held modifier keys
-    let mut active_modifiers: HashSet<Key> = HashSet::new();
+/// Decides which key events of a passthrough device reach applications
+///
+/// The press that fires a hotkey is swallowed, along with its repeats and
+/// release. Everything else passes, including the modifiers of the hotkey
+/// and keys that only start a chord or double tap.
+#[derive(Default)]
+struct PassthroughFilter {
+    /// Keys whose press fired a hotkey and that haven't been released yet
+    swallowed: HashSet<Key>,
+}
 
-    // Track if we're currently "pressed" (to handle repeat events)
-    let mut is_pressed = false;
+impl PassthroughFilter {
+    /// Whether to pass on a key event, given the hotkey events it caused
+    fn pass(&mut self, key: Key, value: i32, hotkeys: &[HotkeyEvent]) -> bool {
+        if value == 1
+            && hotkeys
+                .iter()
+                .any(|event| matches!(event, HotkeyEvent::Pressed { .. }))
+        {
+            self.swallowed.insert(key);
+        }
+        let swallowed = self.swallowed.contains(&key);
+        if value == 0 {
+            self.swallowed.remove(&key);
+        }
+        !swallowed
+    }
 
-    if let Some(cancel) = cancel_key {
-        tracing::info!(
-            "Listening for {:?} (with modifiers: {:?}) and cancel key {:?} on {} device(s)",
-            target_key,
-            modifier_keys,
-            cancel,
-            manager.devices.len()
-        );
-    } else {
-        tracing::info!(
-            "Listening for {:?} (with modifiers: {:?}) on {} device(s)",
-            target_key,
-            modifier_keys,
-            manager.devices.len()
-        );
+    /// Forget swallowed keys (devices changed)
+    fn reset(&mut self) {
+        self.swallowed.clear();
     }
+}
 
+/// Read key events from a device until it goes away
+///
+/// Sleeps in epoll until the kernel has events, so an idle listener costs
+/// no wakeups. A revoked or unplugged device becomes readable and fails
+/// with ENODEV, which ends the task.
+async fn read_device(
+    path: PathBuf,
+    id: u64,
+    mut device: AsyncFd<Device>,
+    passthrough: bool,
+    tx: mpsc::UnboundedSender<DeviceEvent>,
+) {
     loop {
-        // Check for stop signal (non-blocking)
-        match stop_rx.try_recv() {
-            Ok(_) | Err(oneshot::error::TryRecvError::Closed) => {
-                tracing::debug!("Hotkey listener stopping");
-                return Ok(());
+        let mut guard = match device.readable_mut().await {
+            Ok(guard) => guard,
+            Err(e) => {
+                tracing::debug!("Device poll error on {:?}: {}", path, e);
+                break;
             }
-            Err(oneshot::error::TryRecvError::Empty) => {}
-        }
+        };
 
-        // Check inotify for device changes
-        if manager.check_for_device_changes() {
-            // Clear state when devices change
-            active_modifiers.clear();
-            is_pressed = false;
-            manager.handle_device_changes();
-        }
+        let events = guard.get_inner_mut().fetch_events().map(|events| {
+            events
+                .filter(|event| match event.kind() {
+                    InputEventKind::Key(_) => true,
+                    // Only needed to re-emit the input
+                    InputEventKind::RelAxis(_)
+                    | InputEventKind::Synchronization(Synchronization::SYN_REPORT) => passthrough,
+                    _ => false,
+                })
+                .collect::<Vec<_>>()
+        });
 
-        // Periodic validation (every 30 seconds)
-        if manager.last_validation.elapsed() > Duration::from_secs(30) {
-            if manager.validate_devices() {
-                // Devices were removed, clear state
-                active_modifiers.clear();
-                is_pressed = false;
-                tracing::debug!("Stale devices removed during validation");
+        match events {
+            Ok(events) if events.is_empty() => {}
+            Ok(events) => {
+                if tx.send(DeviceEvent::Input(id, events)).is_err() {
+                    return; // Listener stopped
+                }
             }
-            manager.last_validation = Instant::now();
-        }
-
-        // If no devices, try to find some
-        if !manager.has_devices() {
-            tracing::warn!("No keyboard devices available, waiting...");
-            std::thread::sleep(Duration::from_secs(1));
-            if let Err(e) = manager.enumerate_devices() {
-                tracing::debug!("Enumeration failed: {}", e);
+            Err(ref e) if e.kind() == std::io::ErrorKind::WouldBlock => {
+                guard.clear_ready();
+            }
+            Err(ref e) if e.raw_os_error() == Some(libc::ENODEV) => {
+                tracing::debug!("Device gone (ENODEV): {:?}", path);
+                break;
+            }
+            Err(e) => {
+                tracing::debug!("Device read error on {:?}: {}", path, e);
+                break;
             }
-            continue;
         }
+    }
 
-        // Poll all devices for events
-        for (key, value) in manager.poll_events() {
-            // Track modifier state
-            if modifier_keys.contains(&key) {
-                match value {
-                    1 => {
-                        active_modifiers.insert(key);
-                    }
-                    0 => {
-                        active_modifiers.remove(&key);
-                    }
-                    _ => {}
-                }
+    let _ = tx.send(DeviceEvent::Gone(path, id));
+}
+
+/// Main listener loop, turning device events into hotkey events
+async fn evdev_listener_loop(
+    mut recognizer: GestureRecognizer,
+    mut manager: DeviceManager,
+    mut events_rx: mpsc::UnboundedReceiver<DeviceEvent>,
+    tx: mpsc::Sender<HotkeyEvent>,
+    mut stop_rx: oneshot::Receiver<()>,
+) {
+    let mut filter = PassthroughFilter::default();
+
+    tracing::info!(
+        "Listening for {} hotkey(s) on {} device(s)",
+        recognizer.bindings().len(),
+        manager.devices.len()
+    );
+    for (i, binding) in recognizer.bindings().iter().enumerate() {
+        tracing::debug!("Binding {}: {:?}", i, binding);
+    }
+
+    loop {
+        tokio::select! {
+            // Stop when asked to, or when the listener is dropped
+            _ = &mut stop_rx => {
+                tracing::debug!("Hotkey listener stopping");
+                manager.close().await;
+                return;
             }
 
-            // Check cancel key first (if configured)
-            if let Some(cancel) = cancel_key {
-                if key == cancel && value == 1 {
-                    // Cancel key pressed (ignore repeats and releases)
-                    tracing::debug!("Cancel key pressed");
-                    if tx.blocking_send(HotkeyEvent::Cancel).is_err() {
-                        return Ok(()); // Channel closed
+            changed = manager.device_changes() => {
+                if changed {
+                    // Clear state when devices change
+                    recognizer.reset();
+                    filter.reset();
+                    manager.handle_device_changes().await;
+                    if !manager.has_devices() {
+                        tracing::warn!("No input devices available, waiting...");
                     }
-                    continue;
                 }
             }
 
-            // Check target key
-            if key == target_key {
-                let modifiers_satisfied =
-                    modifier_keys.iter().all(|m| active_modifiers.contains(m));
-
-                if modifiers_satisfied {
-                    match value {
-                        1 if !is_pressed => {
-                            // Key press (not repeat)
-                            is_pressed = true;
-                            tracing::debug!("Hotkey pressed");
-                            if tx.blocking_send(HotkeyEvent::Pressed).is_err() {
-                                return Ok(()); // Channel closed
+            // The manager holds a sender, so this never ends
+            Some(event) = events_rx.recv() => match event {
+                DeviceEvent::Input(id, events) => {
+                    let mut passthrough = manager.passthrough(id);
+                    let mut batch = Vec::new();
+                    for event in events {
+                        match event.kind() {
+                            InputEventKind::Key(key) => {
+                                let hotkeys =
+                                    recognizer.process(key, event.value(), event.timestamp());
+                                if filter.pass(key, event.value(), &hotkeys) {
+                                    batch.push(event);
+                                }
+                                for hotkey in hotkeys {
+                                    tracing::debug!("Hotkey event: {:?}", hotkey);
+                                    if tx.send(hotkey).await.is_err() {
+                                        return; // Channel closed
+                                    }
+                                }
                             }
-                        }
-                        0 if is_pressed => {
-                            // Key release
-                            is_pressed = false;
-                            tracing::debug!("Hotkey released");
-                            if tx.blocking_send(HotkeyEvent::Released).is_err() {
-                                return Ok(()); // Channel closed
+                            InputEventKind::Synchronization(_) => {
+                                // emit() ends the batch with its own SYN_REPORT
+                                if let Some(device) = passthrough.as_mut() {
+                                    if !batch.is_empty() {
+                                        if let Err(e) = device.emit(&batch) {
+                                            tracing::warn!("Passthrough failed: {}", e);
+                                        }
+                                    }
+                                }
+                                batch.clear();
                             }
+                            _ => batch.push(event),
                         }
-                        2 => {
-                            // Key repeat - ignore
+                    }
+                }
+                DeviceEvent::Gone(path, id) => {
+                    if manager.remove_device(&path, id) {
+                        // A key may have been down on the device, clear state
+                        recognizer.reset();
+                        filter.reset();
+                        if !manager.has_devices() {
+                            tracing::warn!("No input devices available, waiting...");
                         }
-                        _ => {}
                     }
                 }
-            }
+            },
         }
-
-        // Small sleep to avoid busy-waiting
-        std::thread::sleep(Duration::from_millis(5));
     }
 }
 
@@ -479,8 +722,8 @@ fn parse_key_name(name: &str) -> Result<Key, HotkeyError> {
         })
         .collect();
 
-    // Add KEY_ prefix if not present
-    let key_name = if normalized.starts_with("KEY_") {
+    // Add KEY_ prefix if not present (BTN_* names are mouse/joystick buttons)
+    let key_name = if normalized.starts_with("KEY_") || normalized.starts_with("BTN_") {
         normalized
     } else {
         format!("KEY_{}", normalized)
@@ -554,18 +797,68 @@ fn parse_key_name(name: &str) -> Result<Key, HotkeyError> {
         "KEY_NEXTSONG" => Key::KEY_NEXTSONG,
         "KEY_PREVIOUSSONG" => Key::KEY_PREVIOUSSONG,
 
+        // Any other evdev key name (letters, digits, ...)
         // If not found, return error with suggestions
-        _ => {
-            return Err(HotkeyError::UnknownKey(format!(
-                "{}. Try: SCROLLLOCK, PAUSE, F13-F24, or run 'evtest' to find key names",
+        other => other.parse::<Key>().map_err(|_| {
+            HotkeyError::UnknownKey(format!(
+                "{}. Try: SCROLLLOCK, PAUSE, F13-F24, BTN_SIDE, or run 'evtest' to find key names",
                 name
-            )));
-        }
+            ))
+        })?,
     };
 
     Ok(key)
 }
 
+/// A uinput device to run the listener against, where /dev/uinput is usable
+#[cfg(test)]
+pub(crate) mod pedal {
+    use evdev::uinput::{VirtualDevice, VirtualDeviceBuilder};
+    use evdev::{AttributeSet, Device, Key};
+    use std::path::PathBuf;
+
+    pub struct Pedal {
+        _device: VirtualDevice,
+        pub path: PathBuf,
+    }
+
+    impl Pedal {
+        /// Create a device named `name` that sends `key`
+        /// Returns None without access to /dev/uinput.
+        pub fn create(name: &str, key: Key) -> Option<Self> {
+            let mut keys = AttributeSet::<Key>::new();
+            keys.insert(key);
+            let mut device = VirtualDeviceBuilder::new()
+                .ok()?
+                .name(name)
+                .with_keys(&keys)
+                .ok()?
+                .build()
+                .ok()?;
+            let path = device
+                .enumerate_dev_nodes_blocking()
+                .ok()?
+                .find_map(Result::ok)?;
+            Some(Self {
+                _device: device,
+                path,
+            })
+        }
+
+        /// Whether something holds a grab on the device
+        pub fn grabbed(&self) -> bool {
+            let mut device = Device::open(&self.path).unwrap();
+            match device.grab() {
+                Ok(()) => {
+                    let _ = device.ungrab();
+                    false
+                }
+                Err(_) => true,
+            }
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -581,10 +874,103 @@ mod tests {
         assert_eq!(parse_key_name("F13").unwrap(), Key::KEY_F13);
         assert_eq!(parse_key_name("LEFTALT").unwrap(), Key::KEY_LEFTALT);
         assert_eq!(parse_key_name("LALT").unwrap(), Key::KEY_LEFTALT);
+        assert_eq!(parse_key_name("j").unwrap(), Key::KEY_J);
+        assert_eq!(parse_key_name("KPENTER").unwrap(), Key::KEY_KPENTER);
+        assert_eq!(parse_key_name("BTN_SIDE").unwrap(), Key::BTN_SIDE);
+        assert_eq!(parse_key_name("btn-extra").unwrap(), Key::BTN_EXTRA);
     }
 
     #[test]
     fn test_parse_key_name_error() {
         assert!(parse_key_name("INVALID_KEY_NAME").is_err());
     }
+
+    fn pedal(path: &Path) -> DeviceInfo<'_> {
+        DeviceInfo {
+            path,
+            name: "PCsensor FootSwitch Keyboard",
+            vendor: 0x0c45,
+            product: 0x7403,
+        }
+    }
+
+    #[test]
+    fn test_device_filter_by_name_and_id() {
+        let device = pedal(Path::new("/dev/input/event7"));
+        let filter = |toml_str: &str| toml::from_str::<DeviceFilter>(toml_str).unwrap();
+
+        assert!(device_matches(&filter("name = \"footswitch\""), &device));
+        assert!(device_matches(
+            &filter("vendor_id = 0x0c45\nproduct_id = 0x7403"),
+            &device
+        ));
+        assert!(!device_matches(
+            &filter("name = \"footswitch\"\nproduct_id = 0x0001"),
+            &device
+        ));
+        assert!(!device_matches(&filter("name = \"Logitech\""), &device));
+    }
+
+    #[test]
+    fn test_device_filter_by_path_follows_symlinks() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let event = dir.path().join("event7");
+        std::fs::write(&event, "").unwrap();
+        let by_id = dir.path().join("usb-PCsensor_FootSwitch-event-kbd");
+        std::os::unix::fs::symlink(&event, &by_id).unwrap();
+
+        let event = std::fs::canonicalize(&event).unwrap();
+        let device = pedal(&event);
+        let filter = DeviceFilter {
+            path: Some(by_id),
+            ..Default::default()
+        };
+        assert!(device_matches(&filter, &device));
+
+        let other = dir.path().join("event8");
+        assert!(!device_matches(&filter, &pedal(&other)));
+    }
+
+    /// Feed key events through a recognizer for Ctrl+F13 and a passthrough
+    /// filter, returning the events that reach applications
+    fn passed(events: &[(Key, i32)]) -> Vec<(Key, i32)> {
+        let mut binding = KeyBinding::key(Key::KEY_F13);
+        binding.modifiers.insert(Key::KEY_LEFTCTRL);
+        let timing = GestureTiming {
+            double_tap_window: Duration::from_millis(300),
+            chord_window: Duration::from_millis(100),
+        };
+        let mut recognizer = GestureRecognizer::new(vec![binding], timing);
+        let mut filter = PassthroughFilter::default();
+
+        events
+            .iter()
+            .copied()
+            .filter(|&(key, value)| {
+                let hotkeys = recognizer.process(key, value, std::time::SystemTime::now());
+                filter.pass(key, value, &hotkeys)
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_passthrough_swallows_hotkey() {
+        let ctrl = Key::KEY_LEFTCTRL;
+        let f13 = Key::KEY_F13;
+        assert_eq!(
+            passed(&[(ctrl, 1), (f13, 1), (f13, 2), (f13, 0), (ctrl, 0)]),
+            vec![(ctrl, 1), (ctrl, 0)]
+        );
+    }
+
+    #[test]
+    fn test_passthrough_passes_other_keys() {
+        let f13 = Key::KEY_F13;
+        let a = Key::KEY_A;
+        // Without its modifier, F13 isn't a hotkey
+        assert_eq!(
+            passed(&[(f13, 1), (f13, 0), (a, 1), (a, 2), (a, 0)]),
+            vec![(f13, 1), (f13, 0), (a, 1), (a, 2), (a, 0)]
+        );
+    }
 }
@@ -0,0 +1,456 @@
+//! Hotkey gesture recognition
+//!
+//! Turns the raw key events read from evdev into press/release events of
+//! hotkey bindings. Besides a plain key press (with modifiers held), a
+//! binding can be triggered by:
+//!
+//! - a double tap: the key is tapped twice within the double-tap window,
+//!   with no other key pressed in between. The binding is pressed on the
+//!   second press and released when that press ends.
+//! - a chord: several keys go down together (in any order) within the
+//!   chord window. The binding is pressed when the last key goes down and
+//!   released when the first one comes up.
+//!
+//! The recognizer only looks at keys and timestamps, so it can be tested
+//! with synthetic event sequences.
+
+use super::HotkeyEvent;
+use evdev::Key;
+use std::cmp::Reverse;
+use std::collections::{HashMap, HashSet};
+use std::time::{Duration, SystemTime};
+
+/// A key combination that triggers a binding
+#[derive(Debug, Clone)]
+pub struct KeyBinding {
+    /// The key that triggers the binding
+    pub key: Key,
+    /// Modifier keys that must be held
+    pub modifiers: HashSet<Key>,
+    /// Other keys that must go down together with `key`
+    pub chord: Vec<Key>,
+    /// Triggered by a double tap of `key` instead of a single press
+    pub double_tap: bool,
+}
+
+impl KeyBinding {
+    /// A plain key press
+    pub fn key(key: Key) -> Self {
+        Self {
+            key,
+            modifiers: HashSet::new(),
+            chord: Vec::new(),
+            double_tap: false,
+        }
+    }
+
+    /// The key and the other keys of its chord
+    fn keys(&self) -> impl Iterator<Item = &Key> {
+        std::iter::once(&self.key).chain(&self.chord)
+    }
+}
+
+/// Timing windows for gestures
+#[derive(Debug, Clone, Copy)]
+pub struct GestureTiming {
+    /// Longest time from the first press of a double tap to the second
+    pub double_tap_window: Duration,
+    /// Longest time between the first and last key of a chord going down
+    pub chord_window: Duration,
+}
+
+/// Recognizes binding gestures in a stream of key events
+pub struct GestureRecognizer {
+    bindings: Vec<KeyBinding>,
+    timing: GestureTiming,
+    /// Keys currently held and when they went down
+    held: HashMap<Key, SystemTime>,
+    /// Bindings that fired and haven't been released yet, with when they fired
+    active: HashMap<usize, SystemTime>,
+    /// Latest press, while no other key has been pressed since
+    /// (becomes a tap if it is released quickly)
+    tap_candidate: Option<(Key, SystemTime)>,
+    /// Last quick tap (the first half of a double tap)
+    last_tap: Option<(Key, SystemTime)>,
+}
+
+impl GestureRecognizer {
+    pub fn new(bindings: Vec<KeyBinding>, timing: GestureTiming) -> Self {
+        Self {
+            bindings,
+            timing,
+            held: HashMap::new(),
+            active: HashMap::new(),
+            tap_candidate: None,
+            last_tap: None,
+        }
+    }
+
+    /// The bindings being recognized
+    pub fn bindings(&self) -> &[KeyBinding] {
+        &self.bindings
+    }
+
+    /// Process a key event (value: 1 = press, 0 = release, 2 = repeat)
+    /// `time` is the kernel timestamp of the event
+    pub fn process(&mut self, key: Key, value: i32, time: SystemTime) -> Vec<HotkeyEvent> {
+        match value {
+            1 if !self.held.contains_key(&key) => self.press(key, time).into_iter().collect(),
+            0 => self.release(key, time),
+            // Key repeat - ignore
+            _ => Vec::new(),
+        }
+    }
+
+    fn press(&mut self, key: Key, time: SystemTime) -> Option<HotkeyEvent> {
+        let double_tap = self.last_tap.take().is_some_and(|(tapped, tapped_at)| {
+            tapped == key && elapsed(tapped_at, time) <= self.timing.double_tap_window
+        });
+        self.tap_candidate = Some((key, time));
+        self.held.insert(key, time);
+
+        // The most specific gesture wins: a double tap over a plain press,
+        // bigger chords over smaller ones, more modifiers over fewer
+        let binding = self
+            .bindings
+            .iter()
+            .enumerate()
+            .filter(|(i, b)| !self.active.contains_key(i) && self.matches(b, key, double_tap))
+            .max_by_key(|(i, b)| (b.double_tap, b.chord.len(), b.modifiers.len(), Reverse(*i)))
+            .map(|(i, _)| i)?;
+
+        if self.bindings[binding].double_tap {
+            // The second tap doesn't start another double tap
+            self.tap_candidate = None;
+        }
+        self.active.insert(binding, time);
+        Some(HotkeyEvent::Pressed { binding })
+    }
+
+    /// Whether pressing `key` completes the binding's gesture
+    fn matches(&self, binding: &KeyBinding, key: Key, double_tap: bool) -> bool {
+        if !binding.modifiers.iter().all(|m| self.held.contains_key(m)) {
+            return false;
+        }
+        if binding.double_tap {
+            return binding.key == key && double_tap;
+        }
+        if binding.chord.is_empty() {
+            return binding.key == key;
+        }
+
+        // Chords: any key may come last, as long as all went down in time
+        if !binding.keys().any(|k| *k == key) {
+            return false;
+        }
+        let mut pressed_at = Vec::new();
+        for k in binding.keys() {
+            match self.held.get(k) {
+                Some(time) => pressed_at.push(*time),
+                None => return false,
+            }
+        }
+        let (Some(first), Some(last)) = (pressed_at.iter().min(), pressed_at.iter().max()) else {
+            return false;
+        };
+        elapsed(*first, *last) <= self.timing.chord_window
+    }
+
+    fn release(&mut self, key: Key, time: SystemTime) -> Vec<HotkeyEvent> {
+        if let Some((candidate, pressed_at)) = self.tap_candidate {
+            if candidate == key {
+                self.tap_candidate = None;
+                if elapsed(pressed_at, time) <= self.timing.double_tap_window {
+                    self.last_tap = Some((key, pressed_at));
+                }
+            }
+        }
+        self.held.remove(&key);
+
+        // Released even if the modifiers were let go first
+        let mut released: Vec<usize> = self
+            .active
+            .keys()
+            .copied()
+            .filter(|&i| self.bindings[i].keys().any(|k| *k == key))
+            .collect();
+        released.sort();
+        released
+            .into_iter()
+            .map(|binding| {
+                let fired_at = self.active.remove(&binding).unwrap_or(time);
+                HotkeyEvent::Released {
+                    binding,
+                    held: elapsed(fired_at, time),
+                }
+            })
+            .collect()
+    }
+
+    /// Forget all held keys (devices changed)
+    pub fn reset(&mut self) {
+        self.held.clear();
+        self.active.clear();
+        self.tap_candidate = None;
+        self.last_tap = None;
+    }
+}
+
+/// Time from `start` to `end` (zero if the clock went backwards)
+fn elapsed(start: SystemTime, end: SystemTime) -> Duration {
+    end.duration_since(start).unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const TIMING: GestureTiming = GestureTiming {
+        double_tap_window: Duration::from_millis(300),
+        chord_window: Duration::from_millis(100),
+    };
+
+    fn with_modifiers(key: Key, modifiers: &[Key]) -> KeyBinding {
+        KeyBinding {
+            modifiers: modifiers.iter().copied().collect(),
+            ..KeyBinding::key(key)
+        }
+    }
+
+    fn double_tap(key: Key) -> KeyBinding {
+        KeyBinding {
+            double_tap: true,
+            ..KeyBinding::key(key)
+        }
+    }
+
+    fn chord(keys: &[Key]) -> KeyBinding {
+        KeyBinding {
+            chord: keys[1..].to_vec(),
+            ..KeyBinding::key(keys[0])
+        }
+    }
+
+    /// Feed key events to the recognizer, 100ms apart
+    fn run(recognizer: &mut GestureRecognizer, events: &[(Key, i32)]) -> Vec<HotkeyEvent> {
+        let timed: Vec<_> = events
+            .iter()
+            .enumerate()
+            .map(|(i, &(key, value))| (100 * i as u64, key, value))
+            .collect();
+        run_at(recognizer, &timed)
+    }
+
+    /// Feed key events at the given times (milliseconds)
+    fn run_at(recognizer: &mut GestureRecognizer, events: &[(u64, Key, i32)]) -> Vec<HotkeyEvent> {
+        events
+            .iter()
+            .flat_map(|&(ms, key, value)| {
+                let time = SystemTime::UNIX_EPOCH + Duration::from_millis(ms);
+                recognizer.process(key, value, time)
+            })
+            .collect()
+    }
+
+    fn pressed(binding: usize) -> HotkeyEvent {
+        HotkeyEvent::Pressed { binding }
+    }
+
+    fn released(binding: usize, held_ms: u64) -> HotkeyEvent {
+        HotkeyEvent::Released {
+            binding,
+            held: Duration::from_millis(held_ms),
+        }
+    }
+
+    #[test]
+    fn test_press_and_release_ignore_repeats() {
+        let mut recognizer =
+            GestureRecognizer::new(vec![KeyBinding::key(Key::KEY_SCROLLLOCK)], TIMING);
+        let events = run(
+            &mut recognizer,
+            &[
+                (Key::KEY_A, 1),
+                (Key::KEY_SCROLLLOCK, 1),
+                (Key::KEY_SCROLLLOCK, 2),
+                (Key::KEY_SCROLLLOCK, 0),
+            ],
+        );
+        assert_eq!(events, vec![pressed(0), released(0, 200)]);
+    }
+
+    #[test]
+    fn test_events_tagged_with_binding() {
+        let mut recognizer = GestureRecognizer::new(
+            vec![
+                KeyBinding::key(Key::KEY_F13),
+                KeyBinding::key(Key::KEY_ESC),
+                with_modifiers(Key::KEY_F13, &[Key::KEY_LEFTCTRL]),
+            ],
+            TIMING,
+        );
+        let events = run(
+            &mut recognizer,
+            &[
+                (Key::KEY_F13, 1),
+                (Key::KEY_F13, 0),
+                (Key::KEY_ESC, 1),
+                (Key::KEY_LEFTCTRL, 1),
+                (Key::KEY_F13, 1),
+                // Modifier let go before the key: still released
+                (Key::KEY_LEFTCTRL, 0),
+                (Key::KEY_F13, 0),
+            ],
+        );
+        assert_eq!(
+            events,
+            vec![
+                pressed(0),
+                released(0, 100),
+                pressed(1),
+                pressed(2),
+                released(2, 200)
+            ]
+        );
+    }
+
+    #[test]
+    fn test_modifiers_required() {
+        let mut recognizer = GestureRecognizer::new(
+            vec![with_modifiers(Key::KEY_PAUSE, &[Key::KEY_LEFTCTRL])],
+            TIMING,
+        );
+        assert!(run(&mut recognizer, &[(Key::KEY_PAUSE, 1), (Key::KEY_PAUSE, 0)]).is_empty());
+
+        run(&mut recognizer, &[(Key::KEY_LEFTCTRL, 1)]);
+        recognizer.reset();
+        assert!(run(&mut recognizer, &[(Key::KEY_PAUSE, 1)]).is_empty());
+    }
+
+    #[test]
+    fn test_double_tap() {
+        let mut recognizer = GestureRecognizer::new(vec![double_tap(Key::KEY_RIGHTCTRL)], TIMING);
+        let events = run_at(
+            &mut recognizer,
+            &[
+                (0, Key::KEY_RIGHTCTRL, 1),
+                (80, Key::KEY_RIGHTCTRL, 0),
+                (200, Key::KEY_RIGHTCTRL, 1),
+                (900, Key::KEY_RIGHTCTRL, 0),
+                // A third tap right after doesn't count as another double tap
+                (1000, Key::KEY_RIGHTCTRL, 1),
+                (1050, Key::KEY_RIGHTCTRL, 0),
+            ],
+        );
+        assert_eq!(events, vec![pressed(0), released(0, 700)]);
+    }
+
+    #[test]
+    fn test_double_tap_too_slow() {
+        let mut recognizer = GestureRecognizer::new(vec![double_tap(Key::KEY_RIGHTCTRL)], TIMING);
+        let events = run_at(
+            &mut recognizer,
+            &[
+                (0, Key::KEY_RIGHTCTRL, 1),
+                (80, Key::KEY_RIGHTCTRL, 0),
+                (400, Key::KEY_RIGHTCTRL, 1),
+                (450, Key::KEY_RIGHTCTRL, 0),
+                // ...but the late tap can start a new double tap
+                (600, Key::KEY_RIGHTCTRL, 1),
+            ],
+        );
+        assert_eq!(events, vec![pressed(0)]);
+    }
+
+    #[test]
+    fn test_double_tap_interrupted_by_other_key() {
+        // Ctrl+C, Ctrl+V in quick succession is not a double tap of Ctrl
+        let mut recognizer = GestureRecognizer::new(vec![double_tap(Key::KEY_RIGHTCTRL)], TIMING);
+        let events = run_at(
+            &mut recognizer,
+            &[
+                (0, Key::KEY_RIGHTCTRL, 1),
+                (20, Key::KEY_C, 1),
+                (40, Key::KEY_C, 0),
+                (60, Key::KEY_RIGHTCTRL, 0),
+                (100, Key::KEY_RIGHTCTRL, 1),
+                (120, Key::KEY_V, 1),
+                (140, Key::KEY_V, 0),
+                (160, Key::KEY_RIGHTCTRL, 0),
+            ],
+        );
+        assert!(events.is_empty());
+    }
+
+    #[test]
+    fn test_double_tap_preferred_over_single_press() {
+        let mut recognizer = GestureRecognizer::new(
+            vec![KeyBinding::key(Key::KEY_F13), double_tap(Key::KEY_F13)],
+            TIMING,
+        );
+        let events = run(
+            &mut recognizer,
+            &[
+                (Key::KEY_F13, 1),
+                (Key::KEY_F13, 0),
+                (Key::KEY_F13, 1),
+                (Key::KEY_F13, 0),
+            ],
+        );
+        assert_eq!(
+            events,
+            vec![pressed(0), released(0, 100), pressed(1), released(1, 100)]
+        );
+    }
+
+    #[test]
+    fn test_chord_in_any_order() {
+        let mut recognizer = GestureRecognizer::new(vec![chord(&[Key::KEY_J, Key::KEY_K])], TIMING);
+        let events = run_at(
+            &mut recognizer,
+            &[
+                (0, Key::KEY_K, 1),
+                (30, Key::KEY_J, 1),
+                (500, Key::KEY_K, 0),
+                (520, Key::KEY_J, 0),
+            ],
+        );
+        assert_eq!(events, vec![pressed(0), released(0, 470)]);
+    }
+
+    #[test]
+    fn test_chord_too_slow() {
+        let mut recognizer = GestureRecognizer::new(vec![chord(&[Key::KEY_J, Key::KEY_K])], TIMING);
+        let events = run_at(
+            &mut recognizer,
+            &[
+                (0, Key::KEY_J, 1),
+                (250, Key::KEY_K, 1),
+                (300, Key::KEY_K, 0),
+                (310, Key::KEY_J, 0),
+            ],
+        );
+        assert!(events.is_empty());
+    }
+
+    #[test]
+    fn test_bigger_chord_wins() {
+        let mut recognizer = GestureRecognizer::new(
+            vec![
+                chord(&[Key::KEY_J, Key::KEY_K]),
+                chord(&[Key::KEY_J, Key::KEY_K, Key::KEY_L]),
+            ],
+            TIMING,
+        );
+        let events = run_at(
+            &mut recognizer,
+            &[
+                (0, Key::KEY_L, 1),
+                (10, Key::KEY_J, 1),
+                (20, Key::KEY_K, 1),
+                (200, Key::KEY_J, 0),
+            ],
+        );
+        assert_eq!(events, vec![pressed(1), released(1, 180)]);
+    }
+}
@@ -7,20 +7,23 @@
 //! Requires the user to be in the 'input' group.
 
 pub mod evdev_listener;
+pub mod gesture;
 
 use crate::config::HotkeyConfig;
 use crate::error::HotkeyError;
+use std::time::Duration;
 use tokio::sync::mpsc;
 
 /// Events emitted by the hotkey listener
+///
+/// Each event carries the index of the binding that fired, in the order of
+/// [`HotkeyConfig::all_bindings`].
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum HotkeyEvent {
-    /// The hotkey was pressed
-    Pressed,
-    /// The hotkey was released
-    Released,
-    /// The cancel key was pressed (abort recording/transcription)
-    Cancel,
+    /// The binding's key was pressed (with its modifiers held)
+    Pressed { binding: usize },
+    /// The binding's key was released after being held for `held`
+    Released { binding: usize, held: Duration },
 }
 
 /// Trait for hotkey detection implementations
@@ -0,0 +1,160 @@
+//! Client side of the control socket
+//!
+//! Used by `voxtype record` and other short-lived commands. Blocking I/O is
+//! fine here since each command sends a single request and exits.
+
+use super::events::Event;
+use super::{Request, Response};
+use std::io::{self, BufRead, BufReader, Write};
+use std::os::unix::net::UnixStream;
+use std::path::Path;
+use std::time::Duration;
+use ureq::serde_json;
+
+/// How long to wait for the daemon to answer a request
+const RESPONSE_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Send a request to the daemon and wait for its response
+///
+/// Connection errors are returned unchanged so callers can tell a daemon
+/// that isn't listening (`NotFound`, `ConnectionRefused`) from other failures.
+pub fn send_request(path: &Path, request: &Request) -> io::Result<Response> {
+    let (response, _) = request_on_new_connection(path, request)?;
+    Ok(response)
+}
+
+/// Send a start request with the stdout output mode and wait until the
+/// recording is transcribed
+/// Returns the response carrying the text, or the first response if the
+/// recording didn't start.
+pub fn record_to_stdout(path: &Path, request: &Request) -> io::Result<Response> {
+    let (response, mut reader) = request_on_new_connection(path, request)?;
+    if !response.ok {
+        return Ok(response);
+    }
+
+    // Recordings last as long as the user keeps talking
+    reader.get_ref().set_read_timeout(None)?;
+
+    let mut line = String::new();
+    if reader.read_line(&mut line)? == 0 {
+        return Err(io::Error::new(
+            io::ErrorKind::UnexpectedEof,
+            "daemon closed the connection before the text was ready",
+        ));
+    }
+    serde_json::from_str(&line).map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))
+}
+
+/// Subscribe to daemon events
+pub fn subscribe(path: &Path) -> io::Result<Subscription> {
+    let (response, reader) = request_on_new_connection(path, &Request::Subscribe)?;
+    if !response.ok {
+        return Err(io::Error::other(
+            response
+                .error
+                .unwrap_or_else(|| "subscription rejected".to_string()),
+        ));
+    }
+
+    // Events may be arbitrarily far apart
+    reader.get_ref().set_read_timeout(None)?;
+
+    Ok(Subscription {
+        state: response.state.unwrap_or_default(),
+        profile: response.profile,
+        reader,
+    })
+}
+
+/// An open event subscription
+pub struct Subscription {
+    state: String,
+    profile: Option<String>,
+    reader: BufReader<UnixStream>,
+}
+
+impl Subscription {
+    /// Daemon state at the time the subscription was made
+    pub fn state(&self) -> &str {
+        &self.state
+    }
+
+    /// Profile of the current recording at the time the subscription was made
+    pub fn profile(&self) -> Option<&str> {
+        self.profile.as_deref()
+    }
+
+    /// Block until the next event arrives
+    /// Returns `None` once the daemon closes the connection (e.g. on shutdown)
+    pub fn next_event(&mut self) -> io::Result<Option<Event>> {
+        let mut line = String::new();
+        if self.reader.read_line(&mut line)? == 0 {
+            return Ok(None);
+        }
+
+        serde_json::from_str(&line)
+            .map(Some)
+            .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))
+    }
+}
+
+/// Connect, send one request and read its response
+/// Returns the reader so the connection can keep being used
+fn request_on_new_connection(
+    path: &Path,
+    request: &Request,
+) -> io::Result<(Response, BufReader<UnixStream>)> {
+    let mut stream = UnixStream::connect(path)?;
+    stream.set_read_timeout(Some(RESPONSE_TIMEOUT))?;
+
+    let mut json = serde_json::to_string(request)
+        .map_err(|e| io::Error::new(io::ErrorKind::InvalidInput, e))?;
+    json.push('\n');
+    stream.write_all(json.as_bytes())?;
+
+    let mut reader = BufReader::new(stream);
+    let mut line = String::new();
+    reader.read_line(&mut line)?;
+    if line.is_empty() {
+        return Err(io::Error::new(
+            io::ErrorKind::UnexpectedEof,
+            "daemon closed the connection without responding",
+        ));
+    }
+
+    let response =
+        serde_json::from_str(&line).map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;
+    Ok((response, reader))
+}
+
+/// Check whether an error means no daemon is listening on the socket
+pub fn is_not_listening(error: &io::Error) -> bool {
+    matches!(
+        error.kind(),
+        io::ErrorKind::NotFound | io::ErrorKind::ConnectionRefused
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_missing_socket_is_not_listening() {
+        let dir = TempDir::new().unwrap();
+        let err = send_request(&dir.path().join("control.sock"), &Request::Status).unwrap_err();
+        assert!(is_not_listening(&err));
+    }
+
+    #[test]
+    fn test_stale_socket_is_not_listening() {
+        let dir = TempDir::new().unwrap();
+        let path = dir.path().join("control.sock");
+        drop(std::os::unix::net::UnixListener::bind(&path).unwrap());
+
+        let err = send_request(&path, &Request::Status).unwrap_err();
+        assert!(is_not_listening(&err));
+    }
+}
@@ -0,0 +1,107 @@
+//! Daemon events published to subscribers
+//!
+//! Clients send `{"command":"subscribe"}` on the control socket. The daemon
+//! answers with its current state and then keeps the connection open,
+//! writing one event per line as things happen:
+//!
+//! ```text
+//! → {"command":"subscribe"}
+//! ← {"ok":true,"state":"idle"}
+//! ← {"event":"state_changed","state":"recording"}
+//! ← {"event":"recording_started","device":"default"}
+//! ← {"event":"audio_level","level":0.042}
+//! ← {"event":"transcription_finished","text":"hello world","latency_ms":412}
+//! ```
+
+use serde::{Deserialize, Serialize};
+use tokio::sync::broadcast;
+
+/// How many events a slow subscriber may fall behind before it starts
+/// missing them
+pub const EVENT_BUFFER: usize = 64;
+
+/// An event published by the daemon
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "event", rename_all = "snake_case")]
+pub enum Event {
+    /// Daemon state changed ("idle", "recording", "transcribing")
+    StateChanged {
+        state: String,
+        /// Profile of the current recording, if one was selected
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        profile: Option<String>,
+    },
+    /// Audio capture started on the given device
+    RecordingStarted { device: String },
+    /// Input level of the current recording (RMS, 0.0-1.0)
+    AudioLevel { level: f32 },
+    /// Partial transcription of the recording so far (streaming mode)
+    PartialTranscription { text: String },
+    /// Recording stopped and transcription started
+    TranscriptionStarted {
+        /// Length of the recorded audio in seconds
+        audio_secs: f32,
+    },
+    /// Transcription finished
+    TranscriptionFinished {
+        /// Transcribed text, after text processing and post-processing
+        text: String,
+        /// Time spent transcribing and processing, in milliseconds
+        latency_ms: u64,
+    },
+    /// Text was delivered using the named output method
+    OutputCompleted { method: String },
+    /// The last typed transcription was erased
+    Undone {
+        /// Number of characters erased
+        chars: usize,
+    },
+    /// Recording or transcription was cancelled
+    Cancelled,
+    /// Something went wrong
+    Error { message: String },
+}
+
+/// Create the channel the daemon publishes events on
+pub fn channel() -> broadcast::Sender<Event> {
+    broadcast::channel(EVENT_BUFFER).0
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ureq::serde_json;
+
+    #[test]
+    fn test_event_serialization() {
+        let json = serde_json::to_string(&Event::StateChanged {
+            state: "recording".to_string(),
+            profile: None,
+        })
+        .unwrap();
+        assert_eq!(json, r#"{"event":"state_changed","state":"recording"}"#);
+
+        let json = serde_json::to_string(&Event::StateChanged {
+            state: "recording".to_string(),
+            profile: Some("email".to_string()),
+        })
+        .unwrap();
+        assert_eq!(
+            json,
+            r#"{"event":"state_changed","state":"recording","profile":"email"}"#
+        );
+
+        let json = serde_json::to_string(&Event::Cancelled).unwrap();
+        assert_eq!(json, r#"{"event":"cancelled"}"#);
+    }
+
+    #[test]
+    fn test_event_roundtrip() {
+        let event = Event::TranscriptionFinished {
+            text: "hello world".to_string(),
+            latency_ms: 412,
+        };
+        let json = serde_json::to_string(&event).unwrap();
+        assert_eq!(serde_json::from_str::<Event>(&json).unwrap(), event);
+    }
+}
@@ -0,0 +1,242 @@
+//! Control socket for talking to a running daemon
+//!
+//! The daemon listens on a Unix domain socket in the runtime directory
+//! (`$XDG_RUNTIME_DIR/voxtype/control.sock`). Clients such as
+//! `voxtype record` connect, write one JSON request per line, and read one
+//! JSON response per line back:
+//!
+//! ```text
+//! → {"command":"start","output_mode":"paste"}
+//! ← {"ok":true,"state":"recording"}
+//! → {"command":"stop"}
+//! ← {"ok":false,"error":"Not recording"}
+//! ```
+//!
+//! Unlike the legacy SIGUSR1/SIGUSR2 path, every request gets an answer, so
+//! callers learn whether the daemon actually did what they asked.
+//!
+//! A `subscribe` request turns the connection into a stream of daemon
+//! events instead (see [`events`]).
+//!
+//! A `start` request with `"output_mode":"stdout"` gets a second response
+//! once the recording is transcribed, carrying the text:
+//!
+//! ```text
+//! → {"command":"start","output_mode":"stdout"}
+//! ← {"ok":true,"state":"recording"}
+//! ← {"ok":true,"state":"idle","text":"hello world"}
+//! ```
+
+pub mod client;
+pub mod events;
+pub mod server;
+
+use crate::config::{Config, OutputMode};
+use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
+
+/// Get the path of the daemon's control socket
+pub fn socket_path() -> PathBuf {
+    Config::runtime_dir().join("control.sock")
+}
+
+/// A request sent from a client to the daemon
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "command", rename_all = "snake_case")]
+pub enum Request {
+    /// Start recording
+    Start {
+        /// Output mode to use for this recording only
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        output_mode: Option<OutputMode>,
+        /// Profile to use for this recording (see `[profiles]`)
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        profile: Option<String>,
+    },
+    /// Stop recording and transcribe
+    Stop {
+        /// Output mode to use for this recording only
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        output_mode: Option<OutputMode>,
+    },
+    /// Start recording if idle, stop if recording
+    Toggle {
+        /// Output mode to use for this recording only
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        output_mode: Option<OutputMode>,
+        /// Profile to use if this starts a recording
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        profile: Option<String>,
+    },
+    /// Cancel the current recording or transcription
+    Cancel,
+    /// Erase the most recently typed transcription
+    Undo,
+    /// Output the most recent transcription again
+    Replay {
+        /// Output mode to use instead of the configured one
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        output_mode: Option<OutputMode>,
+    },
+    /// Query the current daemon state
+    Status,
+    /// Stream daemon events on this connection until the client hangs up
+    Subscribe,
+}
+
+/// The daemon's answer to a request
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Response {
+    /// Whether the request succeeded
+    pub ok: bool,
+    /// Daemon state after handling the request ("idle", "recording", ...)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub state: Option<String>,
+    /// Error message when `ok` is false
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+    /// Profile of the current recording, if one was selected
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub profile: Option<String>,
+    /// Transcribed text, for a start request with the stdout output mode
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub text: Option<String>,
+}
+
+impl Response {
+    /// Successful response reporting the resulting state
+    pub fn success(state: impl Into<String>) -> Self {
+        Self {
+            ok: true,
+            state: Some(state.into()),
+            error: None,
+            profile: None,
+            text: None,
+        }
+    }
+
+    /// Add the active profile to a response
+    pub fn with_profile(mut self, profile: Option<String>) -> Self {
+        self.profile = profile;
+        self
+    }
+
+    /// Add the transcribed text to a response
+    pub fn with_text(mut self, text: String) -> Self {
+        self.text = Some(text);
+        self
+    }
+
+    /// Failed response with an error message
+    pub fn error(msg: impl Into<String>) -> Self {
+        Self {
+            ok: false,
+            state: None,
+            error: Some(msg.into()),
+            profile: None,
+            text: None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ureq::serde_json;
+
+    #[test]
+    fn test_parse_start_request() {
+        let request: Request = serde_json::from_str(r#"{"command":"start"}"#).unwrap();
+        assert_eq!(
+            request,
+            Request::Start {
+                output_mode: None,
+                profile: None
+            }
+        );
+
+        let request: Request =
+            serde_json::from_str(r#"{"command":"start","profile":"email"}"#).unwrap();
+        assert_eq!(
+            request,
+            Request::Start {
+                output_mode: None,
+                profile: Some("email".to_string())
+            }
+        );
+    }
+
+    #[test]
+    fn test_parse_request_with_output_mode() {
+        let request: Request =
+            serde_json::from_str(r#"{"command":"toggle","output_mode":"paste"}"#).unwrap();
+        assert_eq!(
+            request,
+            Request::Toggle {
+                output_mode: Some(OutputMode::Paste),
+                profile: None
+            }
+        );
+    }
+
+    #[test]
+    fn test_parse_unit_requests() {
+        let cancel: Request = serde_json::from_str(r#"{"command":"cancel"}"#).unwrap();
+        assert_eq!(cancel, Request::Cancel);
+
+        let status: Request = serde_json::from_str(r#"{"command":"status"}"#).unwrap();
+        assert_eq!(status, Request::Status);
+
+        let subscribe: Request = serde_json::from_str(r#"{"command":"subscribe"}"#).unwrap();
+        assert_eq!(subscribe, Request::Subscribe);
+
+        let undo: Request = serde_json::from_str(r#"{"command":"undo"}"#).unwrap();
+        assert_eq!(undo, Request::Undo);
+    }
+
+    #[test]
+    fn test_parse_replay_request() {
+        let request: Request = serde_json::from_str(r#"{"command":"replay"}"#).unwrap();
+        assert_eq!(request, Request::Replay { output_mode: None });
+
+        let request: Request =
+            serde_json::from_str(r#"{"command":"replay","output_mode":"clipboard"}"#).unwrap();
+        assert_eq!(
+            request,
+            Request::Replay {
+                output_mode: Some(OutputMode::Clipboard)
+            }
+        );
+    }
+
+    #[test]
+    fn test_parse_unknown_command_fails() {
+        assert!(serde_json::from_str::<Request>(r#"{"command":"explode"}"#).is_err());
+        assert!(
+            serde_json::from_str::<Request>(r#"{"command":"start","output_mode":"fax"}"#).is_err()
+        );
+    }
+
+    #[test]
+    fn test_request_serialization_omits_empty_override() {
+        let json = serde_json::to_string(&Request::Stop { output_mode: None }).unwrap();
+        assert_eq!(json, r#"{"command":"stop"}"#);
+    }
+
+    #[test]
+    fn test_response_serialization() {
+        let json = serde_json::to_string(&Response::success("recording")).unwrap();
+        assert_eq!(json, r#"{"ok":true,"state":"recording"}"#);
+
+        let json = serde_json::to_string(&Response::error("Not recording")).unwrap();
+        assert_eq!(json, r#"{"ok":false,"error":"Not recording"}"#);
+
+        let response = Response::success("recording").with_profile(Some("email".to_string()));
+        let json = serde_json::to_string(&response).unwrap();
+        assert_eq!(json, r#"{"ok":true,"state":"recording","profile":"email"}"#);
+
+        let response = Response::success("idle").with_text("hello world".to_string());
+        let json = serde_json::to_string(&response).unwrap();
+        assert_eq!(json, r#"{"ok":true,"state":"idle","text":"hello world"}"#);
+    }
+}
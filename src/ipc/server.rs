@@ -0,0 +1,496 @@
+//! Daemon side of the control socket
+//!
+//! Accepts client connections, parses line-delimited JSON requests and
+//! forwards them to the daemon's main loop over a channel. Each request
+//! carries a oneshot sender so the main loop can answer it once the
+//! state machine has acted on it. Other frontends (such as the D-Bus
+//! service) feed the same channel through [`dispatch`].
+//!
+//! Subscribers are served directly from the daemon's event channel. A
+//! stdout recording's request also carries a waiter, which the daemon keeps
+//! with that recording and resolves with its text or the reason there is none.
+
+use super::events::Event;
+use super::{Request, Response};
+use crate::config::OutputMode;
+use crate::output::sink::TextWaiter;
+use serde::Serialize;
+use std::io;
+use std::os::unix::fs::PermissionsExt;
+use std::path::{Path, PathBuf};
+use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
+use tokio::net::unix::OwnedWriteHalf;
+use tokio::net::{UnixListener, UnixStream};
+use tokio::sync::{broadcast, mpsc, oneshot};
+use tokio::task::JoinHandle;
+use ureq::serde_json;
+
+/// A request waiting for the daemon's answer
+#[derive(Debug)]
+pub struct PendingRequest {
+    /// The parsed request
+    pub request: Request,
+    /// Where to send the response
+    reply: oneshot::Sender<Response>,
+    /// Client waiting for the text of the recording this request starts
+    waiter: Option<TextWaiter>,
+}
+
+impl PendingRequest {
+    /// Take the waiter for the recording's text, if the client has one
+    pub fn take_waiter(&mut self) -> Option<TextWaiter> {
+        self.waiter.take()
+    }
+
+    /// Answer the request (the client may already have disconnected)
+    pub fn respond(self, response: Response) {
+        let _ = self.reply.send(response);
+    }
+}
+
+/// Unix socket server for daemon control
+pub struct ControlServer {
+    /// Socket path
+    path: PathBuf,
+    /// Daemon events forwarded to subscribers
+    events: broadcast::Sender<Event>,
+    /// Task accepting new connections
+    accept_task: Option<JoinHandle<()>>,
+}
+
+impl ControlServer {
+    /// Create a control server for the given socket path
+    /// Subscribers receive everything published on `events`
+    pub fn new(path: PathBuf, events: broadcast::Sender<Event>) -> Self {
+        Self {
+            path,
+            events,
+            accept_task: None,
+        }
+    }
+
+    /// Socket path this server listens on
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Bind the socket and start accepting connections
+    /// Incoming requests are sent on `requests`
+    pub async fn start(&mut self, requests: mpsc::Sender<PendingRequest>) -> io::Result<()> {
+        if let Some(parent) = self.path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+
+        // A leftover socket from a crashed daemon is removed, but never
+        // steal the socket from a daemon that is still answering on it
+        if self.path.exists() {
+            if UnixStream::connect(&self.path).await.is_ok() {
+                return Err(io::Error::new(
+                    io::ErrorKind::AddrInUse,
+                    format!("another daemon is listening on {:?}", self.path),
+                ));
+            }
+            std::fs::remove_file(&self.path)?;
+        }
+
+        let listener = UnixListener::bind(&self.path)?;
+        std::fs::set_permissions(&self.path, std::fs::Permissions::from_mode(0o600))?;
+
+        self.accept_task = Some(tokio::spawn(accept_loop(
+            listener,
+            requests,
+            self.events.clone(),
+        )));
+
+        Ok(())
+    }
+
+    /// Stop accepting connections and remove the socket file
+    pub fn stop(&mut self) {
+        if let Some(task) = self.accept_task.take() {
+            task.abort();
+        }
+        if self.path.exists() {
+            if let Err(e) = std::fs::remove_file(&self.path) {
+                tracing::warn!("Failed to remove control socket: {}", e);
+            }
+        }
+    }
+}
+
+impl Drop for ControlServer {
+    fn drop(&mut self) {
+        if let Some(task) = self.accept_task.take() {
+            task.abort();
+        }
+    }
+}
+
+/// Accept connections until the task is aborted
+async fn accept_loop(
+    listener: UnixListener,
+    tx: mpsc::Sender<PendingRequest>,
+    events: broadcast::Sender<Event>,
+) {
+    loop {
+        match listener.accept().await {
+            Ok((stream, _)) => {
+                tokio::spawn(handle_connection(stream, tx.clone(), events.clone()));
+            }
+            Err(e) => {
+                tracing::warn!("Control socket accept failed: {}", e);
+            }
+        }
+    }
+}
+
+/// Serve requests on a single connection until the client hangs up
+async fn handle_connection(
+    stream: UnixStream,
+    tx: mpsc::Sender<PendingRequest>,
+    events: broadcast::Sender<Event>,
+) {
+    let (reader, mut writer) = stream.into_split();
+    let mut lines = BufReader::new(reader).lines();
+
+    while let Ok(Some(line)) = lines.next_line().await {
+        let line = line.trim();
+        if line.is_empty() {
+            continue;
+        }
+
+        let request = match serde_json::from_str::<Request>(line) {
+            Ok(request) => request,
+            Err(e) => {
+                let response = Response::error(format!("Invalid request: {}", e));
+                if !write_line(&mut writer, &response).await {
+                    break;
+                }
+                continue;
+            }
+        };
+        tracing::debug!("Control request: {:?}", request);
+
+        if request == Request::Subscribe {
+            // Subscribe before asking for the state so no event is missed
+            // between the response and the first streamed event
+            let mut event_rx = events.subscribe();
+            let response = dispatch(&tx, request).await;
+            if !write_line(&mut writer, &response).await || !response.ok {
+                break;
+            }
+
+            loop {
+                tokio::select! {
+                    event = event_rx.recv() => match event {
+                        Ok(event) => {
+                            if !write_line(&mut writer, &event).await {
+                                break;
+                            }
+                        }
+                        Err(broadcast::error::RecvError::Lagged(n)) => {
+                            tracing::debug!("Subscriber lagged, skipped {} events", n);
+                        }
+                        Err(broadcast::error::RecvError::Closed) => break,
+                    },
+                    // Anything from the client (including EOF) ends the subscription
+                    _ = lines.next_line() => break,
+                }
+            }
+            break;
+        }
+
+        if let Request::Start {
+            output_mode: Some(OutputMode::Stdout),
+            ..
+        } = request
+        {
+            // The waiter goes along with the request, so the daemon ties it
+            // to the recording it starts
+            let (text_tx, text_rx) = oneshot::channel();
+            let response = deliver(&tx, request, Some(text_tx)).await;
+            if !write_line(&mut writer, &response).await {
+                break;
+            }
+            if !response.ok {
+                continue;
+            }
+
+            let response = tokio::select! {
+                text = text_rx => match text {
+                    Ok(Ok(text)) => Response::success("idle").with_text(text),
+                    Ok(Err(reason)) => Response::error(reason),
+                    Err(_) => Response::error("Daemon dropped the request"),
+                },
+                // The client gave up waiting
+                _ = lines.next_line() => break,
+            };
+            write_line(&mut writer, &response).await;
+            break;
+        }
+
+        let response = dispatch(&tx, request).await;
+        if !write_line(&mut writer, &response).await {
+            break;
+        }
+    }
+}
+
+/// Write a value as one JSON line, returning false if the client is gone
+async fn write_line<T: Serialize>(writer: &mut OwnedWriteHalf, value: &T) -> bool {
+    let Ok(mut json) = serde_json::to_string(value) else {
+        return false;
+    };
+    json.push('\n');
+    writer.write_all(json.as_bytes()).await.is_ok()
+}
+
+/// Create the channel requests are delivered to the daemon on
+pub fn request_channel() -> (mpsc::Sender<PendingRequest>, mpsc::Receiver<PendingRequest>) {
+    mpsc::channel(16)
+}
+
+/// Hand a request to the daemon and wait for its answer
+pub async fn dispatch(tx: &mpsc::Sender<PendingRequest>, request: Request) -> Response {
+    deliver(tx, request, None).await
+}
+
+/// Hand a request to the daemon, along with a waiter for the text of the
+/// recording it starts, and wait for its answer
+async fn deliver(
+    tx: &mpsc::Sender<PendingRequest>,
+    request: Request,
+    waiter: Option<TextWaiter>,
+) -> Response {
+    let (reply_tx, reply_rx) = oneshot::channel();
+    let pending = PendingRequest {
+        request,
+        reply: reply_tx,
+        waiter,
+    };
+
+    if tx.send(pending).await.is_err() {
+        return Response::error("Daemon is shutting down");
+    }
+
+    reply_rx
+        .await
+        .unwrap_or_else(|_| Response::error("Daemon dropped the request"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::OutputMode;
+    use crate::ipc::{client, events};
+    use tempfile::TempDir;
+
+    #[tokio::test]
+    async fn test_request_response_roundtrip() {
+        let dir = TempDir::new().unwrap();
+        let path = dir.path().join("control.sock");
+
+        let mut server = ControlServer::new(path.clone(), events::channel());
+        let (tx, mut rx) = request_channel();
+        server.start(tx).await.unwrap();
+
+        // Answer requests the way the daemon loop would
+        tokio::spawn(async move {
+            while let Some(pending) = rx.recv().await {
+                let response = match &pending.request {
+                    Request::Start {
+                        output_mode: Some(OutputMode::Paste),
+                        ..
+                    } => Response::success("recording"),
+                    Request::Stop { .. } => Response::error("Not recording"),
+                    _ => Response::success("idle"),
+                };
+                pending.respond(response);
+            }
+        });
+
+        let client_path = path.clone();
+        let responses = tokio::task::spawn_blocking(move || {
+            let start = client::send_request(
+                &client_path,
+                &Request::Start {
+                    output_mode: Some(OutputMode::Paste),
+                    profile: None,
+                },
+            )
+            .unwrap();
+            let stop =
+                client::send_request(&client_path, &Request::Stop { output_mode: None }).unwrap();
+            (start, stop)
+        })
+        .await
+        .unwrap();
+
+        assert_eq!(responses.0, Response::success("recording"));
+        assert_eq!(responses.1, Response::error("Not recording"));
+
+        server.stop();
+        assert!(!path.exists());
+    }
+
+    #[tokio::test]
+    async fn test_invalid_request_gets_error_response() {
+        use std::io::{BufRead, BufReader, Write};
+
+        let dir = TempDir::new().unwrap();
+        let path = dir.path().join("control.sock");
+
+        let mut server = ControlServer::new(path.clone(), events::channel());
+        let (tx, _rx) = request_channel();
+        server.start(tx).await.unwrap();
+
+        let line = tokio::task::spawn_blocking(move || {
+            let mut stream = std::os::unix::net::UnixStream::connect(&path).unwrap();
+            stream.write_all(b"not json\n").unwrap();
+            let mut line = String::new();
+            BufReader::new(stream).read_line(&mut line).unwrap();
+            line
+        })
+        .await
+        .unwrap();
+
+        let response: Response = serde_json::from_str(&line).unwrap();
+        assert!(!response.ok);
+        assert!(response.error.unwrap().starts_with("Invalid request"));
+    }
+
+    #[tokio::test]
+    async fn test_subscribe_streams_events() {
+        let dir = TempDir::new().unwrap();
+        let path = dir.path().join("control.sock");
+
+        let events = events::channel();
+        let mut server = ControlServer::new(path.clone(), events.clone());
+        let (tx, mut rx) = request_channel();
+        server.start(tx).await.unwrap();
+
+        tokio::spawn(async move {
+            while let Some(pending) = rx.recv().await {
+                pending.respond(Response::success("idle"));
+            }
+        });
+
+        let client_path = path.clone();
+        let subscriber = tokio::task::spawn_blocking(move || {
+            let mut subscription = client::subscribe(&client_path).unwrap();
+            let state = subscription.state().to_string();
+            let first = subscription.next_event().unwrap();
+            let second = subscription.next_event().unwrap();
+            (state, first, second)
+        });
+
+        // Publish once the subscriber is attached
+        while events.receiver_count() == 0 {
+            tokio::time::sleep(std::time::Duration::from_millis(5)).await;
+        }
+        events
+            .send(Event::StateChanged {
+                state: "recording".to_string(),
+                profile: None,
+            })
+            .unwrap();
+        events.send(Event::Cancelled).unwrap();
+
+        let (state, first, second) = subscriber.await.unwrap();
+        assert_eq!(state, "idle");
+        assert_eq!(
+            first,
+            Some(Event::StateChanged {
+                state: "recording".to_string(),
+                profile: None,
+            })
+        );
+        assert_eq!(second, Some(Event::Cancelled));
+    }
+
+    #[tokio::test]
+    async fn test_stdout_start_waits_for_outcome() {
+        let dir = TempDir::new().unwrap();
+        let path = dir.path().join("control.sock");
+
+        let mut server = ControlServer::new(path.clone(), events::channel());
+        let (tx, mut rx) = request_channel();
+        server.start(tx).await.unwrap();
+
+        // Start, then have the recording stopped with another output mode
+        tokio::spawn(async move {
+            while let Some(mut pending) = rx.recv().await {
+                let waiter = pending.take_waiter().unwrap();
+                pending.respond(Response::success("recording"));
+                let _ = waiter.send(Err("Text was output via clipboard instead".to_string()));
+            }
+        });
+
+        let response = tokio::task::spawn_blocking(move || {
+            client::record_to_stdout(
+                &path,
+                &Request::Start {
+                    output_mode: Some(OutputMode::Stdout),
+                    profile: None,
+                },
+            )
+            .unwrap()
+        })
+        .await
+        .unwrap();
+
+        assert_eq!(
+            response,
+            Response::error("Text was output via clipboard instead")
+        );
+    }
+
+    #[tokio::test]
+    async fn test_rejected_subscribe_gets_error_response() {
+        let dir = TempDir::new().unwrap();
+        let path = dir.path().join("control.sock");
+
+        let mut server = ControlServer::new(path.clone(), events::channel());
+        let (tx, mut rx) = request_channel();
+        server.start(tx).await.unwrap();
+
+        tokio::spawn(async move {
+            while let Some(pending) = rx.recv().await {
+                pending.respond(Response::error("Daemon is shutting down"));
+            }
+        });
+
+        let err = tokio::task::spawn_blocking(move || client::subscribe(&path).err().unwrap())
+            .await
+            .unwrap();
+        assert_eq!(err.to_string(), "Daemon is shutting down");
+    }
+
+    #[tokio::test]
+    async fn test_refuses_to_replace_live_socket() {
+        let dir = TempDir::new().unwrap();
+        let path = dir.path().join("control.sock");
+
+        let mut first = ControlServer::new(path.clone(), events::channel());
+        let (tx, _rx) = request_channel();
+        first.start(tx.clone()).await.unwrap();
+
+        let mut second = ControlServer::new(path.clone(), events::channel());
+        let err = second.start(tx).await.unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::AddrInUse);
+    }
+
+    #[tokio::test]
+    async fn test_replaces_stale_socket() {
+        let dir = TempDir::new().unwrap();
+        let path = dir.path().join("control.sock");
+
+        // Socket file left behind with nobody listening
+        drop(std::os::unix::net::UnixListener::bind(&path).unwrap());
+        assert!(path.exists());
+
+        let mut server = ControlServer::new(path.clone(), events::channel());
+        let (tx, _rx) = request_channel();
+        assert!(server.start(tx).await.is_ok());
+    }
+}
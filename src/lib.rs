@@ -73,15 +73,21 @@ pub mod cli;
 pub mod config;
 pub mod cpu;
 pub mod daemon;
+pub mod dbus;
 pub mod error;
+pub mod history;
 pub mod hotkey;
+pub mod ipc;
 pub mod output;
+pub mod reload;
 pub mod setup;
 pub mod state;
 pub mod text;
 pub mod transcribe;
 
-pub use cli::{Cli, Commands, CompositorType, OutputModeOverride, RecordAction, SetupAction};
+pub use cli::{
+    Cli, Commands, CompositorType, HistoryAction, OutputModeOverride, RecordAction, SetupAction,
+};
 pub use config::Config;
 pub use daemon::Daemon;
 pub use error::{Result, VoxtypeError};
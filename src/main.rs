@@ -7,7 +7,62 @@
 use clap::Parser;
 use std::path::PathBuf;
 use tracing_subscriber::EnvFilter;
-use voxtype::{config, cpu, daemon, setup, transcribe, Cli, Commands, RecordAction, SetupAction};
+use ureq::serde_json;
+use voxtype::ipc::events::Event;
+use voxtype::{
+    config, cpu, daemon, history, output, setup, transcribe, Cli, Commands, HistoryAction,
+    RecordAction, SetupAction,
+};
+
+/// Config settings given on the command line
+/// Kept so they still apply when the daemon reloads its config file
+struct CliOverrides {
+    clipboard: bool,
+    paste: bool,
+    model: Option<String>,
+    hotkey: Option<String>,
+    toggle: bool,
+    wtype_delay: Option<u32>,
+    no_whisper_context_optimization: bool,
+}
+
+impl CliOverrides {
+    fn from_cli(cli: &Cli) -> Self {
+        Self {
+            clipboard: cli.clipboard,
+            paste: cli.paste,
+            model: cli.model.clone(),
+            hotkey: cli.hotkey.clone(),
+            toggle: cli.toggle,
+            wtype_delay: cli.wtype_delay,
+            no_whisper_context_optimization: cli.no_whisper_context_optimization,
+        }
+    }
+
+    fn apply(&self, config: &mut config::Config) {
+        if self.clipboard {
+            config.output.set_mode(config::OutputMode::Clipboard);
+        }
+        if self.paste {
+            config.output.set_mode(config::OutputMode::Paste);
+        }
+        if let Some(ref model) = self.model {
+            config.whisper.model = model.clone();
+        }
+        if let Some(ref hotkey) = self.hotkey {
+            config.hotkey.key = hotkey.clone();
+        }
+        if self.toggle {
+            config.hotkey.mode = config::ActivationMode::Toggle;
+        }
+        if let Some(delay) = self.wtype_delay {
+            config.output.wtype_delay_ms = delay;
+        }
+        if self.no_whisper_context_optimization {
+            config.whisper.context_window_optimization = false;
+        }
+    }
+}
 
 #[tokio::main]
 async fn main() -> anyhow::Result<()> {
@@ -51,36 +106,24 @@ async fn main() -> anyhow::Result<()> {
             .init();
     }
 
-    // Load configuration
+    // Load configuration and apply CLI overrides
+    let overrides = CliOverrides::from_cli(&cli);
     let mut config = config::load_config(cli.config.as_deref())?;
-
-    // Apply CLI overrides
-    if cli.clipboard {
-        config.output.mode = config::OutputMode::Clipboard;
-    }
-    if cli.paste {
-        config.output.mode = config::OutputMode::Paste;
-    }
-    if let Some(model) = cli.model {
-        config.whisper.model = model;
-    }
-    if let Some(hotkey) = cli.hotkey {
-        config.hotkey.key = hotkey;
-    }
-    if cli.toggle {
-        config.hotkey.mode = config::ActivationMode::Toggle;
-    }
-    if let Some(delay) = cli.wtype_delay {
-        config.output.wtype_delay_ms = delay;
-    }
-    if cli.no_whisper_context_optimization {
-        config.whisper.context_window_optimization = false;
-    }
+    overrides.apply(&mut config);
 
     // Run the appropriate command
     match cli.command.unwrap_or(Commands::Daemon) {
         Commands::Daemon => {
-            let mut daemon = daemon::Daemon::new(config);
+            // The daemon re-reads the same file with the same overrides on reload
+            let config_path = cli.config.clone().or_else(config::Config::default_path);
+            let reload_path = config_path.clone();
+            let loader = Box::new(move || {
+                let mut config = config::load_config(reload_path.as_deref())?;
+                overrides.apply(&mut config);
+                Ok(config)
+            });
+
+            let mut daemon = daemon::Daemon::new(config).with_config_reload(config_path, loader);
             daemon.run().await?;
         }
 
@@ -203,13 +246,102 @@ async fn main() -> anyhow::Result<()> {
         Commands::Record { action } => {
             send_record_command(&config, action)?;
         }
+
+        Commands::Replay {
+            type_mode,
+            clipboard,
+            paste,
+        } => {
+            let output_mode = voxtype::OutputModeOverride::from_flags(type_mode, clipboard, paste)
+                .map(config::OutputMode::from);
+            send_replay_command(output_mode)?;
+        }
+
+        Commands::History { action } => {
+            run_history(&config, action.unwrap_or(HistoryAction::List { limit: 20 })).await?;
+        }
     }
 
     Ok(())
 }
 
-/// Send a record command to the running daemon via Unix signals or file triggers
+/// Send a record command to the running daemon via its control socket
+/// Falls back to Unix signals and file triggers for daemons without the socket
 fn send_record_command(config: &config::Config, action: RecordAction) -> anyhow::Result<()> {
+    use voxtype::ipc::{self, Request};
+
+    let output_mode = if action.waits() {
+        Some(config::OutputMode::Stdout)
+    } else {
+        action.output_mode_override().map(config::OutputMode::from)
+    };
+    let profile = action.profile().map(str::to_string);
+    let request = match &action {
+        RecordAction::Start { .. } => Request::Start {
+            output_mode,
+            profile,
+        },
+        RecordAction::Stop { .. } => Request::Stop { output_mode },
+        RecordAction::Toggle { .. } => Request::Toggle {
+            output_mode,
+            profile,
+        },
+        RecordAction::Cancel => Request::Cancel,
+        RecordAction::Undo => Request::Undo,
+    };
+
+    let result = if action.waits() {
+        ipc::client::record_to_stdout(&ipc::socket_path(), &request)
+    } else {
+        ipc::client::send_request(&ipc::socket_path(), &request)
+    };
+    match result {
+        Ok(response) if response.ok => {
+            if let Some(text) = response.text {
+                println!("{}", text);
+            }
+            Ok(())
+        }
+        Ok(response) => {
+            eprintln!(
+                "Error: {}",
+                response.error.as_deref().unwrap_or("Request failed")
+            );
+            std::process::exit(1);
+        }
+        Err(e) if ipc::client::is_not_listening(&e) => {
+            tracing::debug!("Control socket unavailable ({}), using signals", e);
+            send_record_signal(config, action)
+        }
+        Err(e) => Err(anyhow::anyhow!("Failed to talk to daemon: {}", e)),
+    }
+}
+
+/// Ask the running daemon to output its last transcription again
+fn send_replay_command(output_mode: Option<config::OutputMode>) -> anyhow::Result<()> {
+    use voxtype::ipc::{self, Request};
+
+    match ipc::client::send_request(&ipc::socket_path(), &Request::Replay { output_mode }) {
+        Ok(response) if response.ok => Ok(()),
+        Ok(response) => {
+            eprintln!(
+                "Error: {}",
+                response.error.as_deref().unwrap_or("Request failed")
+            );
+            std::process::exit(1);
+        }
+        Err(e) if ipc::client::is_not_listening(&e) => {
+            eprintln!("Error: Voxtype daemon is not running.");
+            eprintln!("Start it with: voxtype daemon");
+            std::process::exit(1);
+        }
+        Err(e) => Err(anyhow::anyhow!("Failed to talk to daemon: {}", e)),
+    }
+}
+
+/// Send a record command to the running daemon via Unix signals or file triggers
+/// Legacy path: the daemon gives no feedback on whether the command succeeded
+fn send_record_signal(config: &config::Config, action: RecordAction) -> anyhow::Result<()> {
     use nix::sys::signal::{kill, Signal};
     use nix::unistd::Pid;
     use voxtype::OutputModeOverride;
@@ -248,6 +380,27 @@ fn send_record_command(config: &config::Config, action: RecordAction) -> anyhow:
         return Ok(());
     }
 
+    // Undo has no signal equivalent
+    if matches!(action, RecordAction::Undo) {
+        eprintln!("Error: This daemon does not support undo (no control socket).");
+        eprintln!("Restart it with a current voxtype: voxtype daemon");
+        std::process::exit(1);
+    }
+
+    // Nor printing the text
+    if action.waits() {
+        eprintln!("Error: This daemon does not support --wait (no control socket).");
+        eprintln!("Restart it with a current voxtype: voxtype daemon");
+        std::process::exit(1);
+    }
+
+    // Neither do profiles
+    if action.profile().is_some() {
+        eprintln!("Error: This daemon does not support profiles (no control socket).");
+        eprintln!("Restart it with a current voxtype: voxtype daemon");
+        std::process::exit(1);
+    }
+
     // Write output mode override file if specified
     if let Some(mode_override) = action.output_mode_override() {
         let override_file = config::Config::runtime_dir().join("output_mode_override");
@@ -290,7 +443,7 @@ fn send_record_command(config: &config::Config, action: RecordAction) -> anyhow:
                 Signal::SIGUSR1 // Start
             }
         }
-        RecordAction::Cancel => unreachable!(), // Handled above
+        RecordAction::Cancel | RecordAction::Undo => unreachable!(), // Handled above
     };
 
     kill(Pid::from_raw(pid), signal)
@@ -347,6 +500,17 @@ fn transcribe_file(config: &config::Config, path: &PathBuf) -> anyhow::Result<()
         mono_samples
     };
 
+    // Trim silence if configured
+    let final_samples = if config.audio.trim.enabled {
+        let trimmed = voxtype::audio::trim::trim_silence(&final_samples, &config.audio.trim);
+        if trimmed.is_empty() {
+            anyhow::bail!("No speech detected in {:?}", path);
+        }
+        trimmed
+    } else {
+        final_samples
+    };
+
     println!(
         "Processing {} samples ({:.2}s)...",
         final_samples.len(),
@@ -442,20 +606,8 @@ async fn run_status(
     extended: bool,
     icon_theme_override: Option<String>,
 ) -> anyhow::Result<()> {
-    let state_file = config.resolve_state_file();
-
-    if state_file.is_none() {
-        eprintln!("Error: state_file is not configured.");
-        eprintln!();
-        eprintln!("To enable status monitoring, add to your config.toml:");
-        eprintln!();
-        eprintln!("  state_file = \"auto\"");
-        eprintln!();
-        eprintln!("This enables external integrations like Waybar to monitor voxtype state.");
-        std::process::exit(1);
-    }
+    use voxtype::ipc::{self, Request};
 
-    let state_path = state_file.unwrap();
     let ext_info = if extended {
         Some(ExtendedStatusInfo::from_config(config))
     } else {
@@ -471,25 +623,151 @@ async fn run_status(
         config.status.resolve_icons()
     };
 
+    let printer = StatusPrinter {
+        format,
+        icons: &icons,
+        ext_info: ext_info.as_ref(),
+    };
+
     if !follow {
-        // One-shot: just read and print current state
-        // First check if daemon is actually running to avoid stale state
-        let state = if !is_daemon_running() {
-            "stopped".to_string()
-        } else {
-            std::fs::read_to_string(&state_path).unwrap_or_else(|_| "stopped".to_string())
+        // One-shot: ask the daemon, falling back to the state file
+        let (state, profile) =
+            match ipc::client::send_request(&ipc::socket_path(), &Request::Status) {
+                Ok(response) if response.ok => {
+                    (response.state.unwrap_or_default(), response.profile)
+                }
+                _ => {
+                    let state_path = require_state_file(config);
+                    // Check if daemon is actually running to avoid stale state
+                    let state = if !is_daemon_running() {
+                        "stopped".to_string()
+                    } else {
+                        std::fs::read_to_string(&state_path)
+                            .unwrap_or_else(|_| "stopped".to_string())
+                    };
+                    (state, None)
+                }
+            };
+
+        printer.print_state(state.trim(), profile.as_deref());
+        return Ok(());
+    }
+
+    // Follow mode: subscribe to daemon events, reconnecting across restarts
+    let mut last_state = String::new();
+    loop {
+        match ipc::client::subscribe(&ipc::socket_path()) {
+            Ok(mut subscription) => {
+                printer.print_state_change(
+                    subscription.state(),
+                    subscription.profile(),
+                    &mut last_state,
+                );
+                loop {
+                    match subscription.next_event() {
+                        Ok(Some(event)) => printer.print_event(&event, &mut last_state),
+                        Ok(None) => break,
+                        Err(e) => {
+                            tracing::warn!("Event stream error: {}", e);
+                            break;
+                        }
+                    }
+                }
+            }
+            Err(e) if ipc::client::is_not_listening(&e) && is_daemon_running() => {
+                // Daemon without a control socket: watch its state file instead
+                tracing::debug!("Control socket unavailable ({}), watching state file", e);
+                let state_path = require_state_file(config);
+                return follow_state_file(&state_path, &printer);
+            }
+            Err(e) if !ipc::client::is_not_listening(&e) => {
+                tracing::warn!("Failed to subscribe to daemon events: {}", e);
+            }
+            Err(_) => {}
+        }
+
+        printer.print_state_change("stopped", None, &mut last_state);
+        std::thread::sleep(std::time::Duration::from_millis(500));
+    }
+}
+
+/// Get the configured state file, exiting with a hint if it is disabled
+fn require_state_file(config: &config::Config) -> PathBuf {
+    match config.resolve_state_file() {
+        Some(path) => path,
+        None => {
+            eprintln!("Error: state_file is not configured.");
+            eprintln!();
+            eprintln!("To enable status monitoring, add to your config.toml:");
+            eprintln!();
+            eprintln!("  state_file = \"auto\"");
+            eprintln!();
+            eprintln!("This enables external integrations like Waybar to monitor voxtype state.");
+            std::process::exit(1);
+        }
+    }
+}
+
+/// Prints status updates in the format requested on the command line
+struct StatusPrinter<'a> {
+    format: &'a str,
+    icons: &'a config::ResolvedIcons,
+    ext_info: Option<&'a ExtendedStatusInfo>,
+}
+
+impl StatusPrinter<'_> {
+    /// Print a daemon state, with the profile of the current recording
+    fn print_state(&self, state: &str, profile: Option<&str>) {
+        match self.format {
+            "json" => println!(
+                "{}",
+                format_state_json(state, profile, self.icons, self.ext_info)
+            ),
+            "events" => self.print_raw_event(&Event::StateChanged {
+                state: state.to_string(),
+                profile: profile.map(str::to_string),
+            }),
+            _ => match profile {
+                Some(profile) => println!("{} ({})", state, profile),
+                None => println!("{}", state),
+            },
+        }
+    }
+
+    /// Print a daemon state if it differs from the last one printed
+    fn print_state_change(&self, state: &str, profile: Option<&str>, last_state: &mut String) {
+        let current = match profile {
+            Some(profile) => format!("{} ({})", state, profile),
+            None => state.to_string(),
         };
-        let state = state.trim();
+        if current != *last_state {
+            self.print_state(state, profile);
+            *last_state = current;
+        }
+    }
 
-        if format == "json" {
-            println!("{}", format_state_json(state, &icons, ext_info.as_ref()));
-        } else {
-            println!("{}", state);
+    /// Print an event from the daemon
+    /// Only state changes are shown unless the format is "events"
+    fn print_event(&self, event: &Event, last_state: &mut String) {
+        match event {
+            Event::StateChanged { state, profile } => {
+                self.print_state_change(state, profile.as_deref(), last_state)
+            }
+            _ if self.format == "events" => self.print_raw_event(event),
+            _ => {}
         }
-        return Ok(());
     }
 
-    // Follow mode: watch for changes using inotify
+    /// Print an event as a JSON line
+    fn print_raw_event(&self, event: &Event) {
+        if let Ok(json) = ureq::serde_json::to_string(event) {
+            println!("{}", json);
+        }
+    }
+}
+
+/// Follow the daemon state by watching the state file (legacy daemons)
+fn follow_state_file(state_path: &std::path::Path, printer: &StatusPrinter) -> anyhow::Result<()> {
     use notify::{Config as NotifyConfig, RecommendedWatcher, RecursiveMode, Watcher};
     use std::sync::mpsc::channel;
     use std::time::Duration;
@@ -498,14 +776,10 @@ async fn run_status(
     let state = if !is_daemon_running() {
         "stopped".to_string()
     } else {
-        std::fs::read_to_string(&state_path).unwrap_or_else(|_| "stopped".to_string())
+        std::fs::read_to_string(state_path).unwrap_or_else(|_| "stopped".to_string())
     };
-    let state = state.trim();
-    if format == "json" {
-        println!("{}", format_state_json(state, &icons, ext_info.as_ref()));
-    } else {
-        println!("{}", state);
-    }
+    let mut last_state = String::new();
+    printer.print_state_change(state.trim(), None, &mut last_state);
 
     // Set up file watcher
     let (tx, rx) = channel();
@@ -524,28 +798,15 @@ async fn run_status(
 
     // Also try to watch the file directly if it exists
     if state_path.exists() {
-        let _ = watcher.watch(&state_path, RecursiveMode::NonRecursive);
+        let _ = watcher.watch(state_path, RecursiveMode::NonRecursive);
     }
 
-    let mut last_state = state.to_string();
-
     loop {
         match rx.recv_timeout(Duration::from_millis(500)) {
             Ok(Ok(_event)) => {
                 // File changed, read new state
-                if let Ok(new_state) = std::fs::read_to_string(&state_path) {
-                    let new_state = new_state.trim().to_string();
-                    if new_state != last_state {
-                        if format == "json" {
-                            println!(
-                                "{}",
-                                format_state_json(&new_state, &icons, ext_info.as_ref())
-                            );
-                        } else {
-                            println!("{}", new_state);
-                        }
-                        last_state = new_state;
-                    }
+                if let Ok(new_state) = std::fs::read_to_string(state_path) {
+                    printer.print_state_change(new_state.trim(), None, &mut last_state);
                 }
             }
             Ok(Err(e)) => {
@@ -553,16 +814,8 @@ async fn run_status(
             }
             Err(std::sync::mpsc::RecvTimeoutError::Timeout) => {
                 // Check if daemon stopped (file deleted or process died)
-                if (!state_path.exists() || !is_daemon_running()) && last_state != "stopped" {
-                    if format == "json" {
-                        println!(
-                            "{}",
-                            format_state_json("stopped", &icons, ext_info.as_ref())
-                        );
-                    } else {
-                        println!("stopped");
-                    }
-                    last_state = "stopped".to_string();
+                if !state_path.exists() || !is_daemon_running() {
+                    printer.print_state_change("stopped", None, &mut last_state);
                 }
             }
             Err(std::sync::mpsc::RecvTimeoutError::Disconnected) => {
@@ -578,6 +831,7 @@ async fn run_status(
 /// The `alt` field enables Waybar's format-icons feature for custom icon mapping
 fn format_state_json(
     state: &str,
+    profile: Option<&str>,
     icons: &config::ResolvedIcons,
     extended: Option<&ExtendedStatusInfo>,
 ) -> String {
@@ -594,24 +848,146 @@ fn format_state_json(
     let alt = state;
     let class = state;
 
-    match extended {
-        Some(info) => {
-            // Extended format includes model, device, backend
-            let tooltip = format!(
-                "{}\\nModel: {}\\nDevice: {}\\nBackend: {}",
-                base_tooltip, info.model, info.device, info.backend
+    // Profile of the current recording, if one was selected
+    let mut tooltip = base_tooltip.to_string();
+    if let Some(profile) = profile {
+        tooltip.push_str(&format!("\nProfile: {}", profile));
+    }
+
+    let mut json = serde_json::json!({
+        "text": text,
+        "alt": alt,
+        "class": class,
+    });
+    if let Some(info) = extended {
+        // Extended format includes model, device, backend
+        tooltip.push_str(&format!(
+            "\nModel: {}\nDevice: {}\nBackend: {}",
+            info.model, info.device, info.backend
+        ));
+        json["model"] = info.model.as_str().into();
+        json["device"] = info.device.as_str().into();
+        json["backend"] = info.backend.as_str().into();
+    }
+    json["tooltip"] = tooltip.into();
+    if let Some(profile) = profile {
+        json["profile"] = profile.into();
+    }
+    json.to_string()
+}
+
+/// Browse and reuse stored transcriptions
+async fn run_history(config: &config::Config, action: HistoryAction) -> anyhow::Result<()> {
+    let history = history::History::new(&config.history);
+
+    match action {
+        HistoryAction::List { limit } => {
+            let entries = history.entries()?;
+            if entries.is_empty() {
+                if config.history.enabled {
+                    println!("No transcriptions in history yet.");
+                } else {
+                    println!("History is disabled (set [history] enabled = true to store transcriptions).");
+                }
+            }
+            print_history_entries(&entries, limit);
+        }
+
+        HistoryAction::Search { query, limit } => {
+            let matches = history.search(&query)?;
+            if matches.is_empty() {
+                println!("No transcriptions matching {:?}.", query);
+            }
+            print_history_entries(&matches, limit);
+        }
+
+        HistoryAction::Show { id } => {
+            let entry = require_history_entry(&history, id)?;
+            println!("Entry:     {}", entry.id);
+            println!("Time:      {}", history::format_timestamp(entry.timestamp));
+            println!("Model:     {}", entry.model);
+            println!("Duration:  {:.1}s", entry.duration_secs);
+            println!(
+                "Output:    {}",
+                entry.output_method.as_deref().unwrap_or("failed")
             );
-            format!(
-                r#"{{"text": "{}", "alt": "{}", "class": "{}", "tooltip": "{}", "model": "{}", "device": "{}", "backend": "{}"}}"#,
-                text, alt, class, tooltip, info.model, info.device, info.backend
-            )
+            println!("Text:      {}", entry.final_text);
+            // Intermediate stages, when text or post-processing changed anything
+            if entry.processed_text != entry.final_text && entry.processed_text != entry.raw_text {
+                println!("Processed: {}", entry.processed_text);
+            }
+            if entry.raw_text != entry.final_text {
+                println!("Raw:       {}", entry.raw_text);
+            }
         }
-        None => {
+
+        HistoryAction::Copy { id } => {
+            let entry = require_history_entry(&history, id)?;
+            let mut output_config = config.output.clone();
+            output_config.set_mode(config::OutputMode::Clipboard);
+            let chain = output::create_output_chain(&output_config);
+            let options = output::OutputOptions {
+                pre_output_command: None,
+                post_output_command: None,
+            };
+            output::output_with_fallback(&chain, &entry.final_text, options).await?;
+            println!("Copied entry {} to the clipboard.", entry.id);
+        }
+
+        HistoryAction::Type { id } => {
+            let entry = require_history_entry(&history, id)?;
+            let mut output_config = config.output.clone();
+            output_config.set_mode(config::OutputMode::Type);
+            let chain = output::create_output_chain(&output_config);
+            let options = output::OutputOptions {
+                pre_output_command: output_config.pre_output_command.as_deref(),
+                post_output_command: output_config.post_output_command.as_deref(),
+            };
+            output::output_with_fallback(&chain, &entry.final_text, options).await?;
+        }
+
+        HistoryAction::Clear => {
+            history.clear()?;
+            println!("History cleared.");
+        }
+    }
+
+    Ok(())
+}
+
+/// Look up a history entry, failing with a readable message if it doesn't exist
+fn require_history_entry(
+    history: &history::History,
+    id: Option<u64>,
+) -> anyhow::Result<history::HistoryEntry> {
+    match (history.get(id)?, id) {
+        (Some(entry), _) => Ok(entry),
+        (None, Some(id)) => anyhow::bail!("No history entry {}", id),
+        (None, None) => anyhow::bail!("History is empty"),
+    }
+}
+
+/// Print the newest `limit` entries as one line each, oldest first
+fn print_history_entries(entries: &[history::HistoryEntry], limit: usize) {
+    const PREVIEW_CHARS: usize = 60;
+
+    for entry in &entries[entries.len().saturating_sub(limit)..] {
+        let text = entry.final_text.replace('\n', " ");
+        let preview = if text.chars().count() > PREVIEW_CHARS {
             format!(
-                r#"{{"text": "{}", "alt": "{}", "class": "{}", "tooltip": "{}"}}"#,
-                text, alt, class, base_tooltip
+                "{}...",
+                text.chars().take(PREVIEW_CHARS).collect::<String>()
             )
-        }
+        } else {
+            text
+        };
+        println!(
+            "{:>5}  {}  {:>5.1}s  {}",
+            entry.id,
+            history::format_timestamp(entry.timestamp),
+            entry.duration_secs,
+            preview
+        );
     }
 }
 
@@ -624,6 +1000,53 @@ async fn show_config(config: &config::Config) -> anyhow::Result<()> {
     println!("  key = {:?}", config.hotkey.key);
     println!("  modifiers = {:?}", config.hotkey.modifiers);
     println!("  mode = {:?}", config.hotkey.mode);
+    if config.hotkey.mode == config::ActivationMode::Hybrid {
+        println!("  tap_threshold_ms = {}", config.hotkey.tap_threshold_ms);
+    }
+    if config.hotkey.grab != config::GrabMode::None {
+        println!("  grab = {:?}", config.hotkey.grab);
+    }
+    for binding in &config.hotkey.bindings {
+        println!("\n[[hotkey.bindings]]");
+        println!("  key = {:?}", binding.key);
+        if !binding.modifiers.is_empty() {
+            println!("  modifiers = {:?}", binding.modifiers);
+        }
+        if !binding.chord.is_empty() {
+            println!("  chord = {:?}", binding.chord);
+        }
+        if binding.double_tap {
+            println!("  double_tap = true");
+        }
+        println!("  action = {:?}", binding.action);
+        if let Some(mode) = binding.mode {
+            println!("  mode = {:?}", mode);
+        }
+        if let Some(ref mode) = binding.output_mode {
+            println!("  output_mode = {:?}", mode);
+        }
+        if let Some(ref language) = binding.language {
+            println!("  language = {:?}", language);
+        }
+        if let Some(ref profile) = binding.profile {
+            println!("  profile = {:?}", profile);
+        }
+    }
+    for device in &config.hotkey.devices {
+        println!("\n[[hotkey.devices]]");
+        if let Some(ref name) = device.name {
+            println!("  name = {:?}", name);
+        }
+        if let Some(id) = device.vendor_id {
+            println!("  vendor_id = {:#06x}", id);
+        }
+        if let Some(id) = device.product_id {
+            println!("  product_id = {:#06x}", id);
+        }
+        if let Some(ref path) = device.path {
+            println!("  path = {:?}", path);
+        }
+    }
 
     println!("\n[audio]");
     println!("  device = {:?}", config.audio.device);
@@ -651,6 +1074,38 @@ async fn show_config(config: &config::Config) -> anyhow::Result<()> {
     );
     println!("  type_delay_ms = {}", config.output.type_delay_ms);
     println!("  wtype_delay_ms = {}", config.output.wtype_delay_ms);
+    if let Some(ref layout) = config.output.keyboard_layout {
+        println!("  keyboard_layout = {:?}", layout);
+    }
+    if config.output.mode == config::OutputMode::Paste {
+        println!("  paste_selection = {:?}", config.output.paste_selection);
+        println!("  restore_clipboard = {}", config.output.restore_clipboard);
+        if config.output.restore_clipboard {
+            println!(
+                "  restore_clipboard_delay_ms = {}",
+                config.output.restore_clipboard_delay_ms
+            );
+        }
+    }
+    if let Some(ref chain) = config.output.chain {
+        let names: Vec<String> = chain.iter().map(|e| e.backend().to_string()).collect();
+        println!("  chain = [{}]", names.join(", "));
+    }
+
+    if let Some(ref file) = config.output.file {
+        println!("\n[output.file]");
+        println!("  path = {:?}", file.path);
+        println!("  template = {:?}", file.template);
+    }
+    if let Some(ref fifo) = config.output.fifo {
+        println!("\n[output.fifo]");
+        println!("  path = {:?}", fifo.path);
+    }
+    if let Some(ref command) = config.output.command {
+        println!("\n[output.command]");
+        println!("  command = {:?}", command.command);
+        println!("  timeout_ms = {}", command.timeout_ms);
+    }
 
     println!("\n[output.notification]");
     println!(
@@ -674,6 +1129,61 @@ async fn show_config(config: &config::Config) -> anyhow::Result<()> {
         icons.idle, icons.recording, icons.transcribing, icons.stopped
     );
 
+    println!("\n[history]");
+    println!("  enabled = {}", config.history.enabled);
+    println!("  max_entries = {}", config.history.max_entries);
+    println!("  max_age_days = {}", config.history.max_age_days);
+    println!("  (stored in: {:?})", history::History::default_path());
+
+    if !config.profiles.is_empty() {
+        let mut names: Vec<&String> = config.profiles.keys().collect();
+        names.sort();
+        for name in names {
+            println!("\n[profiles.{}]", name);
+            let profile = &config.profiles[name];
+            for (section, overrides) in [
+                ("whisper", &profile.whisper),
+                ("text", &profile.text),
+                ("output", &profile.output),
+            ] {
+                for (key, value) in overrides {
+                    println!("  {}.{} = {}", section, key, value);
+                }
+            }
+            match profile.post_process {
+                Some(config::PostProcessOverride::Enabled(enabled)) => {
+                    println!("  post_process = {}", enabled)
+                }
+                Some(config::PostProcessOverride::Settings(ref settings)) => {
+                    for (key, value) in settings {
+                        println!("  post_process.{} = {}", key, value);
+                    }
+                }
+                None => {}
+            }
+        }
+    }
+
+    for rule in &config.app_rules {
+        println!("\n[[app_rules]]");
+        println!("  app = {:?}", rule.app);
+        if let Some(ref mode) = rule.mode {
+            println!("  mode = {:?}", mode);
+        }
+        if let Some(ref paste_keys) = rule.paste_keys {
+            println!("  paste_keys = {:?}", paste_keys);
+        }
+        if let Some(auto_submit) = rule.auto_submit {
+            println!("  auto_submit = {}", auto_submit);
+        }
+        if let Some(delay) = rule.type_delay_ms {
+            println!("  type_delay_ms = {}", delay);
+        }
+        if let Some(ref profile) = rule.profile {
+            println!("  profile = {:?}", profile);
+        }
+    }
+
     if let Some(ref state_file) = config.state_file {
         println!("\n[integration]");
         println!("  state_file = {:?}", state_file);
@@ -683,7 +1193,10 @@ async fn show_config(config: &config::Config) -> anyhow::Result<()> {
     }
 
     // Show output chain status
-    let output_status = setup::detect_output_chain().await;
+    let output_status = setup::detect_output_chain()
+        .await
+        .with_output_mode(&config.output.mode)
+        .with_configured_chain(config.output.chain.as_deref());
     setup::print_output_chain_status(&output_status);
 
     println!("\n---");
@@ -695,3 +1208,28 @@ async fn show_config(config: &config::Config) -> anyhow::Result<()> {
 
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_state_json_escapes_profile_name() {
+        let icons = config::ResolvedIcons {
+            idle: "I".to_string(),
+            recording: "R".to_string(),
+            transcribing: "T".to_string(),
+            stopped: "S".to_string(),
+        };
+        let profile = r#"say "hi" \ bye"#;
+        let json = format_state_json("recording", Some(profile), &icons, None);
+
+        let value: serde_json::Value = serde_json::from_str(&json).unwrap();
+        assert_eq!(value["profile"], profile);
+        assert_eq!(value["text"], "R");
+        assert_eq!(
+            value["tooltip"],
+            format!("Recording...\nProfile: {}", profile)
+        );
+    }
+}
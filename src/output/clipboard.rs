@@ -2,25 +2,61 @@
 //!
 //! Uses wl-copy to copy text to the Wayland clipboard.
 //! This is the most reliable fallback as it works on all Wayland compositors.
+//! On X11 the CLIPBOARD and PRIMARY selections are set directly instead.
+//! Also reads and restores whole selections, for paste mode.
 //!
-//! Requires: wl-clipboard package installed
+//! Requires: wl-clipboard package installed (Wayland only)
 
-use super::TextOutput;
+use super::{data_control, x11, TextOutput};
+use crate::config::{OutputBackend, PasteSelection};
 use crate::error::OutputError;
 use std::process::Stdio;
 use tokio::io::AsyncWriteExt;
 use tokio::process::Command;
 
+/// Contents of a selection: (MIME type, or target on X11, and data) in
+/// the order the owner offered them
+pub type Contents = Vec<(String, Vec<u8>)>;
+
+/// Read `selection` in every type it is offered in
+pub async fn read_selection(selection: PasteSelection, x11: bool) -> Result<Contents, OutputError> {
+    if x11 {
+        return x11::read_selection(selection).await;
+    }
+    data_control::read_selection(selection)
+        .await
+        .map_err(|e| OutputError::InjectionFailed(format!("cannot read the clipboard: {}", e)))
+}
+
+/// Put contents read by `read_selection` back
+pub async fn restore_selection(
+    selection: PasteSelection,
+    x11: bool,
+    contents: Contents,
+) -> Result<(), OutputError> {
+    if x11 {
+        return x11::restore_selection(selection, contents).await;
+    }
+    data_control::set_selection(selection, contents)
+        .await
+        .map_err(|e| OutputError::InjectionFailed(format!("cannot restore the clipboard: {}", e)))
+}
+
 /// Clipboard-based text output
 pub struct ClipboardOutput {
     /// Whether to show a desktop notification
     notify: bool,
+    /// Whether this is an X11 session (set the X selections, not wl-copy)
+    x11: bool,
 }
 
 impl ClipboardOutput {
     /// Create a new clipboard output
     pub fn new(notify: bool) -> Self {
-        Self { notify }
+        Self {
+            notify,
+            x11: crate::setup::detect_display_server() == crate::setup::DisplayServer::X11,
+        }
     }
 
     /// Send a desktop notification
@@ -54,6 +90,15 @@ impl TextOutput for ClipboardOutput {
             return Ok(());
         }
 
+        if self.x11 {
+            x11::set_selections(text).await?;
+            if self.notify {
+                self.send_notification(text).await;
+            }
+            tracing::info!("Text copied to X11 selections ({} chars)", text.len());
+            return Ok(());
+        }
+
         // Spawn wl-copy with stdin pipe
         let mut child = Command::new("wl-copy")
             .stdin(Stdio::piped())
@@ -101,6 +146,10 @@ impl TextOutput for ClipboardOutput {
     }
 
     async fn is_available(&self) -> bool {
+        if self.x11 {
+            return x11::probe().await.is_ok();
+        }
+
         Command::new("which")
             .arg("wl-copy")
             .stdout(Stdio::null())
@@ -112,7 +161,15 @@ impl TextOutput for ClipboardOutput {
     }
 
     fn name(&self) -> &'static str {
-        "clipboard (wl-copy)"
+        if self.x11 {
+            "clipboard (X11)"
+        } else {
+            "clipboard (wl-copy)"
+        }
+    }
+
+    fn backend(&self) -> OutputBackend {
+        OutputBackend::Clipboard
     }
 }
 
@@ -0,0 +1,690 @@
+//! Wayland clipboard access through the data control protocol
+//!
+//! ext-data-control-v1 (or its predecessor, wlr-data-control-unstable-v1)
+//! lets a client without a focused window read and set the clipboard and
+//! the primary selection. Paste mode uses it to save what the user had
+//! copied, in every MIME type it is offered in, and to put it back after
+//! pasting. Unlike wl-paste, reading all types takes a single connection.
+//!
+//! Both protocols have the same requests and events, so their objects are
+//! wrapped in [`Either`] and handled by the same code. All compositor I/O
+//! happens on a blocking thread.
+//!
+//! Setting a selection means serving it: a thread sends the data to each
+//! application that pastes it, until another client sets the selection.
+//!
+//! Requires a compositor offering one of the protocols (Sway, Hyprland,
+//! niri, KDE Plasma, ...; not GNOME).
+
+use super::clipboard::Contents;
+use super::wayland::{self, Globals, WithGlobals};
+use crate::config::PasteSelection;
+use std::fs::File;
+use std::io::{self, Read, Write};
+use std::os::fd::{AsFd, FromRawFd, OwnedFd};
+use std::time::{Duration, Instant};
+use wayland_client::backend::ObjectId;
+use wayland_client::protocol::wl_seat::WlSeat;
+use wayland_client::{
+    delegate_noop, event_created_child, Connection, Dispatch, EventQueue, Proxy, QueueHandle,
+};
+
+/// ext-data-control-v1, under the names both protocols share
+mod ext {
+    pub use wayland_protocols::ext::data_control::v1::client::{
+        ext_data_control_device_v1::{self as device, ExtDataControlDeviceV1 as Device},
+        ext_data_control_manager_v1::ExtDataControlManagerV1 as Manager,
+        ext_data_control_offer_v1::{self as offer, ExtDataControlOfferV1 as Offer},
+        ext_data_control_source_v1::{self as source, ExtDataControlSourceV1 as Source},
+    };
+}
+
+/// wlr-data-control-unstable-v1, under the names both protocols share
+mod wlr {
+    pub use wayland_protocols_wlr::data_control::v1::client::{
+        zwlr_data_control_device_v1::{self as device, ZwlrDataControlDeviceV1 as Device},
+        zwlr_data_control_manager_v1::ZwlrDataControlManagerV1 as Manager,
+        zwlr_data_control_offer_v1::{self as offer, ZwlrDataControlOfferV1 as Offer},
+        zwlr_data_control_source_v1::{self as source, ZwlrDataControlSourceV1 as Source},
+    };
+}
+
+/// How long the selection owner gets to send all of its types
+const READ_TIMEOUT: Duration = Duration::from_secs(2);
+
+/// An object of either data control protocol
+#[derive(Debug, Clone, PartialEq)]
+enum Either<E, W> {
+    Ext(E),
+    Wlr(W),
+}
+
+type Manager = Either<ext::Manager, wlr::Manager>;
+type Device = Either<ext::Device, wlr::Device>;
+type Source = Either<ext::Source, wlr::Source>;
+type Offer = Either<ext::Offer, wlr::Offer>;
+
+/// Evaluate `$body` with the protocol object inside an [`Either`]
+macro_rules! either {
+    ($value:expr, $object:ident => $body:expr) => {
+        match $value {
+            Either::Ext($object) => $body,
+            Either::Wlr($object) => $body,
+        }
+    };
+}
+
+impl Manager {
+    /// Bind the preferred manager the compositor offers
+    fn bind(globals: &Globals, qh: &QueueHandle<Client>) -> io::Result<Self> {
+        if globals.get(ext::Manager::interface().name).is_some() {
+            Ok(Either::Ext(globals.bind(qh, 1)?))
+        } else if globals.get(wlr::Manager::interface().name).is_some() {
+            Ok(Either::Wlr(globals.bind(qh, 2)?))
+        } else {
+            Err(io::Error::new(
+                io::ErrorKind::Unsupported,
+                "compositor doesn't support the data control protocol",
+            ))
+        }
+    }
+
+    /// Whether the primary selection is supported (wlr version 2+)
+    fn supports_primary(&self) -> bool {
+        match self {
+            Either::Ext(_) => true,
+            Either::Wlr(manager) => manager.version() >= 2,
+        }
+    }
+
+    fn get_data_device(&self, seat: &WlSeat, qh: &QueueHandle<Client>) -> Device {
+        match self {
+            Either::Ext(manager) => Either::Ext(manager.get_data_device(seat, qh, ())),
+            Either::Wlr(manager) => Either::Wlr(manager.get_data_device(seat, qh, ())),
+        }
+    }
+
+    fn create_data_source(&self, qh: &QueueHandle<Client>) -> Source {
+        match self {
+            Either::Ext(manager) => Either::Ext(manager.create_data_source(qh, ())),
+            Either::Wlr(manager) => Either::Wlr(manager.create_data_source(qh, ())),
+        }
+    }
+}
+
+impl Device {
+    fn set_selection(&self, selection: PasteSelection, source: &Source) {
+        match (self, source, selection) {
+            (Either::Ext(device), Either::Ext(source), PasteSelection::Clipboard) => {
+                device.set_selection(Some(source))
+            }
+            (Either::Ext(device), Either::Ext(source), PasteSelection::Primary) => {
+                device.set_primary_selection(Some(source))
+            }
+            (Either::Wlr(device), Either::Wlr(source), PasteSelection::Clipboard) => {
+                device.set_selection(Some(source))
+            }
+            (Either::Wlr(device), Either::Wlr(source), PasteSelection::Primary) => {
+                device.set_primary_selection(Some(source))
+            }
+            _ => unreachable!("device and source come from the same manager"),
+        }
+    }
+}
+
+/// Dispatch state: what the compositor announced
+#[derive(Default)]
+struct Client {
+    globals: Globals,
+    /// Offers announced so far, with their MIME types
+    offers: Vec<(Offer, Vec<String>)>,
+    /// Current offers of the clipboard and the primary selection
+    clipboard: Option<Offer>,
+    primary: Option<Offer>,
+    /// Requests for our source's data: (MIME type, where to write it)
+    requests: Vec<(String, OwnedFd)>,
+    /// Our source was replaced, or the device is gone
+    done: bool,
+}
+
+impl WithGlobals for Client {
+    fn globals(&mut self) -> &mut Globals {
+        &mut self.globals
+    }
+}
+
+impl Client {
+    fn add_type(&mut self, offer: ObjectId, mime_type: String) {
+        let offer = self
+            .offers
+            .iter_mut()
+            .find(|(o, _)| either!(o, o => o.id()) == offer);
+        if let Some((_, types)) = offer {
+            types.push(mime_type);
+        }
+    }
+}
+
+wayland::delegate_globals!(Client);
+delegate_noop!(Client: ignore WlSeat);
+
+/// Dispatch the events of one protocol's objects into [`Client`]
+macro_rules! dispatch {
+    ($variant:ident, $protocol:ident) => {
+        delegate_noop!(Client: $protocol::Manager);
+
+        impl Dispatch<$protocol::Device, ()> for Client {
+            fn event(
+                client: &mut Self,
+                _: &$protocol::Device,
+                event: $protocol::device::Event,
+                _: &(),
+                _: &Connection,
+                _: &QueueHandle<Self>,
+            ) {
+                match event {
+                    $protocol::device::Event::DataOffer { id } => {
+                        client.offers.push((Either::$variant(id), Vec::new()))
+                    }
+                    $protocol::device::Event::Selection { id } => {
+                        client.clipboard = id.map(Either::$variant)
+                    }
+                    $protocol::device::Event::PrimarySelection { id } => {
+                        client.primary = id.map(Either::$variant)
+                    }
+                    $protocol::device::Event::Finished => client.done = true,
+                    _ => {}
+                }
+            }
+
+            event_created_child!(Client, $protocol::Device, [
+                $protocol::device::EVT_DATA_OFFER_OPCODE => ($protocol::Offer, ()),
+            ]);
+        }
+
+        impl Dispatch<$protocol::Offer, ()> for Client {
+            fn event(
+                client: &mut Self,
+                offer: &$protocol::Offer,
+                event: $protocol::offer::Event,
+                _: &(),
+                _: &Connection,
+                _: &QueueHandle<Self>,
+            ) {
+                if let $protocol::offer::Event::Offer { mime_type } = event {
+                    client.add_type(offer.id(), mime_type);
+                }
+            }
+        }
+
+        impl Dispatch<$protocol::Source, ()> for Client {
+            fn event(
+                client: &mut Self,
+                _: &$protocol::Source,
+                event: $protocol::source::Event,
+                _: &(),
+                _: &Connection,
+                _: &QueueHandle<Self>,
+            ) {
+                match event {
+                    $protocol::source::Event::Send { mime_type, fd } => {
+                        client.requests.push((mime_type, fd))
+                    }
+                    $protocol::source::Event::Cancelled => client.done = true,
+                    _ => {}
+                }
+            }
+        }
+    };
+}
+
+dispatch!(Ext, ext);
+dispatch!(Wlr, wlr);
+
+/// A data control device for the seat
+struct DataControl {
+    connection: Connection,
+    queue: EventQueue<Client>,
+    client: Client,
+    manager: Manager,
+    device: Device,
+}
+
+impl DataControl {
+    fn new(connection: Connection) -> io::Result<Self> {
+        let mut client = Client::default();
+        let mut queue = wayland::event_queue(&connection, &mut client)?;
+        let qh = queue.handle();
+        let manager = Manager::bind(&client.globals, &qh)?;
+        let seat: WlSeat = client.globals.bind(&qh, 1)?;
+        let device = manager.get_data_device(&seat, &qh);
+
+        // The compositor answers get_data_device with the current selections
+        wayland::roundtrip(&connection, &mut queue, &mut client)?;
+        Ok(Self {
+            connection,
+            queue,
+            client,
+            manager,
+            device,
+        })
+    }
+
+    fn check_supported(&self, selection: PasteSelection) -> io::Result<()> {
+        if selection == PasteSelection::Primary && !self.manager.supports_primary() {
+            return Err(io::Error::new(
+                io::ErrorKind::Unsupported,
+                "compositor doesn't support the primary selection for data control",
+            ));
+        }
+        Ok(())
+    }
+
+    /// Read the current contents of `selection` in all offered types
+    fn read(&mut self, selection: PasteSelection) -> io::Result<Contents> {
+        self.check_supported(selection)?;
+
+        let current = match selection {
+            PasteSelection::Clipboard => &self.client.clipboard,
+            PasteSelection::Primary => &self.client.primary,
+        };
+        let Some((offer, types)) = current
+            .as_ref()
+            .and_then(|current| self.client.offers.iter().find(|(o, _)| o == current))
+        else {
+            // Nothing selected
+            return Ok(Vec::new());
+        };
+
+        let deadline = Instant::now() + READ_TIMEOUT;
+        let mut contents: Contents = Vec::new();
+        for mime_type in types {
+            if contents.iter().any(|(t, _)| t == mime_type) {
+                continue;
+            }
+            let (reader, writer) = pipe()?;
+            either!(offer, o => o.receive(mime_type.clone(), writer.as_fd()));
+            wayland::flush(&self.connection)?;
+            // The owner's copy is the only writer left, so EOF ends the data
+            drop(writer);
+            let data = read_until_eof(reader, deadline)?;
+            contents.push((mime_type.clone(), data));
+        }
+        Ok(contents)
+    }
+
+    /// Offer `contents` as `selection`
+    ///
+    /// Requests for the data may already arrive meanwhile; `serve` answers
+    /// them.
+    fn offer(&mut self, selection: PasteSelection, contents: &Contents) -> io::Result<()> {
+        self.check_supported(selection)?;
+
+        let source = self.manager.create_data_source(&self.queue.handle());
+        for (mime_type, _) in contents {
+            either!(&source, s => s.offer(mime_type.clone()));
+        }
+        self.device.set_selection(selection, &source);
+        self.client.done = false;
+        wayland::roundtrip(&self.connection, &mut self.queue, &mut self.client)
+    }
+
+    /// Send the data to every client that asks, until the source is replaced
+    fn serve(mut self, contents: &Contents) {
+        loop {
+            for (mime_type, fd) in self.client.requests.drain(..) {
+                let data = contents.iter().find(|(t, _)| *t == mime_type);
+                if let Some((_, data)) = data {
+                    // A reader that goes away early only fails its own paste
+                    if let Err(e) = File::from(fd).write_all(data) {
+                        tracing::debug!("Cannot send {} selection data: {}", mime_type, e);
+                    }
+                }
+            }
+            if self.client.done {
+                break;
+            }
+            if let Err(e) = self.queue.blocking_dispatch(&mut self.client) {
+                tracing::debug!("Selection connection closed: {}", e);
+                return;
+            }
+        }
+        tracing::debug!("Wayland selection taken over by another client");
+    }
+}
+
+/// A pipe (read end, write end)
+fn pipe() -> io::Result<(OwnedFd, OwnedFd)> {
+    let mut fds = [0; 2];
+    // SAFETY: pipe2 fills both descriptors on success, which we then own
+    unsafe {
+        if libc::pipe2(fds.as_mut_ptr(), libc::O_CLOEXEC) != 0 {
+            return Err(io::Error::last_os_error());
+        }
+        Ok((OwnedFd::from_raw_fd(fds[0]), OwnedFd::from_raw_fd(fds[1])))
+    }
+}
+
+/// Read a pipe until its writers close it, giving up at `deadline`
+fn read_until_eof(fd: OwnedFd, deadline: Instant) -> io::Result<Vec<u8>> {
+    let mut file = File::from(fd);
+    let mut data = Vec::new();
+    let mut chunk = vec![0u8; 64 * 1024];
+    loop {
+        if !wayland::wait_readable(file.as_fd(), deadline)? {
+            return Err(io::Error::new(
+                io::ErrorKind::TimedOut,
+                "selection owner didn't send its data",
+            ));
+        }
+        match file.read(&mut chunk)? {
+            0 => return Ok(data),
+            read => data.extend_from_slice(&chunk[..read]),
+        }
+    }
+}
+
+/// Whether the compositor offers a data control protocol (blocking)
+pub fn compositor_supports() -> io::Result<bool> {
+    let mut client = Client::default();
+    wayland::event_queue(&wayland::connect()?, &mut client)?;
+    Ok([ext::Manager::interface(), wlr::Manager::interface()]
+        .iter()
+        .any(|interface| client.globals.get(interface.name).is_some()))
+}
+
+/// Read `selection` in all of its types (empty if nothing is selected)
+pub async fn read_selection(selection: PasteSelection) -> io::Result<Contents> {
+    tokio::task::spawn_blocking(move || DataControl::new(wayland::connect()?)?.read(selection))
+        .await
+        .map_err(io::Error::other)?
+}
+
+/// Set `selection`, serving it from a thread until it is replaced
+pub async fn set_selection(selection: PasteSelection, contents: Contents) -> io::Result<()> {
+    tokio::task::spawn_blocking(move || {
+        let mut data_control = DataControl::new(wayland::connect()?)?;
+        data_control.offer(selection, &contents)?;
+        std::thread::Builder::new()
+            .name("wayland-selection".to_string())
+            .spawn(move || data_control.serve(&contents))?;
+        Ok(())
+    })
+    .await
+    .map_err(io::Error::other)?
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::wayland::mock::Compositor;
+    use super::*;
+    use std::sync::mpsc;
+    use wayland_protocols::ext::data_control::v1::server::{
+        ext_data_control_device_v1 as device, ext_data_control_manager_v1 as manager,
+        ext_data_control_offer_v1 as offer, ext_data_control_source_v1 as source,
+    };
+    use wayland_protocols_wlr::data_control::v1::server::{
+        zwlr_data_control_device_v1 as wlr_device, zwlr_data_control_manager_v1 as wlr_manager,
+    };
+    use wayland_server::protocol::wl_seat;
+    use wayland_server::{DataInit, DisplayHandle, GlobalDispatch, New, Resource};
+
+    /// Compositor state: the selection it announces and what it received
+    #[derive(Default)]
+    struct Server {
+        /// Contents of the clipboard, None for an empty one
+        clipboard: Option<&'static [(&'static str, &'static [u8])]>,
+        /// Types offered by the client's sources
+        offered: Vec<String>,
+        /// Where to report the data an application pasted
+        pasted: Option<mpsc::Sender<Vec<u8>>>,
+    }
+
+    /// Bind any global without user data
+    macro_rules! global {
+        ($interface:ty) => {
+            impl GlobalDispatch<$interface, ()> for Server {
+                fn bind(
+                    _: &mut Self,
+                    _: &DisplayHandle,
+                    _: &wayland_server::Client,
+                    resource: New<$interface>,
+                    _: &(),
+                    init: &mut DataInit<'_, Self>,
+                ) {
+                    init.init(resource, ());
+                }
+            }
+        };
+    }
+
+    global!(wl_seat::WlSeat);
+    global!(manager::ExtDataControlManagerV1);
+    global!(wlr_manager::ZwlrDataControlManagerV1);
+
+    /// Ignore every request to an object
+    macro_rules! ignore {
+        ($interface:ty) => {
+            impl wayland_server::Dispatch<$interface, ()> for Server {
+                fn request(
+                    _: &mut Self,
+                    _: &wayland_server::Client,
+                    _: &$interface,
+                    _: <$interface as Resource>::Request,
+                    _: &(),
+                    _: &DisplayHandle,
+                    _: &mut DataInit<'_, Self>,
+                ) {
+                }
+            }
+        };
+    }
+
+    ignore!(wl_seat::WlSeat);
+    ignore!(wlr_device::ZwlrDataControlDeviceV1);
+
+    impl wayland_server::Dispatch<wlr_manager::ZwlrDataControlManagerV1, ()> for Server {
+        fn request(
+            _: &mut Self,
+            _: &wayland_server::Client,
+            _: &wlr_manager::ZwlrDataControlManagerV1,
+            request: wlr_manager::Request,
+            _: &(),
+            _: &DisplayHandle,
+            init: &mut DataInit<'_, Self>,
+        ) {
+            if let wlr_manager::Request::GetDataDevice { id, .. } = request {
+                init.init(id, ());
+            }
+        }
+    }
+
+    impl wayland_server::Dispatch<manager::ExtDataControlManagerV1, ()> for Server {
+        fn request(
+            server: &mut Self,
+            client: &wayland_server::Client,
+            _: &manager::ExtDataControlManagerV1,
+            request: manager::Request,
+            _: &(),
+            display: &DisplayHandle,
+            init: &mut DataInit<'_, Self>,
+        ) {
+            match request {
+                manager::Request::CreateDataSource { id } => {
+                    init.init(id, ());
+                }
+                manager::Request::GetDataDevice { id, .. } => {
+                    let device = init.init(id, ());
+                    let Some(types) = server.clipboard else {
+                        device.selection(None);
+                        return;
+                    };
+                    let offer = client
+                        .create_resource::<offer::ExtDataControlOfferV1, _, Self>(display, 1, types)
+                        .unwrap();
+                    device.data_offer(&offer);
+                    for (mime_type, _) in types {
+                        offer.offer(mime_type.to_string());
+                    }
+                    device.selection(Some(&offer));
+                    device.primary_selection(None);
+                }
+                _ => {}
+            }
+        }
+    }
+
+    impl wayland_server::Dispatch<device::ExtDataControlDeviceV1, ()> for Server {
+        fn request(
+            server: &mut Self,
+            _: &wayland_server::Client,
+            _: &device::ExtDataControlDeviceV1,
+            request: device::Request,
+            _: &(),
+            _: &DisplayHandle,
+            _: &mut DataInit<'_, Self>,
+        ) {
+            let device::Request::SetPrimarySelection {
+                source: Some(source),
+            } = request
+            else {
+                return;
+            };
+            // An application pastes, then another client copies
+            let (reader, writer) = pipe().unwrap();
+            source.send("text/html".to_string(), writer.as_fd());
+            source.cancelled();
+            let pasted = server.pasted.clone().unwrap();
+            std::thread::spawn(move || {
+                let mut data = Vec::new();
+                File::from(reader).read_to_end(&mut data).unwrap();
+                pasted.send(data).unwrap();
+            });
+        }
+    }
+
+    impl wayland_server::Dispatch<source::ExtDataControlSourceV1, ()> for Server {
+        fn request(
+            server: &mut Self,
+            _: &wayland_server::Client,
+            _: &source::ExtDataControlSourceV1,
+            request: source::Request,
+            _: &(),
+            _: &DisplayHandle,
+            _: &mut DataInit<'_, Self>,
+        ) {
+            if let source::Request::Offer { mime_type } = request {
+                server.offered.push(mime_type);
+            }
+        }
+    }
+
+    impl
+        wayland_server::Dispatch<
+            offer::ExtDataControlOfferV1,
+            &'static [(&'static str, &'static [u8])],
+        > for Server
+    {
+        fn request(
+            _: &mut Self,
+            _: &wayland_server::Client,
+            _: &offer::ExtDataControlOfferV1,
+            request: offer::Request,
+            types: &&'static [(&'static str, &'static [u8])],
+            _: &DisplayHandle,
+            _: &mut DataInit<'_, Self>,
+        ) {
+            if let offer::Request::Receive { mime_type, fd } = request {
+                let (_, data) = types.iter().find(|(t, _)| *t == mime_type).unwrap();
+                File::from(fd).write_all(data).unwrap();
+            }
+        }
+    }
+
+    /// Start a compositor offering ext data control, or wlr at `wlr_version`
+    fn compositor(server: Server, wlr_version: Option<u32>) -> (Compositor<Server>, DataControl) {
+        let (compositor, connection) = Compositor::start(server, move |display| {
+            display.create_global::<Server, wl_seat::WlSeat, ()>(8, ());
+            match wlr_version {
+                Some(version) => display
+                    .create_global::<Server, wlr_manager::ZwlrDataControlManagerV1, ()>(
+                        version,
+                        (),
+                    ),
+                None => {
+                    display.create_global::<Server, manager::ExtDataControlManagerV1, ()>(1, ())
+                }
+            };
+        });
+        (compositor, DataControl::new(connection).unwrap())
+    }
+
+    #[test]
+    fn test_read_selection() {
+        let server = Server {
+            clipboard: Some(&[("text/plain", b"fn main() {}"), ("image/png", b"\x89PNG")]),
+            ..Server::default()
+        };
+        let (compositor, mut data_control) = compositor(server, None);
+        let contents = data_control.read(PasteSelection::Clipboard).unwrap();
+        drop(data_control);
+        compositor.finish();
+
+        assert_eq!(
+            contents,
+            vec![
+                ("text/plain".to_string(), b"fn main() {}".to_vec()),
+                ("image/png".to_string(), b"\x89PNG".to_vec()),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_empty_selection() {
+        let (_compositor, mut data_control) = compositor(Server::default(), None);
+        assert!(data_control
+            .read(PasteSelection::Clipboard)
+            .unwrap()
+            .is_empty());
+        assert!(data_control
+            .read(PasteSelection::Primary)
+            .unwrap()
+            .is_empty());
+    }
+
+    #[test]
+    fn test_primary_needs_wlr_version_2() {
+        let (_compositor, mut data_control) = compositor(Server::default(), Some(1));
+        let error = data_control.read(PasteSelection::Primary).unwrap_err();
+        assert_eq!(error.kind(), io::ErrorKind::Unsupported);
+
+        let (_compositor, mut data_control) = compositor(Server::default(), Some(2));
+        assert!(data_control
+            .read(PasteSelection::Primary)
+            .unwrap()
+            .is_empty());
+    }
+
+    #[test]
+    fn test_serve_selection() {
+        let (pasted_tx, pasted_rx) = mpsc::channel();
+        let server = Server {
+            pasted: Some(pasted_tx),
+            ..Server::default()
+        };
+        let (compositor, mut data_control) = compositor(server, None);
+
+        let contents = vec![
+            ("text/plain".to_string(), b"hi".to_vec()),
+            ("text/html".to_string(), b"<b>hi</b>".to_vec()),
+        ];
+        data_control
+            .offer(PasteSelection::Primary, &contents)
+            .unwrap();
+        data_control.serve(&contents);
+        assert_eq!(pasted_rx.recv().unwrap(), b"<b>hi</b>");
+
+        // Every type is offered
+        assert_eq!(compositor.finish().offered, ["text/plain", "text/html"]);
+    }
+}
@@ -0,0 +1,250 @@
+//! Focused window detection
+//!
+//! Finds out which application has the keyboard focus, so app rules can
+//! adapt the output to it. Each desktop is asked its own way:
+//! - Hyprland: `hyprctl activewindow -j` (class)
+//! - Sway: `swaymsg -t get_tree` (app_id, or class for Xwayland windows)
+//! - niri: `niri msg --json focused-window` (app_id)
+//! - X11: `_NET_ACTIVE_WINDOW` and its WM_CLASS
+
+use super::keymap::command_output;
+use crate::config::AppRule;
+use ureq::serde_json;
+
+/// Query for the focused application
+#[async_trait::async_trait]
+pub trait FocusQuery: Send + Sync {
+    /// App ID (Wayland) or WM_CLASS class (X11) of the focused window,
+    /// None if nothing has the focus or the query failed
+    async fn focused_app(&self) -> Option<String>;
+
+    /// Human-readable name for logging
+    fn name(&self) -> &'static str;
+}
+
+/// Pick the query for the running desktop, if it is supported
+pub fn detect() -> Option<Box<dyn FocusQuery>> {
+    let set = |var: &str| std::env::var_os(var).is_some();
+
+    if set("HYPRLAND_INSTANCE_SIGNATURE") {
+        Some(Box::new(Hyprland))
+    } else if set("SWAYSOCK") {
+        Some(Box::new(Sway))
+    } else if set("NIRI_SOCKET") {
+        Some(Box::new(Niri))
+    } else if !set("WAYLAND_DISPLAY") && set("DISPLAY") {
+        Some(Box::new(X11))
+    } else {
+        None
+    }
+}
+
+/// The first of `rules` for the focused application
+pub async fn focused_rule<'a>(query: &dyn FocusQuery, rules: &'a [AppRule]) -> Option<&'a AppRule> {
+    let Some(app) = query.focused_app().await else {
+        tracing::debug!("No focused app found via {}", query.name());
+        return None;
+    };
+
+    let rule = rules.iter().find(|rule| rule.matches(&app));
+    match rule {
+        Some(rule) => tracing::info!("Focused app {:?} matches app rule {:?}", app, rule.app),
+        None => tracing::debug!("Focused app {:?} matches no app rule", app),
+    }
+    rule
+}
+
+/// Hyprland, through hyprctl
+pub struct Hyprland;
+
+#[async_trait::async_trait]
+impl FocusQuery for Hyprland {
+    async fn focused_app(&self) -> Option<String> {
+        let output = command_output("hyprctl", &["activewindow", "-j"]).await?;
+        hyprland_app(&output)
+    }
+
+    fn name(&self) -> &'static str {
+        "hyprctl"
+    }
+}
+
+/// Sway, through swaymsg
+pub struct Sway;
+
+#[async_trait::async_trait]
+impl FocusQuery for Sway {
+    async fn focused_app(&self) -> Option<String> {
+        let output = command_output("swaymsg", &["-t", "get_tree"]).await?;
+        sway_app(&output)
+    }
+
+    fn name(&self) -> &'static str {
+        "swaymsg"
+    }
+}
+
+/// niri, through niri msg
+pub struct Niri;
+
+#[async_trait::async_trait]
+impl FocusQuery for Niri {
+    async fn focused_app(&self) -> Option<String> {
+        let output = command_output("niri", &["msg", "--json", "focused-window"]).await?;
+        niri_app(&output)
+    }
+
+    fn name(&self) -> &'static str {
+        "niri msg"
+    }
+}
+
+/// X11, through the window manager's `_NET_ACTIVE_WINDOW`
+pub struct X11;
+
+#[async_trait::async_trait]
+impl FocusQuery for X11 {
+    async fn focused_app(&self) -> Option<String> {
+        super::x11::active_window_class().await.unwrap_or_else(|e| {
+            tracing::debug!("Cannot read the active window: {}", e);
+            None
+        })
+    }
+
+    fn name(&self) -> &'static str {
+        "X11"
+    }
+}
+
+/// Non-empty string field of a JSON object
+fn string_field(value: &serde_json::Value, key: &str) -> Option<String> {
+    value
+        .get(key)?
+        .as_str()
+        .filter(|s| !s.is_empty())
+        .map(str::to_string)
+}
+
+/// Class of `hyprctl activewindow -j` output (`{}` without a window)
+fn hyprland_app(json: &str) -> Option<String> {
+    let window: serde_json::Value = serde_json::from_str(json).ok()?;
+    string_field(&window, "class")
+}
+
+/// App of the focused node of a sway tree
+fn sway_app(json: &str) -> Option<String> {
+    fn focused(node: &serde_json::Value) -> Option<&serde_json::Value> {
+        if node.get("focused").and_then(|f| f.as_bool()) == Some(true) {
+            return Some(node);
+        }
+        ["nodes", "floating_nodes"]
+            .iter()
+            .filter_map(|key| node.get(key)?.as_array())
+            .flatten()
+            .find_map(focused)
+    }
+
+    let tree: serde_json::Value = serde_json::from_str(json).ok()?;
+    let node = focused(&tree)?;
+    string_field(node, "app_id").or_else(|| string_field(node.get("window_properties")?, "class"))
+}
+
+/// App ID of `niri msg --json focused-window` output (`null` without a window)
+fn niri_app(json: &str) -> Option<String> {
+    let window: serde_json::Value = serde_json::from_str(json).ok()?;
+    string_field(&window, "app_id")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Reports a fixed app
+    struct FakeFocus(Option<&'static str>);
+
+    #[async_trait::async_trait]
+    impl FocusQuery for FakeFocus {
+        async fn focused_app(&self) -> Option<String> {
+            self.0.map(str::to_string)
+        }
+
+        fn name(&self) -> &'static str {
+            "fake"
+        }
+    }
+
+    fn rule(app: &str, paste_keys: &str) -> AppRule {
+        let mut rule = AppRule {
+            app: app.to_string(),
+            paste_keys: Some(paste_keys.to_string()),
+            ..Default::default()
+        };
+        rule.compile().unwrap();
+        rule
+    }
+
+    #[tokio::test]
+    async fn test_focused_rule() {
+        let rules = [
+            rule("kitty|alacritty", "ctrl+shift+v"),
+            rule("org\\.gnome\\..*", "shift+insert"),
+            rule(".*", "ctrl+v"),
+        ];
+        let paste_keys = |rule: Option<&AppRule>| rule.and_then(|r| r.paste_keys.clone());
+
+        // Case-insensitive, first match wins
+        let found = focused_rule(&FakeFocus(Some("Alacritty")), &rules).await;
+        assert_eq!(paste_keys(found).as_deref(), Some("ctrl+shift+v"));
+        let found = focused_rule(&FakeFocus(Some("org.gnome.Console")), &rules).await;
+        assert_eq!(paste_keys(found).as_deref(), Some("shift+insert"));
+
+        // Whole names only
+        let found = focused_rule(&FakeFocus(Some("kitty-helper")), &rules).await;
+        assert_eq!(paste_keys(found).as_deref(), Some("ctrl+v"));
+
+        assert!(focused_rule(&FakeFocus(None), &rules).await.is_none());
+        assert!(focused_rule(&FakeFocus(Some("firefox")), &rules[..2])
+            .await
+            .is_none());
+    }
+
+    #[test]
+    fn test_hyprland_app() {
+        let json = r#"{"address": "0x1", "class": "kitty", "title": "~"}"#;
+        assert_eq!(hyprland_app(json).as_deref(), Some("kitty"));
+        assert_eq!(hyprland_app("{}"), None);
+    }
+
+    #[test]
+    fn test_sway_app() {
+        let json = r#"{
+            "type": "root", "focused": false,
+            "nodes": [{
+                "type": "output", "focused": false,
+                "nodes": [{
+                    "type": "workspace", "focused": false,
+                    "nodes": [{"type": "con", "focused": false, "app_id": "foot"}],
+                    "floating_nodes": [{
+                        "type": "floating_con", "focused": true, "app_id": null,
+                        "window_properties": {"class": "Slack", "instance": "slack"}
+                    }]
+                }]
+            }]
+        }"#;
+        assert_eq!(sway_app(json).as_deref(), Some("Slack"));
+
+        let json = r#"{"focused": false, "nodes": [{"focused": true, "app_id": "foot"}]}"#;
+        assert_eq!(sway_app(json).as_deref(), Some("foot"));
+
+        // A focused workspace has no app
+        let json = r#"{"focused": false, "nodes": [{"type": "workspace", "focused": true}]}"#;
+        assert_eq!(sway_app(json), None);
+    }
+
+    #[test]
+    fn test_niri_app() {
+        let json = r#"{"id": 3, "title": "Inbox", "app_id": "org.gnome.Evolution"}"#;
+        assert_eq!(niri_app(json).as_deref(), Some("org.gnome.Evolution"));
+        assert_eq!(niri_app("null"), None);
+    }
+}
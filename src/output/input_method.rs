@@ -0,0 +1,511 @@
+//! Wayland input method output
+//!
+//! Registers with the compositor as an input method (input-method-unstable-v2)
+//! and commits the transcription as one string to the focused text field,
+//! which receives it through text-input-v3. No keys are simulated, so the
+//! keyboard layout doesn't matter, and the clipboard is left alone.
+//!
+//! A seat has at most one input method. Ours is registered only for the
+//! moment it takes to commit the text; while fcitx5 or IBus is running it
+//! is unavailable and the next output method is used.
+//!
+//! Requires:
+//! - A compositor offering zwp_input_method_manager_v2 (Sway, Hyprland,
+//!   niri, ...)
+//! - A focused application with text-input-v3 support (GTK and Qt apps on
+//!   Wayland; not Xwayland apps)
+
+use super::wayland::{self, Globals, WithGlobals};
+use super::TextOutput;
+use crate::config::OutputBackend;
+use crate::error::OutputError;
+use std::io;
+use std::process::Stdio;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+use tokio::process::Command;
+use wayland_client::protocol::wl_seat::WlSeat;
+use wayland_client::{delegate_noop, Connection, Dispatch, EventQueue, Proxy, QueueHandle};
+use wayland_protocols_misc::zwp_input_method_v2::client::zwp_input_method_manager_v2::ZwpInputMethodManagerV2;
+use wayland_protocols_misc::zwp_input_method_v2::client::zwp_input_method_v2::{
+    self, ZwpInputMethodV2,
+};
+
+/// How long a text field gets to activate the input method
+const ACTIVATE_WAIT: Duration = Duration::from_millis(300);
+
+/// Interval between checks for activation
+const ACTIVATE_POLL: Duration = Duration::from_millis(20);
+
+/// The compositor connection, kept for the life of the process
+static SESSION: Mutex<Option<Session>> = Mutex::new(None);
+
+/// Input method state as announced by the compositor
+#[derive(Debug, Default)]
+struct State {
+    /// Whether a text field is active (as of the last `done`)
+    active: bool,
+    /// Activation announced but not yet applied with `done`
+    pending_active: bool,
+    /// Number of `done` events, the serial for `commit`
+    serial: u32,
+    /// Another input method owns the seat
+    unavailable: bool,
+}
+
+impl State {
+    fn handle(&mut self, event: &zwp_input_method_v2::Event) {
+        match event {
+            zwp_input_method_v2::Event::Activate => self.pending_active = true,
+            zwp_input_method_v2::Event::Deactivate => self.pending_active = false,
+            zwp_input_method_v2::Event::Done => {
+                self.serial += 1;
+                self.active = self.pending_active;
+            }
+            zwp_input_method_v2::Event::Unavailable => self.unavailable = true,
+            // Surrounding text, content type etc. don't matter for commits
+            _ => {}
+        }
+    }
+}
+
+/// Dispatch state of the connection
+#[derive(Default)]
+struct Client {
+    globals: Globals,
+    /// State of the input method being registered
+    input_method: State,
+}
+
+impl WithGlobals for Client {
+    fn globals(&mut self) -> &mut Globals {
+        &mut self.globals
+    }
+}
+
+wayland::delegate_globals!(Client);
+delegate_noop!(Client: ignore WlSeat);
+delegate_noop!(Client: ZwpInputMethodManagerV2);
+
+impl Dispatch<ZwpInputMethodV2, ()> for Client {
+    fn event(
+        client: &mut Self,
+        _: &ZwpInputMethodV2,
+        event: zwp_input_method_v2::Event,
+        _: &(),
+        _: &Connection,
+        _: &QueueHandle<Self>,
+    ) {
+        client.input_method.handle(&event);
+    }
+}
+
+/// An edit to apply to the focused text field
+enum Edit<'a> {
+    /// Insert text at the cursor
+    Insert(&'a str),
+    /// Delete this many bytes before the cursor
+    DeleteBefore(u32),
+}
+
+/// A connection with the seat and input method manager bound
+struct Session {
+    connection: Connection,
+    queue: EventQueue<Client>,
+    client: Client,
+    seat: WlSeat,
+    manager: ZwpInputMethodManagerV2,
+    /// Last text committed, for erase
+    last_commit: Option<String>,
+}
+
+impl Session {
+    fn new(connection: Connection) -> io::Result<Self> {
+        let mut client = Client::default();
+        let mut queue = wayland::event_queue(&connection, &mut client)?;
+        let qh = queue.handle();
+        let seat = client.globals.bind(&qh, 1)?;
+        let manager = client.globals.bind(&qh, 1)?;
+        wayland::roundtrip(&connection, &mut queue, &mut client)?;
+        Ok(Self {
+            connection,
+            queue,
+            client,
+            seat,
+            manager,
+            last_commit: None,
+        })
+    }
+
+    fn roundtrip(&mut self) -> Result<(), OutputError> {
+        wayland::roundtrip(&self.connection, &mut self.queue, &mut self.client)
+            .map_err(protocol_error)
+    }
+
+    /// Register an input method, apply `edit` once a text field activates
+    /// it, then unregister again (blocking)
+    fn apply(&mut self, edit: Edit<'_>) -> Result<(), OutputError> {
+        self.client.input_method = State::default();
+        let input_method = self
+            .manager
+            .get_input_method(&self.seat, &self.queue.handle(), ());
+
+        let deadline = Instant::now() + ACTIVATE_WAIT;
+        let result = loop {
+            self.roundtrip()?;
+            let state = &self.client.input_method;
+            if state.unavailable {
+                break Err(OutputError::InjectionFailed(
+                    "another input method is running".to_string(),
+                ));
+            }
+            if state.active {
+                break Ok(());
+            }
+            if Instant::now() >= deadline {
+                break Err(OutputError::InjectionFailed(
+                    "focused window doesn't accept input method text (text-input-v3)".to_string(),
+                ));
+            }
+            std::thread::sleep(ACTIVATE_POLL);
+        };
+
+        if result.is_ok() {
+            match edit {
+                Edit::Insert(text) => input_method.commit_string(text.to_string()),
+                Edit::DeleteBefore(bytes) => input_method.delete_surrounding_text(bytes, 0),
+            }
+            input_method.commit(self.client.input_method.serial);
+        }
+        input_method.destroy();
+        self.roundtrip()?;
+        result
+    }
+}
+
+fn protocol_error(e: io::Error) -> OutputError {
+    OutputError::InjectionFailed(format!("input method: {}", e))
+}
+
+/// Run `f` with the session on a blocking thread, connecting first if needed
+///
+/// The session is dropped when `f` fails, so the next call starts with a
+/// fresh connection (e.g. after the compositor restarted).
+async fn with_session<F>(f: F) -> Result<(), OutputError>
+where
+    F: FnOnce(&mut Session) -> Result<(), OutputError> + Send + 'static,
+{
+    tokio::task::spawn_blocking(move || {
+        let mut session = SESSION.lock().unwrap_or_else(|e| e.into_inner());
+        if session.is_none() {
+            let connected = wayland::connect()
+                .and_then(Session::new)
+                .map_err(protocol_error)?;
+            tracing::debug!("Connected to the compositor for input method output");
+            *session = Some(connected);
+        }
+
+        let result = f(session.as_mut().expect("session created above"));
+        if result.is_err() {
+            *session = None;
+        }
+        result
+    })
+    .await
+    .map_err(|e| OutputError::InjectionFailed(format!("input method: {}", e)))?
+}
+
+/// Whether the compositor offers the input method protocol
+pub fn compositor_supports() -> io::Result<bool> {
+    let mut client = Client::default();
+    wayland::event_queue(&wayland::connect()?, &mut client)?;
+    Ok(client
+        .globals
+        .get(ZwpInputMethodManagerV2::interface().name)
+        .is_some())
+}
+
+/// Text output through a Wayland input method
+pub struct InputMethodOutput {
+    /// Whether to show a desktop notification
+    notify: bool,
+    /// Whether to send Enter key after output
+    auto_submit: bool,
+}
+
+impl InputMethodOutput {
+    /// Create a new input method output
+    pub fn new(notify: bool, auto_submit: bool) -> Self {
+        Self {
+            notify,
+            auto_submit,
+        }
+    }
+
+    /// Send a desktop notification
+    async fn send_notification(&self, text: &str) {
+        // Truncate preview for notification
+        let preview: String = text.chars().take(100).collect();
+        let preview = if text.chars().count() > 100 {
+            format!("{}...", preview)
+        } else {
+            preview
+        };
+
+        let _ = Command::new("notify-send")
+            .args([
+                "--app-name=Voxtype",
+                "--expire-time=3000",
+                "Transcribed",
+                &preview,
+            ])
+            .stdout(Stdio::null())
+            .stderr(Stdio::null())
+            .status()
+            .await;
+    }
+}
+
+#[async_trait::async_trait]
+impl TextOutput for InputMethodOutput {
+    async fn output(&self, text: &str) -> Result<(), OutputError> {
+        if text.is_empty() {
+            return Ok(());
+        }
+
+        let committed = text.to_string();
+        with_session(move |session| {
+            session.apply(Edit::Insert(&committed))?;
+            session.last_commit = Some(committed);
+            Ok(())
+        })
+        .await?;
+
+        // Input methods can't press keys; Enter goes through the virtual keyboard
+        if self.auto_submit {
+            if let Err(e) = super::virtual_keyboard::send_keysyms(vec!["Return".to_string()]).await
+            {
+                tracing::warn!("Failed to send Enter key: {}", e);
+            }
+        }
+
+        if self.notify {
+            self.send_notification(text).await;
+        }
+
+        Ok(())
+    }
+
+    async fn erase(&self, count: usize) -> Result<(), OutputError> {
+        // Surrounding text is deleted in bytes, so only our own last commit
+        // can be erased exactly
+        let name = self.name();
+        with_session(move |session| {
+            let bytes = match session.last_commit {
+                Some(ref text) if text.chars().count() == count => text.len() as u32,
+                _ => return Err(OutputError::EraseNotSupported(name.to_string())),
+            };
+            session.apply(Edit::DeleteBefore(bytes))?;
+            session.last_commit = None;
+            Ok(())
+        })
+        .await
+    }
+
+    async fn is_available(&self) -> bool {
+        match with_session(|_| Ok(())).await {
+            Ok(()) => true,
+            Err(e) => {
+                tracing::debug!("{}", e);
+                false
+            }
+        }
+    }
+
+    fn name(&self) -> &'static str {
+        "input-method"
+    }
+
+    fn backend(&self) -> OutputBackend {
+        OutputBackend::InputMethod
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::wayland::mock::Compositor;
+    use super::*;
+    use wayland_protocols_misc::zwp_input_method_v2::server::{
+        zwp_input_method_manager_v2 as manager, zwp_input_method_v2 as input_method,
+    };
+    use wayland_server::protocol::wl_seat;
+    use wayland_server::{DataInit, DisplayHandle, GlobalDispatch, New};
+
+    /// Events the compositor answers get_input_method with
+    #[derive(Clone, Copy)]
+    enum Event {
+        Activate,
+        Done,
+        Unavailable,
+    }
+
+    /// Compositor state recording the requests sent to input methods
+    struct Server {
+        events: &'static [Event],
+        requests: Vec<input_method::Request>,
+    }
+
+    impl GlobalDispatch<wl_seat::WlSeat, ()> for Server {
+        fn bind(
+            _: &mut Self,
+            _: &DisplayHandle,
+            _: &wayland_server::Client,
+            seat: New<wl_seat::WlSeat>,
+            _: &(),
+            init: &mut DataInit<'_, Self>,
+        ) {
+            init.init(seat, ());
+        }
+    }
+
+    impl wayland_server::Dispatch<wl_seat::WlSeat, ()> for Server {
+        fn request(
+            _: &mut Self,
+            _: &wayland_server::Client,
+            _: &wl_seat::WlSeat,
+            _: wl_seat::Request,
+            _: &(),
+            _: &DisplayHandle,
+            _: &mut DataInit<'_, Self>,
+        ) {
+        }
+    }
+
+    impl GlobalDispatch<manager::ZwpInputMethodManagerV2, ()> for Server {
+        fn bind(
+            _: &mut Self,
+            _: &DisplayHandle,
+            _: &wayland_server::Client,
+            manager: New<manager::ZwpInputMethodManagerV2>,
+            _: &(),
+            init: &mut DataInit<'_, Self>,
+        ) {
+            init.init(manager, ());
+        }
+    }
+
+    impl wayland_server::Dispatch<manager::ZwpInputMethodManagerV2, ()> for Server {
+        fn request(
+            server: &mut Self,
+            _: &wayland_server::Client,
+            _: &manager::ZwpInputMethodManagerV2,
+            request: manager::Request,
+            _: &(),
+            _: &DisplayHandle,
+            init: &mut DataInit<'_, Self>,
+        ) {
+            if let manager::Request::GetInputMethod { input_method, .. } = request {
+                let input_method = init.init(input_method, ());
+                for event in server.events {
+                    match event {
+                        Event::Activate => input_method.activate(),
+                        Event::Done => input_method.done(),
+                        Event::Unavailable => input_method.unavailable(),
+                    }
+                }
+            }
+        }
+    }
+
+    impl wayland_server::Dispatch<input_method::ZwpInputMethodV2, ()> for Server {
+        fn request(
+            server: &mut Self,
+            _: &wayland_server::Client,
+            _: &input_method::ZwpInputMethodV2,
+            request: input_method::Request,
+            _: &(),
+            _: &DisplayHandle,
+            _: &mut DataInit<'_, Self>,
+        ) {
+            server.requests.push(request);
+        }
+    }
+
+    /// Start a compositor that answers get_input_method with `events`
+    fn compositor(events: &'static [Event]) -> (Compositor<Server>, Session) {
+        let server = Server {
+            events,
+            requests: Vec::new(),
+        };
+        let (server, connection) = Compositor::start(server, |display| {
+            display.create_global::<Server, wl_seat::WlSeat, ()>(8, ());
+            display.create_global::<Server, manager::ZwpInputMethodManagerV2, ()>(1, ());
+        });
+        (server, Session::new(connection).unwrap())
+    }
+
+    #[test]
+    fn test_state() {
+        let mut state = State::default();
+        state.handle(&zwp_input_method_v2::Event::Activate);
+        assert!(!state.active);
+        state.handle(&zwp_input_method_v2::Event::Done);
+        assert!(state.active);
+        assert_eq!(state.serial, 1);
+        state.handle(&zwp_input_method_v2::Event::Deactivate);
+        state.handle(&zwp_input_method_v2::Event::Done);
+        assert!(!state.active);
+        assert_eq!(state.serial, 2);
+    }
+
+    #[test]
+    fn test_commits_text() {
+        let (server, mut session) = compositor(&[Event::Activate, Event::Done]);
+        session.apply(Edit::Insert("Grüße 世界")).unwrap();
+        session.apply(Edit::DeleteBefore(5)).unwrap();
+        drop(session);
+
+        let requests = format!("{:?}", server.finish().requests);
+        assert_eq!(
+            requests,
+            format!(
+                "{:?}",
+                [
+                    input_method::Request::CommitString {
+                        text: "Grüße 世界".to_string()
+                    },
+                    input_method::Request::Commit { serial: 1 },
+                    input_method::Request::Destroy,
+                    input_method::Request::DeleteSurroundingText {
+                        before_length: 5,
+                        after_length: 0
+                    },
+                    input_method::Request::Commit { serial: 1 },
+                    input_method::Request::Destroy,
+                ]
+            )
+        );
+    }
+
+    #[test]
+    fn test_another_input_method_running() {
+        let (server, mut session) = compositor(&[Event::Unavailable]);
+        let error = session.apply(Edit::Insert("hi")).unwrap_err();
+        assert!(error.to_string().contains("another input method"));
+        drop(session);
+
+        // Nothing committed, the input method is destroyed again
+        let requests = server.finish().requests;
+        assert!(matches!(
+            requests.as_slice(),
+            [input_method::Request::Destroy]
+        ));
+    }
+
+    #[test]
+    fn test_no_text_field() {
+        let (_server, mut session) = compositor(&[Event::Done]);
+        let error = session.apply(Edit::Insert("hi")).unwrap_err();
+        assert!(error.to_string().contains("text-input-v3"));
+    }
+}
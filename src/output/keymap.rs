@@ -0,0 +1,550 @@
+//! Keyboard layouts for typing through uinput
+//!
+//! A virtual keyboard sends keycodes, and the compositor turns them into
+//! characters using the user's XKB layout. To type a character we need the
+//! reverse: which key, with which modifiers, produces it in that layout.
+//!
+//! Layouts are compiled with libxkbcommon, which is loaded at runtime so it
+//! isn't a build dependency. Every Wayland desktop has it installed; without
+//! it only the US layout is known.
+
+use evdev::Key;
+use libloading::Library;
+use std::collections::hash_map::Entry;
+use std::collections::HashMap;
+use std::ffi::{c_char, c_int, c_void, CString};
+use std::process::Stdio;
+use tokio::process::Command;
+use ureq::serde_json;
+
+/// An XKB layout with optional variant, written like "de" or "de(nodeadkeys)"
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LayoutName {
+    pub layout: String,
+    pub variant: String,
+}
+
+impl LayoutName {
+    /// US QWERTY
+    pub fn us() -> Self {
+        Self::new("us", "")
+    }
+
+    /// Layout and variant as given by setxkbmap, localectl or the compositor
+    /// A list of layouts ("de,us") means the first one
+    pub fn new(layout: &str, variant: &str) -> Self {
+        let first = |list: &str| list.split(',').next().unwrap_or("").trim().to_string();
+        Self {
+            layout: first(layout),
+            variant: first(variant),
+        }
+    }
+
+    /// Parse "layout" or "layout(variant)"
+    pub fn parse(name: &str) -> Self {
+        match name.trim().split_once('(') {
+            Some((layout, variant)) => Self::new(layout, variant.trim_end_matches(')')),
+            None => Self::new(name, ""),
+        }
+    }
+}
+
+impl std::fmt::Display for LayoutName {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if self.variant.is_empty() {
+            write!(f, "{}", self.layout)
+        } else {
+            write!(f, "{}({})", self.layout, self.variant)
+        }
+    }
+}
+
+/// Find the keyboard layout of the running session
+///
+/// Checked in order: the XKB_DEFAULT_* environment (used by Sway and other
+/// wlroots compositors), Hyprland's input options, the X server (setxkbmap)
+/// and the system keyboard configuration (localectl).
+pub async fn detect_layout() -> Option<LayoutName> {
+    if let Ok(layout) = std::env::var("XKB_DEFAULT_LAYOUT") {
+        if !layout.is_empty() {
+            let variant = std::env::var("XKB_DEFAULT_VARIANT").unwrap_or_default();
+            return Some(LayoutName::new(&layout, &variant));
+        }
+    }
+
+    if std::env::var("HYPRLAND_INSTANCE_SIGNATURE").is_ok() {
+        if let Some(layout) = hyprctl_option("input:kb_layout").await {
+            let variant = hyprctl_option("input:kb_variant").await.unwrap_or_default();
+            return Some(LayoutName::new(&layout, &variant));
+        }
+    }
+
+    if std::env::var("DISPLAY").is_ok() {
+        if let Some(output) = command_output("setxkbmap", &["-query"]).await {
+            if let Some(layout) = parse_layout_fields(&output, "layout", "variant") {
+                return Some(layout);
+            }
+        }
+    }
+
+    let output = command_output("localectl", &["status"]).await?;
+    parse_layout_fields(&output, "X11 Layout", "X11 Variant")
+}
+
+/// Value of a Hyprland string option
+async fn hyprctl_option(option: &str) -> Option<String> {
+    let output = command_output("hyprctl", &["getoption", option, "-j"]).await?;
+    let json: serde_json::Value = serde_json::from_str(&output).ok()?;
+    json.get("str")?
+        .as_str()
+        .map(|s| s.trim().to_string())
+        .filter(|s| !s.is_empty() && s != "[[EMPTY]]")
+}
+
+/// Stdout of a command, if it ran successfully
+pub(super) async fn command_output(program: &str, args: &[&str]) -> Option<String> {
+    let output = Command::new(program)
+        .args(args)
+        .stdin(Stdio::null())
+        .stderr(Stdio::null())
+        .output()
+        .await
+        .ok()?;
+    output
+        .status
+        .success()
+        .then(|| String::from_utf8_lossy(&output.stdout).into_owned())
+}
+
+/// Read "key: value" lines like those of `setxkbmap -query` and `localectl status`
+fn parse_layout_fields(text: &str, layout_key: &str, variant_key: &str) -> Option<LayoutName> {
+    let field = |key: &str| {
+        text.lines().find_map(|line| {
+            let (name, value) = line.split_once(':')?;
+            (name.trim() == key).then(|| value.trim().to_string())
+        })
+    };
+    let layout = field(layout_key).filter(|layout| !layout.is_empty() && layout != "n/a")?;
+    let variant = field(variant_key).unwrap_or_default();
+    Some(LayoutName::new(&layout, &variant))
+}
+
+/// The key and modifiers that type a character
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct KeyStroke {
+    pub key: Key,
+    pub modifiers: Vec<Key>,
+}
+
+impl KeyStroke {
+    fn plain(key: Key) -> Self {
+        Self {
+            key,
+            modifiers: Vec::new(),
+        }
+    }
+}
+
+/// Characters of a keyboard layout and how to type them
+#[derive(Debug, Clone)]
+pub struct Keymap {
+    strokes: HashMap<char, KeyStroke>,
+}
+
+/// Keys of the US layout, in the order of `US_PLAIN` and `US_SHIFTED`
+const US_KEYS: [Key; 47] = [
+    Key::KEY_GRAVE,
+    Key::KEY_1,
+    Key::KEY_2,
+    Key::KEY_3,
+    Key::KEY_4,
+    Key::KEY_5,
+    Key::KEY_6,
+    Key::KEY_7,
+    Key::KEY_8,
+    Key::KEY_9,
+    Key::KEY_0,
+    Key::KEY_MINUS,
+    Key::KEY_EQUAL,
+    Key::KEY_Q,
+    Key::KEY_W,
+    Key::KEY_E,
+    Key::KEY_R,
+    Key::KEY_T,
+    Key::KEY_Y,
+    Key::KEY_U,
+    Key::KEY_I,
+    Key::KEY_O,
+    Key::KEY_P,
+    Key::KEY_LEFTBRACE,
+    Key::KEY_RIGHTBRACE,
+    Key::KEY_BACKSLASH,
+    Key::KEY_A,
+    Key::KEY_S,
+    Key::KEY_D,
+    Key::KEY_F,
+    Key::KEY_G,
+    Key::KEY_H,
+    Key::KEY_J,
+    Key::KEY_K,
+    Key::KEY_L,
+    Key::KEY_SEMICOLON,
+    Key::KEY_APOSTROPHE,
+    Key::KEY_Z,
+    Key::KEY_X,
+    Key::KEY_C,
+    Key::KEY_V,
+    Key::KEY_B,
+    Key::KEY_N,
+    Key::KEY_M,
+    Key::KEY_COMMA,
+    Key::KEY_DOT,
+    Key::KEY_SLASH,
+];
+const US_PLAIN: &str = "`1234567890-=qwertyuiop[]\\asdfghjkl;'zxcvbnm,./";
+const US_SHIFTED: &str = "~!@#$%^&*()_+QWERTYUIOP{}|ASDFGHJKL:\"ZXCVBNM<>?";
+
+impl Keymap {
+    /// US QWERTY, used when libxkbcommon isn't available
+    pub fn us() -> Self {
+        let mut keymap = Self {
+            strokes: HashMap::new(),
+        };
+        for ((key, plain), shifted) in US_KEYS.iter().zip(US_PLAIN.chars()).zip(US_SHIFTED.chars())
+        {
+            keymap.insert(plain, KeyStroke::plain(*key));
+            keymap.insert(
+                shifted,
+                KeyStroke {
+                    key: *key,
+                    modifiers: vec![Key::KEY_LEFTSHIFT],
+                },
+            );
+        }
+        keymap.with_whitespace()
+    }
+
+    /// Compile a layout with libxkbcommon
+    pub fn from_xkb(name: &LayoutName) -> Result<Self, String> {
+        let xkb = Xkb::load()?;
+        let layout = CString::new(name.layout.as_str()).map_err(|e| e.to_string())?;
+        let variant = CString::new(name.variant.as_str()).map_err(|e| e.to_string())?;
+        let names = RuleNames {
+            rules: std::ptr::null(),
+            model: std::ptr::null(),
+            layout: layout.as_ptr(),
+            variant: variant.as_ptr(),
+            options: std::ptr::null(),
+        };
+
+        // SAFETY: the functions come from libxkbcommon with their documented
+        // signatures, and every pointer passed is valid for the call. The
+        // keymap and context are released before returning.
+        unsafe {
+            let context = (xkb.context_new)(0);
+            if context.is_null() {
+                return Err("Failed to create XKB context".to_string());
+            }
+            let keymap = (xkb.keymap_new_from_names)(context, &names, 0);
+            let result = if keymap.is_null() {
+                Err(format!("Unknown keyboard layout '{}'", name))
+            } else {
+                let result = Self::read_xkb_keymap(&xkb, keymap);
+                (xkb.keymap_unref)(keymap);
+                result
+            };
+            (xkb.context_unref)(context);
+            result
+        }
+    }
+
+    /// Collect the characters of the first layout group of a compiled keymap
+    ///
+    /// # Safety
+    /// `keymap` must be a valid keymap created by `xkb`
+    unsafe fn read_xkb_keymap(xkb: &Xkb, keymap: *mut c_void) -> Result<Self, String> {
+        let mod_bit = |name: &str| {
+            let name = CString::new(name).expect("modifier names have no NUL bytes");
+            match (xkb.keymap_mod_get_index)(keymap, name.as_ptr()) {
+                XKB_MOD_INVALID => 0,
+                index => 1u32 << index,
+            }
+        };
+        let shift = mod_bit("Shift");
+        // AltGr; keymaps put ISO_Level3_Shift on Mod5
+        let level3 = mod_bit("Mod5");
+
+        // Characters with the keycode and level that produce them
+        let mut symbols = Vec::new();
+        let mut level3_keys = Vec::new();
+        for keycode in (xkb.keymap_min_keycode)(keymap)..=(xkb.keymap_max_keycode)(keymap) {
+            // XKB keycodes are evdev codes plus 8
+            let Some(key) = keycode
+                .checked_sub(8)
+                .and_then(|code| u16::try_from(code).ok())
+                .map(Key::new)
+            else {
+                continue;
+            };
+
+            for level in 0..(xkb.keymap_num_levels_for_key)(keymap, keycode, 0) {
+                let mut syms: *const u32 = std::ptr::null();
+                let count =
+                    (xkb.keymap_key_get_syms_by_level)(keymap, keycode, 0, level, &mut syms);
+                if count <= 0 || syms.is_null() {
+                    continue;
+                }
+                for &sym in std::slice::from_raw_parts(syms, count as usize) {
+                    if sym == XKB_KEY_ISO_LEVEL3_SHIFT && level == 0 {
+                        level3_keys.push(key);
+                    }
+                    if let Some(c) = char::from_u32((xkb.keysym_to_utf32)(sym)) {
+                        if !c.is_control() {
+                            symbols.push((key, keycode, level, c));
+                        }
+                    }
+                }
+            }
+        }
+
+        // Right Alt is AltGr on every layout that has one
+        let level3_key = if level3_keys.contains(&Key::KEY_RIGHTALT) {
+            Some(Key::KEY_RIGHTALT)
+        } else {
+            level3_keys.first().copied()
+        };
+
+        let mut keymap_out = Self {
+            strokes: HashMap::new(),
+        };
+        for (key, keycode, level, c) in symbols {
+            let mut masks = [0u32; 8];
+            let count = (xkb.keymap_key_get_mods_for_level)(
+                keymap,
+                keycode,
+                0,
+                level,
+                masks.as_mut_ptr(),
+                masks.len(),
+            );
+
+            // Use the simplest modifier combination we can press
+            let mask = masks[..count.min(masks.len())]
+                .iter()
+                .copied()
+                .filter(|mask| mask & !(shift | level3) == 0)
+                .min_by_key(|mask| mask.count_ones());
+            let Some(mask) = mask else {
+                continue;
+            };
+
+            let mut modifiers = Vec::new();
+            if mask & shift != 0 {
+                modifiers.push(Key::KEY_LEFTSHIFT);
+            }
+            if mask & level3 != 0 {
+                match level3_key {
+                    Some(level3_key) => modifiers.push(level3_key),
+                    None => continue,
+                }
+            }
+            keymap_out.insert(c, KeyStroke { key, modifiers });
+        }
+
+        if keymap_out.strokes.is_empty() {
+            return Err("XKB keymap has no characters".to_string());
+        }
+        Ok(keymap_out.with_whitespace())
+    }
+
+    /// Remember how to type `c`, unless it's already known with fewer modifiers
+    fn insert(&mut self, c: char, stroke: KeyStroke) {
+        match self.strokes.entry(c) {
+            Entry::Occupied(mut entry) => {
+                if stroke.modifiers.len() < entry.get().modifiers.len() {
+                    entry.insert(stroke);
+                }
+            }
+            Entry::Vacant(entry) => {
+                entry.insert(stroke);
+            }
+        }
+    }
+
+    /// Add the keys that type whitespace, which are the same on every layout
+    fn with_whitespace(mut self) -> Self {
+        self.strokes.insert('\n', KeyStroke::plain(Key::KEY_ENTER));
+        self.strokes.insert('\t', KeyStroke::plain(Key::KEY_TAB));
+        self.insert(' ', KeyStroke::plain(Key::KEY_SPACE));
+        self
+    }
+
+    /// The key and modifiers that type `c`
+    pub fn stroke(&self, c: char) -> Option<&KeyStroke> {
+        self.strokes.get(&c)
+    }
+}
+
+/// `struct xkb_rule_names`
+#[repr(C)]
+struct RuleNames {
+    rules: *const c_char,
+    model: *const c_char,
+    layout: *const c_char,
+    variant: *const c_char,
+    options: *const c_char,
+}
+
+const XKB_MOD_INVALID: u32 = 0xffff_ffff;
+const XKB_KEY_ISO_LEVEL3_SHIFT: u32 = 0xfe03;
+
+/// The parts of libxkbcommon needed to read a keymap
+struct Xkb {
+    // Keeps the function pointers below valid
+    _library: Library,
+    context_new: unsafe extern "C" fn(c_int) -> *mut c_void,
+    context_unref: unsafe extern "C" fn(*mut c_void),
+    keymap_new_from_names:
+        unsafe extern "C" fn(*mut c_void, *const RuleNames, c_int) -> *mut c_void,
+    keymap_unref: unsafe extern "C" fn(*mut c_void),
+    keymap_min_keycode: unsafe extern "C" fn(*mut c_void) -> u32,
+    keymap_max_keycode: unsafe extern "C" fn(*mut c_void) -> u32,
+    keymap_num_levels_for_key: unsafe extern "C" fn(*mut c_void, u32, u32) -> u32,
+    keymap_key_get_syms_by_level:
+        unsafe extern "C" fn(*mut c_void, u32, u32, u32, *mut *const u32) -> c_int,
+    keymap_key_get_mods_for_level:
+        unsafe extern "C" fn(*mut c_void, u32, u32, u32, *mut u32, usize) -> usize,
+    keymap_mod_get_index: unsafe extern "C" fn(*mut c_void, *const c_char) -> u32,
+    keysym_to_utf32: unsafe extern "C" fn(u32) -> u32,
+}
+
+impl Xkb {
+    fn load() -> Result<Self, String> {
+        // SAFETY: loading libxkbcommon runs no initialization code with
+        // requirements of its own
+        let library = unsafe { Library::new("libxkbcommon.so.0") }
+            .map_err(|_| "libxkbcommon.so.0 not found".to_string())?;
+
+        // SAFETY: each symbol is read as the field type it initializes, which
+        // is its signature in xkbcommon.h (xkb_keymap_key_get_mods_for_level
+        // needs libxkbcommon 1.0 or newer)
+        macro_rules! function {
+            ($name:literal) => {
+                *unsafe { library.get(concat!($name, "\0").as_bytes()) }
+                    .map_err(|_| format!("libxkbcommon is missing {} (too old?)", $name))?
+            };
+        }
+
+        Ok(Self {
+            context_new: function!("xkb_context_new"),
+            context_unref: function!("xkb_context_unref"),
+            keymap_new_from_names: function!("xkb_keymap_new_from_names"),
+            keymap_unref: function!("xkb_keymap_unref"),
+            keymap_min_keycode: function!("xkb_keymap_min_keycode"),
+            keymap_max_keycode: function!("xkb_keymap_max_keycode"),
+            keymap_num_levels_for_key: function!("xkb_keymap_num_levels_for_key"),
+            keymap_key_get_syms_by_level: function!("xkb_keymap_key_get_syms_by_level"),
+            keymap_key_get_mods_for_level: function!("xkb_keymap_key_get_mods_for_level"),
+            keymap_mod_get_index: function!("xkb_keymap_mod_get_index"),
+            keysym_to_utf32: function!("xkb_keysym_to_utf32"),
+            _library: library,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_layout_name_parse() {
+        assert_eq!(LayoutName::parse("de"), LayoutName::new("de", ""));
+        assert_eq!(
+            LayoutName::parse("de(nodeadkeys)"),
+            LayoutName::new("de", "nodeadkeys")
+        );
+        assert_eq!(
+            LayoutName::new("fr,us", ",dvorak"),
+            LayoutName::new("fr", "")
+        );
+        assert_eq!(
+            LayoutName::parse("de(nodeadkeys)").to_string(),
+            "de(nodeadkeys)"
+        );
+    }
+
+    #[test]
+    fn test_parse_setxkbmap_and_localectl() {
+        let setxkbmap =
+            "rules:      evdev\nmodel:      pc105\nlayout:     de,us\nvariant:    nodeadkeys,\n";
+        assert_eq!(
+            parse_layout_fields(setxkbmap, "layout", "variant"),
+            Some(LayoutName::new("de", "nodeadkeys"))
+        );
+
+        let localectl =
+            "   System Locale: LANG=fr_FR.UTF-8\n       VC Keymap: fr\n      X11 Layout: fr\n";
+        assert_eq!(
+            parse_layout_fields(localectl, "X11 Layout", "X11 Variant"),
+            Some(LayoutName::new("fr", ""))
+        );
+
+        let unset =
+            "   System Locale: LANG=C.UTF-8\n       VC Keymap: n/a\n      X11 Layout: n/a\n";
+        assert_eq!(
+            parse_layout_fields(unset, "X11 Layout", "X11 Variant"),
+            None
+        );
+    }
+
+    #[test]
+    fn test_us_keymap() {
+        let keymap = Keymap::us();
+        assert_eq!(keymap.stroke('a'), Some(&KeyStroke::plain(Key::KEY_A)));
+        assert_eq!(
+            keymap.stroke('?'),
+            Some(&KeyStroke {
+                key: Key::KEY_SLASH,
+                modifiers: vec![Key::KEY_LEFTSHIFT],
+            })
+        );
+        assert_eq!(keymap.stroke('\n'), Some(&KeyStroke::plain(Key::KEY_ENTER)));
+        assert_eq!(keymap.stroke(' '), Some(&KeyStroke::plain(Key::KEY_SPACE)));
+        assert_eq!(keymap.stroke('ä'), None);
+    }
+
+    #[test]
+    fn test_xkb_german_layout() {
+        // Needs libxkbcommon and the XKB data files
+        let Ok(keymap) = Keymap::from_xkb(&LayoutName::new("de", "")) else {
+            return;
+        };
+        // Y and Z are swapped compared to US
+        assert_eq!(keymap.stroke('z'), Some(&KeyStroke::plain(Key::KEY_Y)));
+        assert_eq!(
+            keymap.stroke('ä'),
+            Some(&KeyStroke::plain(Key::KEY_APOSTROPHE))
+        );
+        assert_eq!(
+            keymap.stroke('@'),
+            Some(&KeyStroke {
+                key: Key::KEY_Q,
+                modifiers: vec![Key::KEY_RIGHTALT],
+            })
+        );
+        assert_eq!(
+            keymap.stroke('Z'),
+            Some(&KeyStroke {
+                key: Key::KEY_Y,
+                modifiers: vec![Key::KEY_LEFTSHIFT],
+            })
+        );
+    }
+
+    #[test]
+    fn test_xkb_unknown_layout() {
+        if Xkb::load().is_ok() {
+            assert!(Keymap::from_xkb(&LayoutName::new("no-such-layout", "")).is_err());
+        }
+    }
+}
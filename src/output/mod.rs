@@ -3,19 +3,42 @@
 //! Provides text output via keyboard simulation or clipboard.
 //!
 //! Fallback chain for `mode = "type"`:
-//! 1. wtype - Wayland-native, best Unicode/CJK support, no daemon needed
-//! 2. ydotool - Works on X11/Wayland/TTY, requires daemon
-//! 3. clipboard - Universal fallback via wl-copy
+//! 1. virtual-keyboard - Wayland virtual keyboard protocol spoken directly,
+//!    full Unicode/CJK support, nothing to install
+//! 2. wtype - Same protocol via the wtype binary (for compositors that
+//!    reject our client but allow wtype, or a missing WAYLAND_DISPLAY)
+//! 3. uinput - Built-in virtual keyboard, works on X11/Wayland/TTY, no daemon
+//!    needed, limited to the characters of the keyboard layout
+//! 4. ydotool - Works on X11/Wayland/TTY, requires daemon
+//! 5. clipboard - Universal fallback via wl-copy
+//!
+//! On X11 sessions, xtest (the XTEST extension, full Unicode through keysym
+//! remapping) replaces 1 and 2, and the clipboard is set without wl-copy.
 //!
 //! Paste mode (clipboard + Ctrl+V) helps with system with non US keyboard layouts.
+//! Input method mode commits the text through the Wayland input method
+//! protocol instead, then falls back to the type chain.
+//!
+//! The file, fifo, command and stdout modes hand the text to a program
+//! instead (see [`sink`]), without fallback.
 
 pub mod clipboard;
+pub mod data_control;
+pub mod focus;
+pub mod input_method;
+pub mod keymap;
 pub mod paste;
 pub mod post_process;
+pub mod sink;
+pub mod uinput;
+pub mod virtual_keyboard;
+pub mod wayland;
 pub mod wtype;
+pub mod x11;
+pub mod xtest;
 pub mod ydotool;
 
-use crate::config::OutputConfig;
+use crate::config::{OutputBackend, OutputConfig, OutputMode};
 use crate::error::OutputError;
 use std::process::Stdio;
 use tokio::process::Command;
@@ -26,57 +49,165 @@ pub trait TextOutput: Send + Sync {
     /// Output text (type it or copy to clipboard)
     async fn output(&self, text: &str) -> Result<(), OutputError>;
 
+    /// Delete the last `count` characters this method typed (backspaces)
+    async fn erase(&self, count: usize) -> Result<(), OutputError> {
+        let _ = count;
+        Err(OutputError::EraseNotSupported(self.name().to_string()))
+    }
+
     /// Check if this output method is available
     async fn is_available(&self) -> bool;
 
     /// Human-readable name for logging
     fn name(&self) -> &'static str;
+
+    /// Backend this output implements (e.g. to erase with the same one)
+    fn backend(&self) -> OutputBackend;
+}
+
+/// Create one output backend from the [output] settings
+pub fn create_backend(
+    backend: OutputBackend,
+    config: &OutputConfig,
+    notify: bool,
+) -> Box<dyn TextOutput> {
+    match backend {
+        OutputBackend::VirtualKeyboard => Box::new(virtual_keyboard::VirtualKeyboardOutput::new(
+            config.type_delay_ms,
+            notify,
+            config.auto_submit,
+        )),
+        OutputBackend::Wtype => Box::new(wtype::WtypeOutput::new(
+            notify,
+            config.auto_submit,
+            config.wtype_delay_ms,
+        )),
+        OutputBackend::Xtest => Box::new(xtest::XtestOutput::new(
+            config.type_delay_ms,
+            notify,
+            config.auto_submit,
+        )),
+        OutputBackend::Uinput => Box::new(uinput::UinputOutput::new(
+            config.type_delay_ms,
+            notify,
+            config.auto_submit,
+            config.keyboard_layout.clone(),
+        )),
+        OutputBackend::Ydotool => Box::new(ydotool::YdotoolOutput::new(
+            config.type_delay_ms,
+            notify,
+            config.auto_submit,
+        )),
+        OutputBackend::Clipboard => Box::new(clipboard::ClipboardOutput::new(notify)),
+        OutputBackend::Paste => Box::new(paste::PasteOutput::new(
+            notify,
+            config.auto_submit,
+            config.paste_keys.clone(),
+            config.paste_selection,
+            config
+                .restore_clipboard
+                .then_some(config.restore_clipboard_delay_ms),
+        )),
+        OutputBackend::InputMethod => Box::new(input_method::InputMethodOutput::new(
+            notify,
+            config.auto_submit,
+        )),
+        OutputBackend::File => Box::new(sink::FileOutput::new(config.file.clone())),
+        OutputBackend::Fifo => Box::new(sink::FifoOutput::new(config.fifo.clone())),
+        OutputBackend::Command => Box::new(sink::CommandOutput::new(config.command.clone())),
+        OutputBackend::Stdout => Box::new(sink::StdoutOutput::new(false)),
+    }
 }
 
 /// Factory function that returns a fallback chain of output methods
 pub fn create_output_chain(config: &OutputConfig) -> Vec<Box<dyn TextOutput>> {
+    let notify = config.notification.on_transcription;
+
+    // User-ordered chain (only the backend that succeeds notifies)
+    if let Some(ref entries) = config.chain {
+        return entries
+            .iter()
+            .map(|entry| create_backend(entry.backend(), &entry.apply(config), notify))
+            .collect();
+    }
+
     let mut chain: Vec<Box<dyn TextOutput>> = Vec::new();
 
     match config.mode {
-        crate::config::OutputMode::Type => {
-            // Primary: wtype for Wayland (best Unicode/CJK support, no daemon)
-            chain.push(Box::new(wtype::WtypeOutput::new(
-                config.notification.on_transcription,
-                config.auto_submit,
-                config.wtype_delay_ms,
-            )));
+        OutputMode::InputMethod => {
+            // Primary: commit as input method (apps with text-input-v3)
+            chain.push(create_backend(OutputBackend::InputMethod, config, notify));
+
+            // Fallback: type it (terminals, Xwayland and other apps)
+            let mut config = config.clone();
+            config.mode = OutputMode::Type;
+            config.notification.on_transcription = false;
+            chain.extend(create_output_chain(&config));
+        }
+        OutputMode::Type => {
+            if crate::setup::detect_display_server() == crate::setup::DisplayServer::X11 {
+                // Primary on X11: XTEST (any Unicode character, no process)
+                chain.push(create_backend(OutputBackend::Xtest, config, notify));
+            } else {
+                // Primary: Wayland virtual keyboard (best Unicode/CJK support, no process)
+                chain.push(create_backend(
+                    OutputBackend::VirtualKeyboard,
+                    config,
+                    notify,
+                ));
+
+                // Fallback: wtype for Wayland (same protocol, separate process)
+                chain.push(create_backend(OutputBackend::Wtype, config, notify));
+            }
+
+            // Fallback: built-in uinput keyboard (works on X11/TTY, no daemon)
+            // No notification, the primary method handles it if available
+            chain.push(create_backend(OutputBackend::Uinput, config, false));
 
             // Fallback: ydotool (works on X11/TTY, requires daemon)
-            chain.push(Box::new(ydotool::YdotoolOutput::new(
-                config.type_delay_ms,
-                false, // no notification, wtype handles it if available
-                config.auto_submit,
-            )));
+            chain.push(create_backend(OutputBackend::Ydotool, config, false));
 
             // Last resort: clipboard
             if config.fallback_to_clipboard {
-                chain.push(Box::new(clipboard::ClipboardOutput::new(false)));
+                chain.push(create_backend(OutputBackend::Clipboard, config, false));
             }
         }
-        crate::config::OutputMode::Clipboard => {
+        OutputMode::Clipboard => {
             // Only clipboard
-            chain.push(Box::new(clipboard::ClipboardOutput::new(
-                config.notification.on_transcription,
-            )));
+            chain.push(create_backend(OutputBackend::Clipboard, config, notify));
         }
-        crate::config::OutputMode::Paste => {
-            // Only paste mode (no fallback as requested)
-            chain.push(Box::new(paste::PasteOutput::new(
-                config.notification.on_transcription,
-                config.auto_submit,
-                config.paste_keys.clone(),
-            )));
+        OutputMode::Paste => {
+            // Only paste mode (no fallback as requested, see `chain` for one)
+            chain.push(create_backend(OutputBackend::Paste, config, notify));
         }
+        OutputMode::File => chain.push(create_backend(OutputBackend::File, config, notify)),
+        OutputMode::Fifo => chain.push(create_backend(OutputBackend::Fifo, config, notify)),
+        OutputMode::Command => chain.push(create_backend(OutputBackend::Command, config, notify)),
+        OutputMode::Stdout => chain.push(create_backend(OutputBackend::Stdout, config, notify)),
     }
 
     chain
 }
 
+/// Delete `count` characters typed by the given output backend
+/// Used to undo the last dictation with the backend that typed it.
+pub async fn erase_with(
+    config: &OutputConfig,
+    backend: OutputBackend,
+    count: usize,
+) -> Result<(), OutputError> {
+    // A custom chain holds the backend with its own settings
+    if config.chain.is_some() {
+        let chain = create_output_chain(config);
+        return match chain.iter().find(|output| output.backend() == backend) {
+            Some(output) => output.erase(count).await,
+            None => Err(OutputError::EraseNotSupported(backend.to_string())),
+        };
+    }
+
+    create_backend(backend, config, false).erase(count).await
+}
+
 /// Run a shell command (for pre/post hooks)
 pub async fn run_hook(command: &str, hook_name: &str) -> Result<(), String> {
     tracing::debug!("Running {} hook: {}", hook_name, command);
@@ -107,11 +238,12 @@ pub struct OutputOptions<'a> {
 
 /// Try each output method in the chain until one succeeds
 /// Pre/post output commands are run before and after typing (for compositor integration).
-pub async fn output_with_fallback(
-    chain: &[Box<dyn TextOutput>],
+/// Returns the method that delivered the text.
+pub async fn output_with_fallback<'a>(
+    chain: &'a [Box<dyn TextOutput>],
     text: &str,
     options: OutputOptions<'_>,
-) -> Result<(), OutputError> {
+) -> Result<&'a dyn TextOutput, OutputError> {
     // Run pre-output hook if configured (e.g., switch to modifier-suppressing submap)
     if let Some(cmd) = options.pre_output_command {
         if let Err(e) = run_hook(cmd, "pre_output").await {
@@ -122,6 +254,7 @@ pub async fn output_with_fallback(
 
     // Try each output method
     let mut result = Err(OutputError::AllMethodsFailed);
+    let mut errors = Vec::new();
     for output in chain {
         if !output.is_available().await {
             tracing::debug!("{} not available, trying next", output.name());
@@ -131,15 +264,21 @@ pub async fn output_with_fallback(
         match output.output(text).await {
             Ok(()) => {
                 tracing::debug!("Text output via {}", output.name());
-                result = Ok(());
+                result = Ok(output.as_ref());
                 break;
             }
             Err(e) => {
                 tracing::warn!("{} failed: {}, trying next", output.name(), e);
+                errors.push(e);
             }
         }
     }
 
+    // A single method that failed tells best what went wrong
+    if result.is_err() && errors.len() == 1 {
+        result = Err(errors.remove(0));
+    }
+
     // Run post-output hook if configured (e.g., reset submap)
     // Always run this, even on failure, to ensure cleanup
     if let Some(cmd) = options.post_output_command {
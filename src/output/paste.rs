@@ -1,17 +1,30 @@
 //! Paste-based text output
 //!
-//! Uses wl-copy to copy text to clipboard, then simulates a paste keystroke.
+//! Uses wl-copy to copy text to clipboard (or sets the X11 selections
+//! directly on X11), then simulates a paste keystroke.
 //! This works around non-US keyboard layout issues by avoiding direct typing.
 //!
+//! Optionally the selection's previous contents, in all of their types, are
+//! saved first and put back after the paste (through the data control
+//! protocol on Wayland), and the primary selection can be used instead of
+//! the clipboard.
+//!
 //! Requires:
-//! - wl-copy installed (for clipboard access)
-//! - wtype OR ydotool installed (for keystroke simulation)
+//! - wl-copy installed (for clipboard access on Wayland)
+//! - wtype, XTEST, /dev/uinput access OR ydotool (for keystroke simulation)
 //!   - wtype: Wayland-native, no daemon needed (preferred)
+//!   - xtest: built in, X11 only
+//!   - uinput: built-in virtual keyboard, needs write access to /dev/uinput
 //!   - ydotool: Works on X11/Wayland/TTY, requires ydotoold daemon
 
-use super::TextOutput;
+use super::clipboard::{self, Contents};
+use super::x11::{self, KeySym};
+use super::{uinput, xtest, TextOutput};
+use crate::config::{OutputBackend, PasteSelection};
 use crate::error::OutputError;
+use evdev::Key;
 use std::process::Stdio;
+use std::time::Duration;
 use tokio::io::AsyncWriteExt;
 use tokio::process::Command;
 
@@ -106,6 +119,52 @@ impl ParsedKeystroke {
 
         Ok(args)
     }
+
+    /// Convert to evdev modifier keys and key, for the uinput keyboard
+    fn to_evdev_keys(&self) -> Result<(Vec<Key>, Key), String> {
+        let modifiers = self
+            .modifiers
+            .iter()
+            .map(|m| key_name_to_evdev(m).map(Key::new))
+            .collect::<Result<Vec<_>, _>>()?;
+        let key = Key::new(key_name_to_evdev(&self.key)?);
+        Ok((modifiers, key))
+    }
+
+    /// Convert to X keysyms, for XTEST
+    fn to_keysyms(&self) -> Result<(Vec<KeySym>, KeySym), String> {
+        let modifiers = self
+            .modifiers
+            .iter()
+            .map(|m| key_name_to_keysym(m))
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok((modifiers, key_name_to_keysym(&self.key)?))
+    }
+}
+
+/// Convert a key name to its X keysym
+fn key_name_to_keysym(name: &str) -> Result<KeySym, String> {
+    match name.to_lowercase().as_str() {
+        // Modifiers
+        "ctrl" | "control" | "leftctrl" => Ok(0xffe3), // Control_L
+        "rightctrl" => Ok(0xffe4),                     // Control_R
+        "shift" | "leftshift" => Ok(0xffe1),           // Shift_L
+        "rightshift" => Ok(0xffe2),                    // Shift_R
+        "alt" | "leftalt" => Ok(0xffe9),               // Alt_L
+        "rightalt" | "altgr" => Ok(0xfe03),            // ISO_Level3_Shift
+        "super" | "meta" | "leftmeta" | "win" => Ok(0xffeb), // Super_L
+
+        // Common keys
+        "insert" | "ins" => Ok(0xff63),   // Insert
+        "enter" | "return" => Ok(0xff0d), // Return
+
+        // Letter keysyms are their lowercase ASCII codes
+        letter if letter.len() == 1 && letter.as_bytes()[0].is_ascii_lowercase() => {
+            Ok(letter.as_bytes()[0] as KeySym)
+        }
+
+        other => Err(format!("Unknown key: {}", other)),
+    }
 }
 
 /// Convert a key name to its evdev code
@@ -164,11 +223,23 @@ pub struct PasteOutput {
     auto_submit: bool,
     /// Parsed paste keystroke
     keystroke: ParsedKeystroke,
+    /// Whether this is an X11 session (X selections and XTEST)
+    x11: bool,
+    /// Selection to paste through
+    selection: PasteSelection,
+    /// Delay after pasting before restoring the selection, None to not restore
+    restore_delay: Option<Duration>,
 }
 
 impl PasteOutput {
     /// Create a new paste output
-    pub fn new(notify: bool, auto_submit: bool, paste_keys: Option<String>) -> Self {
+    pub fn new(
+        notify: bool,
+        auto_submit: bool,
+        paste_keys: Option<String>,
+        selection: PasteSelection,
+        restore_delay_ms: Option<u32>,
+    ) -> Self {
         let keystroke_str = paste_keys.as_deref().unwrap_or("ctrl+v");
         let keystroke = ParsedKeystroke::parse(keystroke_str).unwrap_or_else(|e| {
             tracing::warn!(
@@ -185,6 +256,9 @@ impl PasteOutput {
             notify,
             auto_submit,
             keystroke,
+            x11: crate::setup::detect_display_server() == crate::setup::DisplayServer::X11,
+            selection,
+            restore_delay: restore_delay_ms.map(|ms| Duration::from_millis(ms as u64)),
         }
     }
 
@@ -211,10 +285,18 @@ impl PasteOutput {
             .await;
     }
 
-    /// Copy text to clipboard using wl-copy
+    /// Copy text to the selection using wl-copy, or directly on X11
     async fn copy_to_clipboard(&self, text: &str) -> Result<(), OutputError> {
+        if self.x11 {
+            return x11::set_selection(self.selection, text).await;
+        }
+
         // Spawn wl-copy with stdin pipe
-        let mut child = Command::new("wl-copy")
+        let mut command = Command::new("wl-copy");
+        if self.selection == PasteSelection::Primary {
+            command.arg("--primary");
+        }
+        let mut child = command
             .stdin(Stdio::piped())
             .stdout(Stdio::null())
             .stderr(Stdio::piped())
@@ -253,6 +335,58 @@ impl PasteOutput {
         Ok(())
     }
 
+    /// Read the selection's current contents, None if there is nothing to
+    /// restore (or it can't be read)
+    async fn save_selection(&self) -> Option<Contents> {
+        match clipboard::read_selection(self.selection, self.x11).await {
+            Ok(contents) if contents.is_empty() => {
+                tracing::debug!("Selection is empty, nothing to restore");
+                None
+            }
+            Ok(contents) => {
+                tracing::debug!(
+                    "Saved selection ({})",
+                    contents
+                        .iter()
+                        .map(|(kind, _)| kind.as_str())
+                        .collect::<Vec<_>>()
+                        .join(", ")
+                );
+                Some(contents)
+            }
+            Err(e) => {
+                tracing::warn!("Cannot save the clipboard, it won't be restored: {}", e);
+                None
+            }
+        }
+    }
+
+    /// Put saved contents back into the selection
+    async fn restore_selection(&self, contents: Contents) {
+        match clipboard::restore_selection(self.selection, self.x11, contents).await {
+            Ok(()) => tracing::debug!("Restored the previous selection"),
+            Err(e) => tracing::warn!("Cannot restore the clipboard: {}", e),
+        }
+    }
+
+    /// Copy the text and press the paste keystroke (and Enter if configured)
+    async fn paste(&self, text: &str) -> Result<(), OutputError> {
+        // Step 1: Copy to clipboard
+        self.copy_to_clipboard(text).await?;
+
+        // Small delay to ensure clipboard is set before pasting
+        tokio::time::sleep(Duration::from_millis(100)).await;
+
+        // Step 2: Simulate paste keystroke
+        self.simulate_paste_keystroke().await?;
+
+        // Send Enter key if configured
+        if self.auto_submit {
+            self.send_enter().await?;
+        }
+        Ok(())
+    }
+
     /// Check if wtype is available
     async fn is_wtype_available(&self) -> bool {
         // Check if wtype exists
@@ -273,6 +407,11 @@ impl PasteOutput {
         std::env::var("WAYLAND_DISPLAY").is_ok()
     }
 
+    /// Check if XTEST is available (X11 sessions only)
+    async fn is_xtest_available(&self) -> bool {
+        self.x11 && xtest::is_available().await
+    }
+
     /// Check if ydotool is available (installed and daemon running)
     async fn is_ydotool_available(&self) -> bool {
         // Check if ydotool exists
@@ -368,7 +507,23 @@ impl PasteOutput {
         Ok(())
     }
 
-    /// Simulate paste keystroke, trying wtype first then ydotool
+    /// Simulate paste keystroke using XTEST
+    async fn simulate_paste_xtest(&self) -> Result<(), OutputError> {
+        let (modifiers, key) = self.keystroke.to_keysyms().map_err(|e| {
+            OutputError::CtrlVFailed(format!("Cannot convert keystroke for XTEST: {}", e))
+        })?;
+        xtest::send_keystroke(&modifiers, key).await
+    }
+
+    /// Simulate paste keystroke using the built-in uinput keyboard
+    async fn simulate_paste_uinput(&self) -> Result<(), OutputError> {
+        let (modifiers, key) = self.keystroke.to_evdev_keys().map_err(|e| {
+            OutputError::CtrlVFailed(format!("Cannot convert keystroke for uinput: {}", e))
+        })?;
+        uinput::send_keystroke(&modifiers, key).await
+    }
+
+    /// Simulate paste keystroke, trying wtype first, then XTEST, uinput and ydotool
     async fn simulate_paste_keystroke(&self) -> Result<(), OutputError> {
         // Try wtype first (preferred - no daemon needed)
         if self.is_wtype_available().await {
@@ -378,7 +533,33 @@ impl PasteOutput {
                     return Ok(());
                 }
                 Err(e) => {
-                    tracing::debug!("wtype paste failed: {}, trying ydotool", e);
+                    tracing::debug!("wtype paste failed: {}, trying XTEST", e);
+                }
+            }
+        }
+
+        // Then XTEST on X11
+        if self.is_xtest_available().await {
+            match self.simulate_paste_xtest().await {
+                Ok(()) => {
+                    tracing::debug!("Paste keystroke sent via XTEST");
+                    return Ok(());
+                }
+                Err(e) => {
+                    tracing::debug!("XTEST paste failed: {}, trying uinput", e);
+                }
+            }
+        }
+
+        // Then the built-in uinput keyboard (no daemon needed either)
+        if uinput::is_available().await {
+            match self.simulate_paste_uinput().await {
+                Ok(()) => {
+                    tracing::debug!("Paste keystroke sent via uinput");
+                    return Ok(());
+                }
+                Err(e) => {
+                    tracing::debug!("uinput paste failed: {}, trying ydotool", e);
                 }
             }
         }
@@ -398,7 +579,7 @@ impl PasteOutput {
         }
 
         Err(OutputError::CtrlVFailed(
-            "Neither wtype nor ydotool available for paste keystroke".to_string(),
+            "None of wtype, XTEST, uinput or ydotool available for paste keystroke".to_string(),
         ))
     }
 
@@ -420,6 +601,21 @@ impl PasteOutput {
             }
         }
 
+        // Then XTEST
+        if self.is_xtest_available().await
+            && xtest::send_keystroke(&[], xtest::KEYSYM_RETURN)
+                .await
+                .is_ok()
+        {
+            return Ok(());
+        }
+
+        // Then uinput
+        if uinput::is_available().await && uinput::send_keystroke(&[], Key::KEY_ENTER).await.is_ok()
+        {
+            return Ok(());
+        }
+
         // Fall back to ydotool
         if self.is_ydotool_available().await {
             let output = Command::new("ydotool")
@@ -449,19 +645,21 @@ impl TextOutput for PasteOutput {
             return Ok(());
         }
 
-        // Step 1: Copy to clipboard
-        self.copy_to_clipboard(text).await?;
+        // Save what the user had copied before overwriting it
+        let saved = match self.restore_delay {
+            Some(_) => self.save_selection().await,
+            None => None,
+        };
 
-        // Small delay to ensure clipboard is set before pasting
-        tokio::time::sleep(std::time::Duration::from_millis(100)).await;
+        let result = self.paste(text).await;
 
-        // Step 2: Simulate paste keystroke
-        self.simulate_paste_keystroke().await?;
-
-        // Send Enter key if configured
-        if self.auto_submit {
-            self.send_enter().await?;
+        // Put it back once the application has read the text (and even if
+        // pasting failed)
+        if let (Some(delay), Some(contents)) = (self.restore_delay, saved) {
+            tokio::time::sleep(delay).await;
+            self.restore_selection(contents).await;
         }
+        result?;
 
         // Send notification if enabled
         if self.notify {
@@ -482,37 +680,104 @@ impl TextOutput for PasteOutput {
         Ok(())
     }
 
+    async fn erase(&self, count: usize) -> Result<(), OutputError> {
+        if count == 0 {
+            return Ok(());
+        }
+
+        // Pasted text is deleted like typed text, with whichever keystroke tool works
+        if self.is_wtype_available().await {
+            let output = Command::new("wtype")
+                .args(super::wtype::backspace_args(count))
+                .stdout(Stdio::null())
+                .stderr(Stdio::piped())
+                .output()
+                .await;
+
+            if let Ok(out) = output {
+                if out.status.success() {
+                    return Ok(());
+                }
+            }
+        }
+
+        if self.is_xtest_available().await
+            && xtest::send_backspaces(count, Duration::ZERO).await.is_ok()
+        {
+            return Ok(());
+        }
+
+        if uinput::is_available().await
+            && uinput::send_backspaces(count, Duration::ZERO).await.is_ok()
+        {
+            return Ok(());
+        }
+
+        if self.is_ydotool_available().await {
+            let output = Command::new("ydotool")
+                .arg("key")
+                .args(super::ydotool::backspace_args(count))
+                .stdout(Stdio::null())
+                .stderr(Stdio::piped())
+                .output()
+                .await
+                .map_err(|e| OutputError::InjectionFailed(e.to_string()))?;
+
+            if output.status.success() {
+                return Ok(());
+            }
+            let stderr = String::from_utf8_lossy(&output.stderr);
+            return Err(OutputError::InjectionFailed(stderr.to_string()));
+        }
+
+        Err(OutputError::InjectionFailed(
+            "None of wtype, XTEST, uinput or ydotool available to erase text".to_string(),
+        ))
+    }
+
     async fn is_available(&self) -> bool {
-        // Check if wl-copy exists (required for clipboard)
-        let wl_copy_available = Command::new("which")
-            .arg("wl-copy")
-            .stdout(Stdio::null())
-            .stderr(Stdio::null())
-            .status()
-            .await
-            .map(|s| s.success())
-            .unwrap_or(false);
+        // Check for clipboard access: the X server itself on X11, wl-copy otherwise
+        if self.x11 {
+            if let Err(e) = x11::probe().await {
+                tracing::debug!("paste mode unavailable: {}", e);
+                return false;
+            }
+        } else {
+            let wl_copy_available = Command::new("which")
+                .arg("wl-copy")
+                .stdout(Stdio::null())
+                .stderr(Stdio::null())
+                .status()
+                .await
+                .map(|s| s.success())
+                .unwrap_or(false);
 
-        if !wl_copy_available {
-            tracing::debug!("paste mode unavailable: wl-copy not found");
-            return false;
+            if !wl_copy_available {
+                tracing::debug!("paste mode unavailable: wl-copy not found");
+                return false;
+            }
         }
 
-        // Check if EITHER wtype OR ydotool is available for keystroke simulation
+        // Check if wtype, XTEST, uinput or ydotool is available for keystroke simulation
         let wtype_available = self.is_wtype_available().await;
+        let xtest_available = self.is_xtest_available().await;
+        let uinput_available = uinput::is_available().await;
         let ydotool_available = self.is_ydotool_available().await;
 
-        if !wtype_available && !ydotool_available {
+        if !wtype_available && !xtest_available && !uinput_available && !ydotool_available {
             tracing::debug!(
-                "paste mode unavailable: neither wtype nor ydotool available \
-                (wtype needs WAYLAND_DISPLAY, ydotool needs daemon running)"
+                "paste mode unavailable: none of wtype, XTEST, uinput or ydotool available \
+                (wtype needs WAYLAND_DISPLAY, XTEST needs an X11 session, \
+                uinput needs write access to /dev/uinput, ydotool needs daemon running)"
             );
             return false;
         }
 
         tracing::debug!(
-            "paste mode available (wtype: {}, ydotool: {})",
+            "paste mode available (wtype: {}, xtest: {}, uinput: {}, ydotool: {})",
             wtype_available,
+            xtest_available,
+            uinput_available,
             ydotool_available
         );
         true
@@ -521,4 +786,8 @@ impl TextOutput for PasteOutput {
     fn name(&self) -> &'static str {
         "paste (clipboard + keystroke)"
     }
+
+    fn backend(&self) -> OutputBackend {
+        OutputBackend::Paste
+    }
 }
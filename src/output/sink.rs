@@ -0,0 +1,485 @@
+//! Output sinks for scripts
+//!
+//! Hand the text to a program instead of typing it. None of these need a
+//! display server, so they also work on a TTY or over SSH:
+//! - file: append to a file, with strftime patterns in path and template
+//! - fifo: write a line to a named pipe that something is reading
+//! - command: pipe the text into a shell command's stdin
+//! - stdout: pass the text to a waiting `voxtype record start --wait`,
+//!   which prints it
+
+use super::TextOutput;
+use crate::config::{CommandOutputConfig, FifoOutputConfig, FileOutputConfig, OutputBackend};
+use crate::error::OutputError;
+use std::ffi::CString;
+use std::os::unix::fs::FileTypeExt;
+use std::path::{Path, PathBuf};
+use std::process::Stdio;
+use std::time::Duration;
+use tokio::io::AsyncWriteExt;
+use tokio::process::Command;
+use tokio::sync::oneshot;
+
+/// A `voxtype record start --wait` client waiting for its recording's text,
+/// or for the reason there is none
+pub type TextWaiter = oneshot::Sender<Result<String, String>>;
+
+/// Expand strftime patterns (`%Y-%m-%d`, `%H:%M`, ...) with the local time
+fn strftime(format: &str) -> String {
+    let Ok(c_format) = CString::new(format) else {
+        return format.to_string();
+    };
+    let now = crate::history::unix_now() as libc::time_t;
+
+    // SAFETY: localtime_r only writes to the tm struct we pass in, and
+    // strftime writes at most buf.len() bytes
+    unsafe {
+        let mut tm: libc::tm = std::mem::zeroed();
+        if libc::localtime_r(&now, &mut tm).is_null() {
+            return format.to_string();
+        }
+
+        // 0 means the buffer was too small (or the result is empty)
+        let mut buf = vec![0u8; format.len() * 4 + 64];
+        while buf.len() <= 64 * 1024 {
+            let len = libc::strftime(
+                buf.as_mut_ptr() as *mut libc::c_char,
+                buf.len(),
+                c_format.as_ptr(),
+                &tm,
+            );
+            if len > 0 {
+                buf.truncate(len);
+                return String::from_utf8_lossy(&buf).into_owned();
+            }
+            buf.resize(buf.len() * 2, 0);
+        }
+    }
+    String::new()
+}
+
+/// Expand a leading `~/` to the home directory
+fn expand_home(path: &str) -> PathBuf {
+    match (path.strip_prefix("~/"), directories::BaseDirs::new()) {
+        (Some(rest), Some(dirs)) => dirs.home_dir().join(rest),
+        _ => PathBuf::from(path),
+    }
+}
+
+/// What the file output appends for `text`
+/// Time patterns are expanded first, so `%` in the text is kept as is.
+fn render_template(template: &str, text: &str) -> String {
+    strftime(template).replace("{text}", text)
+}
+
+/// Appends each transcription to a file
+pub struct FileOutput {
+    config: Option<FileOutputConfig>,
+}
+
+impl FileOutput {
+    /// Create a file output from [output.file]
+    pub fn new(config: Option<FileOutputConfig>) -> Self {
+        Self { config }
+    }
+}
+
+#[async_trait::async_trait]
+impl TextOutput for FileOutput {
+    async fn output(&self, text: &str) -> Result<(), OutputError> {
+        let config = self
+            .config
+            .as_ref()
+            .ok_or(OutputError::SinkNotConfigured("file"))?;
+        let path = expand_home(&strftime(&config.path));
+        let failed = |e: std::io::Error| {
+            OutputError::SinkWriteFailed(path.display().to_string(), e.to_string())
+        };
+
+        if let Some(parent) = path.parent().filter(|p| !p.as_os_str().is_empty()) {
+            tokio::fs::create_dir_all(parent).await.map_err(failed)?;
+        }
+        let mut file = tokio::fs::OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&path)
+            .await
+            .map_err(failed)?;
+        file.write_all(render_template(&config.template, text).as_bytes())
+            .await
+            .map_err(failed)?;
+        // tokio finishes the write in the background otherwise
+        file.flush().await.map_err(failed)?;
+
+        tracing::info!("Text appended to {:?} ({} chars)", path, text.len());
+        Ok(())
+    }
+
+    async fn is_available(&self) -> bool {
+        true
+    }
+
+    fn name(&self) -> &'static str {
+        "file"
+    }
+
+    fn backend(&self) -> OutputBackend {
+        OutputBackend::File
+    }
+}
+
+/// Longest wait for the reader of a named pipe to take a line
+const FIFO_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Create the named pipe at `path` if missing
+fn create_fifo(path: &Path) -> std::io::Result<()> {
+    match std::fs::metadata(path) {
+        Ok(metadata) if !metadata.file_type().is_fifo() => {
+            Err(std::io::Error::other("not a named pipe"))
+        }
+        Ok(_) => Ok(()),
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => {
+            let c_path =
+                CString::new(path.as_os_str().as_encoded_bytes()).map_err(std::io::Error::other)?;
+            // SAFETY: c_path is a valid NUL-terminated string
+            if unsafe { libc::mkfifo(c_path.as_ptr(), 0o600) } != 0 {
+                return Err(std::io::Error::last_os_error());
+            }
+            Ok(())
+        }
+        Err(e) => Err(e),
+    }
+}
+
+/// Write `data` to the named pipe at `path`, creating the pipe if missing
+/// Fails right away if nothing has the pipe open for reading, and after
+/// `timeout` if the reader doesn't take the data.
+async fn write_fifo(path: &Path, data: &[u8], timeout: Duration) -> std::io::Result<()> {
+    create_fifo(path)?;
+
+    // The pipe is opened non-blocking, which fails with ENXIO instead of
+    // waiting for a reader
+    let mut fifo = tokio::net::unix::pipe::OpenOptions::new()
+        .open_sender(path)
+        .map_err(|e| match e.raw_os_error() {
+            Some(libc::ENXIO) => std::io::Error::other("no reader has the pipe open"),
+            _ => e,
+        })?;
+
+    tokio::time::timeout(timeout, fifo.write_all(data))
+        .await
+        .map_err(|_| {
+            std::io::Error::new(
+                std::io::ErrorKind::TimedOut,
+                format!("reader didn't take the text within {:?}", timeout),
+            )
+        })?
+}
+
+/// Writes each transcription as a line to a named pipe
+pub struct FifoOutput {
+    config: Option<FifoOutputConfig>,
+}
+
+impl FifoOutput {
+    /// Create a named pipe output from [output.fifo]
+    pub fn new(config: Option<FifoOutputConfig>) -> Self {
+        Self { config }
+    }
+}
+
+#[async_trait::async_trait]
+impl TextOutput for FifoOutput {
+    async fn output(&self, text: &str) -> Result<(), OutputError> {
+        let config = self
+            .config
+            .as_ref()
+            .ok_or(OutputError::SinkNotConfigured("fifo"))?;
+        let path = expand_home(&config.path);
+        let line = format!("{}\n", text);
+
+        write_fifo(&path, line.as_bytes(), FIFO_TIMEOUT)
+            .await
+            .map_err(|e| OutputError::SinkWriteFailed(path.display().to_string(), e.to_string()))?;
+
+        tracing::info!("Text written to {:?} ({} chars)", path, text.len());
+        Ok(())
+    }
+
+    async fn is_available(&self) -> bool {
+        true
+    }
+
+    fn name(&self) -> &'static str {
+        "fifo"
+    }
+
+    fn backend(&self) -> OutputBackend {
+        OutputBackend::Fifo
+    }
+}
+
+/// Pipes each transcription into a shell command
+pub struct CommandOutput {
+    config: Option<CommandOutputConfig>,
+}
+
+impl CommandOutput {
+    /// Create a command output from [output.command]
+    pub fn new(config: Option<CommandOutputConfig>) -> Self {
+        Self { config }
+    }
+}
+
+#[async_trait::async_trait]
+impl TextOutput for CommandOutput {
+    async fn output(&self, text: &str) -> Result<(), OutputError> {
+        let config = self
+            .config
+            .as_ref()
+            .ok_or(OutputError::SinkNotConfigured("command"))?;
+
+        let mut child = Command::new("sh")
+            .args(["-c", &config.command])
+            .stdin(Stdio::piped())
+            .stdout(Stdio::null())
+            .stderr(Stdio::piped())
+            .kill_on_drop(true)
+            .spawn()
+            .map_err(|e| OutputError::CommandFailed(format!("failed to spawn: {}", e)))?;
+
+        // The timeout covers writing the text too: a command that neither
+        // reads its input nor exits would block the write
+        let stdin = child.stdin.take();
+        let run = async move {
+            if let Some(mut stdin) = stdin {
+                // A command that doesn't read its input is fine
+                if let Err(e) = stdin.write_all(text.as_bytes()).await {
+                    if e.kind() != std::io::ErrorKind::BrokenPipe {
+                        return Err(e);
+                    }
+                }
+            }
+            child.wait_with_output().await
+        };
+
+        let timeout = Duration::from_millis(config.timeout_ms);
+        let output = tokio::time::timeout(timeout, run)
+            .await
+            .map_err(|_| {
+                OutputError::CommandFailed(format!("timed out after {}ms", config.timeout_ms))
+            })?
+            .map_err(|e| OutputError::CommandFailed(e.to_string()))?;
+
+        if !output.status.success() {
+            let stderr = String::from_utf8_lossy(&output.stderr);
+            return Err(OutputError::CommandFailed(format!(
+                "{}: {}",
+                output.status,
+                stderr.trim()
+            )));
+        }
+
+        tracing::info!("Text piped into output command ({} chars)", text.len());
+        Ok(())
+    }
+
+    async fn is_available(&self) -> bool {
+        true
+    }
+
+    fn name(&self) -> &'static str {
+        "command"
+    }
+
+    fn backend(&self) -> OutputBackend {
+        OutputBackend::Command
+    }
+}
+
+/// Passes the transcription to the client waiting in
+/// `voxtype record start --wait`
+///
+/// The daemon keeps that client's waiter with the recording and hands it the
+/// text once this output succeeded. Without a waiting client it fails, so a
+/// chain moves on to the next output.
+pub struct StdoutOutput {
+    client_waiting: bool,
+}
+
+impl StdoutOutput {
+    pub fn new(client_waiting: bool) -> Self {
+        Self { client_waiting }
+    }
+}
+
+#[async_trait::async_trait]
+impl TextOutput for StdoutOutput {
+    async fn output(&self, text: &str) -> Result<(), OutputError> {
+        if !self.client_waiting {
+            return Err(OutputError::NoWaitingClient);
+        }
+
+        tracing::info!("Text passed to the waiting client ({} chars)", text.len());
+        Ok(())
+    }
+
+    async fn is_available(&self) -> bool {
+        true
+    }
+
+    fn name(&self) -> &'static str {
+        "stdout"
+    }
+
+    fn backend(&self) -> OutputBackend {
+        OutputBackend::Stdout
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::os::unix::fs::OpenOptionsExt;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_render_template() {
+        assert_eq!(render_template("{text}\n", "hello"), "hello\n");
+        assert_eq!(render_template("- {text}", "100%"), "- 100%");
+        assert_eq!(render_template("%%{text}", "x"), "%x");
+
+        let year = render_template("%Y {text}", "x");
+        assert_eq!(year.len(), 6);
+        assert!(year.starts_with("20"));
+    }
+
+    #[tokio::test]
+    async fn test_file_output_appends() {
+        let dir = TempDir::new().unwrap();
+        let path = dir.path().join("notes").join("dictation.txt");
+        let output = FileOutput::new(Some(FileOutputConfig {
+            path: path.to_string_lossy().into_owned(),
+            template: "> {text}\n".to_string(),
+        }));
+
+        output.output("first").await.unwrap();
+        output.output("second").await.unwrap();
+        assert_eq!(
+            std::fs::read_to_string(&path).unwrap(),
+            "> first\n> second\n"
+        );
+
+        let err = FileOutput::new(None).output("x").await.unwrap_err();
+        assert!(matches!(err, OutputError::SinkNotConfigured("file")));
+    }
+
+    #[tokio::test]
+    async fn test_fifo_output() {
+        let dir = TempDir::new().unwrap();
+        let path = dir.path().join("output.fifo");
+        let output = FifoOutput::new(Some(FifoOutputConfig {
+            path: path.to_string_lossy().into_owned(),
+        }));
+
+        // Creates the pipe, then fails without a reader
+        let err = output.output("lost").await.unwrap_err();
+        assert!(matches!(err, OutputError::SinkWriteFailed(..)));
+        assert!(std::fs::metadata(&path).unwrap().file_type().is_fifo());
+
+        let reader_path = path.clone();
+        let reader = std::thread::spawn(move || std::fs::read_to_string(reader_path).unwrap());
+        // Wait for the reader to open the pipe
+        let mut result = output.output("hello").await;
+        for _ in 0..100 {
+            if result.is_ok() {
+                break;
+            }
+            tokio::time::sleep(Duration::from_millis(10)).await;
+            result = output.output("hello").await;
+        }
+        result.unwrap();
+        assert_eq!(reader.join().unwrap(), "hello\n");
+    }
+
+    #[tokio::test]
+    async fn test_fifo_write_times_out_without_reading() {
+        let dir = TempDir::new().unwrap();
+        let path = dir.path().join("output.fifo");
+        create_fifo(&path).unwrap();
+
+        // A reader that has the pipe open but never reads from it
+        let reader = std::fs::OpenOptions::new()
+            .read(true)
+            .custom_flags(libc::O_NONBLOCK)
+            .open(&path)
+            .unwrap();
+        let err = write_fifo(&path, &vec![b'x'; 1 << 20], Duration::from_millis(50))
+            .await
+            .unwrap_err();
+        assert_eq!(err.kind(), std::io::ErrorKind::TimedOut);
+        drop(reader);
+    }
+
+    #[tokio::test]
+    async fn test_fifo_output_rejects_regular_file() {
+        let dir = TempDir::new().unwrap();
+        let path = dir.path().join("output.txt");
+        std::fs::write(&path, "").unwrap();
+
+        let output = FifoOutput::new(Some(FifoOutputConfig {
+            path: path.to_string_lossy().into_owned(),
+        }));
+        assert!(output.output("hello").await.is_err());
+        assert_eq!(std::fs::read_to_string(&path).unwrap(), "");
+    }
+
+    #[tokio::test]
+    async fn test_command_output() {
+        let dir = TempDir::new().unwrap();
+        let path = dir.path().join("out.txt");
+        let command = |command: String, timeout_ms| {
+            CommandOutput::new(Some(CommandOutputConfig {
+                command,
+                timeout_ms,
+            }))
+        };
+
+        command(format!("cat > {:?}", path), 5000)
+            .output("hello world")
+            .await
+            .unwrap();
+        assert_eq!(std::fs::read_to_string(&path).unwrap(), "hello world");
+
+        // Not reading stdin is fine, failing is not
+        command("true".to_string(), 5000)
+            .output("ignored")
+            .await
+            .unwrap();
+        let err = command("echo nope >&2; exit 3".to_string(), 5000)
+            .output("x")
+            .await
+            .unwrap_err();
+        assert!(err.to_string().contains("nope"), "{}", err);
+
+        let err = command("sleep 5".to_string(), 50)
+            .output("x")
+            .await
+            .unwrap_err();
+        assert!(err.to_string().contains("timed out"), "{}", err);
+
+        // More text than the pipe holds, for a command that never reads it
+        let err = command("sleep 5".to_string(), 50)
+            .output(&"x".repeat(1 << 20))
+            .await
+            .unwrap_err();
+        assert!(err.to_string().contains("timed out"), "{}", err);
+    }
+
+    #[tokio::test]
+    async fn test_stdout_output_needs_waiting_client() {
+        StdoutOutput::new(true).output("hello").await.unwrap();
+        let err = StdoutOutput::new(false).output("hello").await.unwrap_err();
+        assert!(matches!(err, OutputError::NoWaitingClient), "{}", err);
+    }
+}
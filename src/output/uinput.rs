@@ -0,0 +1,312 @@
+//! Built-in uinput text output
+//!
+//! Types text through a virtual keyboard created with the kernel's uinput
+//! interface. Like ydotool this works on Wayland, X11 and the console, but
+//! no daemon is needed. Characters are mapped to keys with the active XKB
+//! layout (see `keymap`).
+//!
+//! Requires:
+//! - Write access to /dev/uinput (user in 'input' group with the uinput
+//!   udev rule, as for ydotool)
+
+use super::keymap::{self, KeyStroke, Keymap, LayoutName};
+use super::TextOutput;
+use crate::config::OutputBackend;
+use crate::error::OutputError;
+use evdev::uinput::{VirtualDevice, VirtualDeviceBuilder};
+use evdev::{AttributeSet, EventType, InputEvent, Key};
+use std::process::Stdio;
+use std::time::Duration;
+use tokio::process::Command;
+use tokio::sync::{MappedMutexGuard, Mutex, MutexGuard};
+
+/// Name of the virtual keyboard (the hotkey listener never opens it)
+pub const KEYBOARD_NAME: &str = "voxtype keyboard";
+
+/// How long the compositor gets to pick up a new virtual keyboard
+/// (events sent earlier are dropped)
+const DEVICE_SETTLE: Duration = Duration::from_millis(200);
+
+/// The virtual keyboard, created on first use and kept for the life of the
+/// process, so the settle delay is only paid once
+static KEYBOARD: Mutex<Option<VirtualKeyboard>> = Mutex::const_new(None);
+
+/// A uinput keyboard and the keymap for typing with it
+struct VirtualKeyboard {
+    device: VirtualDevice,
+    /// Keymap of the layout it was built for
+    keymap: Option<(LayoutName, Keymap)>,
+}
+
+impl VirtualKeyboard {
+    fn new() -> std::io::Result<Self> {
+        // Every keyboard key, so any layout can be typed
+        let mut keys = AttributeSet::<Key>::new();
+        for code in Key::KEY_ESC.code()..=Key::KEY_MICMUTE.code() {
+            keys.insert(Key::new(code));
+        }
+        let device = VirtualDeviceBuilder::new()?
+            .name(KEYBOARD_NAME)
+            .with_keys(&keys)?
+            .build()?;
+        Ok(Self {
+            device,
+            keymap: None,
+        })
+    }
+
+    /// Keymap for a layout, compiled the first time it is used
+    fn keymap(&mut self, layout: &LayoutName) -> &Keymap {
+        if self.keymap.as_ref().is_some_and(|(name, _)| name != layout) {
+            self.keymap = None;
+        }
+        &self
+            .keymap
+            .get_or_insert_with(|| {
+                let keymap = Keymap::from_xkb(layout).unwrap_or_else(|e| {
+                    tracing::warn!("{}, typing with the US layout", e);
+                    Keymap::us()
+                });
+                tracing::info!("uinput typing with keyboard layout {}", layout);
+                (layout.clone(), keymap)
+            })
+            .1
+    }
+
+    fn send(&mut self, key: Key, value: i32) -> Result<(), OutputError> {
+        self.device
+            .emit(&[InputEvent::new(EventType::KEY, key.code(), value)])
+            .map_err(|e| OutputError::InjectionFailed(format!("uinput: {}", e)))
+    }
+
+    /// Press and release a key with modifiers held
+    fn tap(&mut self, stroke: &KeyStroke) -> Result<(), OutputError> {
+        for modifier in &stroke.modifiers {
+            self.send(*modifier, 1)?;
+        }
+        self.send(stroke.key, 1)?;
+        self.send(stroke.key, 0)?;
+        for modifier in stroke.modifiers.iter().rev() {
+            self.send(*modifier, 0)?;
+        }
+        Ok(())
+    }
+
+    /// Tap each stroke, waiting `delay` between them
+    async fn type_strokes(
+        &mut self,
+        strokes: &[KeyStroke],
+        delay: Duration,
+    ) -> Result<(), OutputError> {
+        for (i, stroke) in strokes.iter().enumerate() {
+            if i > 0 && !delay.is_zero() {
+                tokio::time::sleep(delay).await;
+            }
+            self.tap(stroke)?;
+        }
+        Ok(())
+    }
+}
+
+/// Lock the virtual keyboard, creating it on first use
+async fn lock_keyboard() -> Result<MappedMutexGuard<'static, VirtualKeyboard>, OutputError> {
+    let mut keyboard = KEYBOARD.lock().await;
+    if keyboard.is_none() {
+        let created =
+            VirtualKeyboard::new().map_err(|e| OutputError::UinputUnavailable(e.to_string()))?;
+        tracing::debug!("Created uinput keyboard");
+        tokio::time::sleep(DEVICE_SETTLE).await;
+        *keyboard = Some(created);
+    }
+    Ok(MutexGuard::map(keyboard, |keyboard| {
+        keyboard.as_mut().expect("keyboard created above")
+    }))
+}
+
+/// Whether a uinput keyboard can be created
+pub async fn is_available() -> bool {
+    match lock_keyboard().await {
+        Ok(_) => true,
+        Err(e) => {
+            tracing::debug!("{}", e);
+            false
+        }
+    }
+}
+
+/// Press a key combination (e.g. Ctrl+V for paste mode)
+pub async fn send_keystroke(modifiers: &[Key], key: Key) -> Result<(), OutputError> {
+    let stroke = KeyStroke {
+        key,
+        modifiers: modifiers.to_vec(),
+    };
+    lock_keyboard().await?.tap(&stroke)
+}
+
+/// Tap Backspace `count` times
+pub async fn send_backspaces(count: usize, delay: Duration) -> Result<(), OutputError> {
+    let strokes = vec![
+        KeyStroke {
+            key: Key::KEY_BACKSPACE,
+            modifiers: Vec::new(),
+        };
+        count
+    ];
+    lock_keyboard().await?.type_strokes(&strokes, delay).await
+}
+
+/// Keystrokes that type `text`; fails if any character isn't in the layout
+fn text_strokes(
+    keymap: &Keymap,
+    layout: &LayoutName,
+    text: &str,
+) -> Result<Vec<KeyStroke>, OutputError> {
+    let mut strokes = Vec::new();
+    let mut missing = String::new();
+    for c in text.chars() {
+        match keymap.stroke(c) {
+            Some(stroke) => strokes.push(stroke.clone()),
+            None if !missing.contains(c) => missing.push(c),
+            None => {}
+        }
+    }
+
+    if missing.is_empty() {
+        Ok(strokes)
+    } else {
+        Err(OutputError::NotInLayout(layout.to_string(), missing))
+    }
+}
+
+/// Text output through a built-in uinput keyboard
+pub struct UinputOutput {
+    /// Delay between keypresses in milliseconds
+    delay_ms: u32,
+    /// Whether to show a desktop notification
+    notify: bool,
+    /// Whether to send Enter key after output
+    auto_submit: bool,
+    /// Configured XKB layout (None: detect)
+    layout: Option<String>,
+}
+
+impl UinputOutput {
+    /// Create a new uinput output
+    pub fn new(delay_ms: u32, notify: bool, auto_submit: bool, layout: Option<String>) -> Self {
+        Self {
+            delay_ms,
+            notify,
+            auto_submit,
+            layout,
+        }
+    }
+
+    /// The configured layout, or the one the session uses
+    async fn layout(&self) -> LayoutName {
+        match self.layout {
+            Some(ref layout) => LayoutName::parse(layout),
+            None => keymap::detect_layout().await.unwrap_or_else(LayoutName::us),
+        }
+    }
+
+    /// Send a desktop notification
+    async fn send_notification(&self, text: &str) {
+        // Truncate preview for notification
+        let preview: String = text.chars().take(100).collect();
+        let preview = if text.chars().count() > 100 {
+            format!("{}...", preview)
+        } else {
+            preview
+        };
+
+        let _ = Command::new("notify-send")
+            .args([
+                "--app-name=Voxtype",
+                "--expire-time=3000",
+                "Transcribed",
+                &preview,
+            ])
+            .stdout(Stdio::null())
+            .stderr(Stdio::null())
+            .status()
+            .await;
+    }
+}
+
+#[async_trait::async_trait]
+impl TextOutput for UinputOutput {
+    async fn output(&self, text: &str) -> Result<(), OutputError> {
+        if text.is_empty() {
+            return Ok(());
+        }
+
+        let layout = self.layout().await;
+        let mut keyboard = lock_keyboard().await?;
+
+        // Check every character first, so nothing is typed if the next
+        // output method has to take over
+        let mut strokes = text_strokes(keyboard.keymap(&layout), &layout, text)?;
+        if self.auto_submit {
+            strokes.push(KeyStroke {
+                key: Key::KEY_ENTER,
+                modifiers: Vec::new(),
+            });
+        }
+
+        let delay = Duration::from_millis(self.delay_ms as u64);
+        keyboard.type_strokes(&strokes, delay).await?;
+        drop(keyboard);
+
+        if self.notify {
+            self.send_notification(text).await;
+        }
+
+        Ok(())
+    }
+
+    async fn erase(&self, count: usize) -> Result<(), OutputError> {
+        send_backspaces(count, Duration::from_millis(self.delay_ms as u64)).await
+    }
+
+    async fn is_available(&self) -> bool {
+        is_available().await
+    }
+
+    fn name(&self) -> &'static str {
+        "uinput"
+    }
+
+    fn backend(&self) -> OutputBackend {
+        OutputBackend::Uinput
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_text_strokes() {
+        let keymap = Keymap::us();
+        let strokes = text_strokes(&keymap, &LayoutName::us(), "Hi!\n").unwrap();
+        let keys: Vec<_> = strokes.iter().map(|s| s.key).collect();
+        assert_eq!(
+            keys,
+            vec![Key::KEY_H, Key::KEY_I, Key::KEY_1, Key::KEY_ENTER]
+        );
+        assert_eq!(strokes[0].modifiers, vec![Key::KEY_LEFTSHIFT]);
+        assert!(strokes[1].modifiers.is_empty());
+    }
+
+    #[test]
+    fn test_text_strokes_reports_missing_characters() {
+        let keymap = Keymap::us();
+        match text_strokes(&keymap, &LayoutName::us(), "café für 日本") {
+            Err(OutputError::NotInLayout(layout, missing)) => {
+                assert_eq!(layout, "us");
+                assert_eq!(missing, "éü日本");
+            }
+            other => panic!("unexpected result: {:?}", other),
+        }
+    }
+}
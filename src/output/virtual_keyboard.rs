@@ -0,0 +1,593 @@
+//! Native Wayland virtual keyboard output
+//!
+//! Types text with the zwp_virtual_keyboard_v1 protocol, like wtype, but from
+//! inside the daemon: no process is spawned per transcription, and the
+//! connection and virtual keyboard are kept between transcriptions.
+//!
+//! For every text a keymap is generated that gives each character its own
+//! key with a single level. Any Unicode character (CJK, emoji) can be typed
+//! that way, and neither the user's layout nor held modifiers change what
+//! arrives: the virtual keyboard's modifiers are reset before typing. After
+//! a new keymap is uploaded we wait for the compositor to process it, so the
+//! first character is not dropped (the wtype_delay_ms workaround).
+//!
+//! Requires:
+//! - A compositor offering zwp_virtual_keyboard_manager_v1 (wlroots-based
+//!   compositors such as Sway and Hyprland, niri)
+//! - Running on Wayland (WAYLAND_DISPLAY set)
+
+use super::wayland::{self, Globals, WithGlobals};
+use super::TextOutput;
+use crate::config::OutputBackend;
+use crate::error::OutputError;
+use std::io::{self, Write};
+use std::os::fd::{AsFd, FromRawFd, OwnedFd};
+use std::process::Stdio;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+use tokio::process::Command;
+use wayland_client::protocol::wl_keyboard::KeymapFormat;
+use wayland_client::protocol::wl_seat::WlSeat;
+use wayland_client::{delegate_noop, Connection, EventQueue, Proxy};
+use wayland_protocols_misc::zwp_virtual_keyboard_v1::client::zwp_virtual_keyboard_manager_v1::ZwpVirtualKeyboardManagerV1;
+use wayland_protocols_misc::zwp_virtual_keyboard_v1::client::zwp_virtual_keyboard_v1::ZwpVirtualKeyboardV1;
+
+/// Keys a generated keymap can hold (XKB keycodes 9..=255, which X11
+/// clients under Xwayland can still see)
+const MAX_KEYS: usize = 247;
+
+/// The virtual keyboard, created on first use and kept for the life of the
+/// process
+static KEYBOARD: Mutex<Option<VirtualKeyboard>> = Mutex::new(None);
+
+/// Keysym name for a character, as understood by xkbcommon
+fn keysym_name(c: char) -> Option<String> {
+    match c {
+        '\n' => Some("Return".to_string()),
+        '\t' => Some("Tab".to_string()),
+        c if c.is_control() => None,
+        // "U<hex>" is the Unicode keysym (Latin-1 maps to the legacy one)
+        c => Some(format!("U{:04X}", c as u32)),
+    }
+}
+
+/// Keys to tap with the keymap that contains them
+#[derive(Debug, PartialEq)]
+struct Batch {
+    /// Keysym of each key; key code `i + 1` types `keysyms[i]`
+    keysyms: Vec<String>,
+    /// Key codes to tap, in order
+    keys: Vec<u32>,
+}
+
+/// Split keysyms into batches whose keymaps fit in MAX_KEYS keys
+///
+/// The first batch extends the `current` keymap, so texts using characters
+/// already on the keyboard don't need a new keymap.
+fn plan(current: &[String], keysyms: impl IntoIterator<Item = String>) -> Vec<Batch> {
+    let mut batches = vec![Batch {
+        keysyms: current.to_vec(),
+        keys: Vec::new(),
+    }];
+    for keysym in keysyms {
+        let batch = match batches.last_mut() {
+            Some(batch) if batch.keysyms.contains(&keysym) || batch.keysyms.len() < MAX_KEYS => {
+                batch
+            }
+            _ => {
+                batches.push(Batch {
+                    keysyms: Vec::new(),
+                    keys: Vec::new(),
+                });
+                batches.last_mut().expect("just pushed")
+            }
+        };
+        let index = match batch.keysyms.iter().position(|k| *k == keysym) {
+            Some(index) => index,
+            None => {
+                batch.keysyms.push(keysym);
+                batch.keysyms.len() - 1
+            }
+        };
+        batch.keys.push(index as u32 + 1);
+    }
+    batches.retain(|batch| !batch.keys.is_empty());
+    batches
+}
+
+/// Keymap with key code `i + 1` (XKB keycode `i + 9`) typing `keysyms[i]`
+fn keymap_text(keysyms: &[String]) -> String {
+    let mut keycodes = String::new();
+    let mut symbols = String::new();
+    for (i, keysym) in keysyms.iter().enumerate() {
+        keycodes.push_str(&format!("    <K{}> = {};\n", i + 1, i + 9));
+        symbols.push_str(&format!("    key <K{}> {{ [ {} ] }};\n", i + 1, keysym));
+    }
+
+    format!(
+        "xkb_keymap {{\n\
+         xkb_keycodes \"voxtype\" {{\n    minimum = 8;\n    maximum = {};\n{}}};\n\
+         xkb_types \"voxtype\" {{ include \"complete\" }};\n\
+         xkb_compatibility \"voxtype\" {{ include \"complete\" }};\n\
+         xkb_symbols \"voxtype\" {{\n{}}};\n\
+         }};\n",
+        keysyms.len() + 8,
+        keycodes,
+        symbols
+    )
+}
+
+/// Put a keymap in a memfd for the compositor to map
+fn keymap_fd(keymap: &str) -> io::Result<(OwnedFd, u32)> {
+    let name = c"voxtype-keymap";
+    // SAFETY: memfd_create with a valid C string; the fd is owned from here
+    let fd = unsafe { libc::memfd_create(name.as_ptr(), libc::MFD_CLOEXEC) };
+    if fd < 0 {
+        return Err(io::Error::last_os_error());
+    }
+    let mut file = std::fs::File::from(unsafe { OwnedFd::from_raw_fd(fd) });
+    // The keymap is sent NUL-terminated
+    file.write_all(keymap.as_bytes())?;
+    file.write_all(&[0])?;
+    Ok((file.into(), keymap.len() as u32 + 1))
+}
+
+/// Dispatch state of the connection (virtual keyboards have no events)
+#[derive(Default)]
+struct State {
+    globals: Globals,
+}
+
+impl WithGlobals for State {
+    fn globals(&mut self) -> &mut Globals {
+        &mut self.globals
+    }
+}
+
+wayland::delegate_globals!(State);
+delegate_noop!(State: ignore WlSeat);
+delegate_noop!(State: ZwpVirtualKeyboardManagerV1);
+delegate_noop!(State: ZwpVirtualKeyboardV1);
+
+/// A virtual keyboard on the compositor's seat
+struct VirtualKeyboard {
+    connection: Connection,
+    queue: EventQueue<State>,
+    state: State,
+    keyboard: ZwpVirtualKeyboardV1,
+    /// Keysyms of the keymap the compositor has
+    keysyms: Vec<String>,
+    /// Reference for key event timestamps
+    start: Instant,
+}
+
+impl VirtualKeyboard {
+    fn new(connection: Connection) -> io::Result<Self> {
+        let mut state = State::default();
+        let mut queue = wayland::event_queue(&connection, &mut state)?;
+        let qh = queue.handle();
+        let seat: WlSeat = state.globals.bind(&qh, 1)?;
+        let manager: ZwpVirtualKeyboardManagerV1 = state.globals.bind(&qh, 1)?;
+        let keyboard = manager.create_virtual_keyboard(&seat, &qh, ());
+        // Some compositors refuse untrusted clients with a protocol error
+        wayland::roundtrip(&connection, &mut queue, &mut state)?;
+
+        Ok(Self {
+            connection,
+            queue,
+            state,
+            keyboard,
+            keysyms: Vec::new(),
+            start: Instant::now(),
+        })
+    }
+
+    fn roundtrip(&mut self) -> io::Result<()> {
+        wayland::roundtrip(&self.connection, &mut self.queue, &mut self.state)
+    }
+
+    /// Make sure the compositor uses the keymap for `keysyms`
+    fn use_keymap(&mut self, keysyms: &[String]) -> io::Result<()> {
+        if self.keysyms == keysyms {
+            return Ok(());
+        }
+
+        let (fd, size) = keymap_fd(&keymap_text(keysyms))?;
+        self.keyboard
+            .keymap(KeymapFormat::XkbV1.into(), fd.as_fd(), size);
+        // No modifiers: nothing the user holds applies to this keyboard
+        self.keyboard.modifiers(0, 0, 0, 0);
+        // Keys sent before the keymap is in place get lost
+        self.roundtrip()?;
+        self.keysyms = keysyms.to_vec();
+        Ok(())
+    }
+
+    fn tap(&mut self, key: u32) -> io::Result<()> {
+        for state in [1, 0] {
+            let time = self.start.elapsed().as_millis() as u32;
+            self.keyboard.key(time, key, state);
+        }
+        wayland::flush(&self.connection)
+    }
+
+    /// Type the keysyms, waiting `delay` between keys (blocking)
+    fn type_keysyms(
+        &mut self,
+        keysyms: impl IntoIterator<Item = String>,
+        delay: Duration,
+    ) -> io::Result<()> {
+        for (i, batch) in plan(&self.keysyms, keysyms).into_iter().enumerate() {
+            self.use_keymap(&batch.keysyms)?;
+            for (j, key) in batch.keys.into_iter().enumerate() {
+                if (i > 0 || j > 0) && !delay.is_zero() {
+                    std::thread::sleep(delay);
+                }
+                self.tap(key)?;
+            }
+        }
+        // Surface errors now rather than on the next transcription
+        self.roundtrip()
+    }
+}
+
+/// Run `f` with the virtual keyboard on a blocking thread, creating the
+/// keyboard first if needed
+///
+/// A keyboard whose connection failed is dropped, so the next call
+/// reconnects (e.g. after the compositor restarted).
+async fn with_keyboard<F>(f: F) -> Result<(), OutputError>
+where
+    F: FnOnce(&mut VirtualKeyboard) -> io::Result<()> + Send + 'static,
+{
+    let failed = |e: &dyn std::fmt::Display| {
+        OutputError::InjectionFailed(format!("virtual keyboard: {}", e))
+    };
+    tokio::task::spawn_blocking(move || {
+        let mut keyboard = KEYBOARD.lock().unwrap_or_else(|e| e.into_inner());
+        if keyboard.is_none() {
+            let created = wayland::connect()
+                .and_then(VirtualKeyboard::new)
+                .map_err(|e| failed(&e))?;
+            tracing::debug!("Created Wayland virtual keyboard");
+            *keyboard = Some(created);
+        }
+
+        let result = f(keyboard.as_mut().expect("keyboard created above"));
+        result.map_err(|e| {
+            *keyboard = None;
+            failed(&e)
+        })
+    })
+    .await
+    .map_err(|e| failed(&e))?
+}
+
+/// Tap keys given by keysym name (e.g. "Return")
+pub async fn send_keysyms(keysyms: Vec<String>) -> Result<(), OutputError> {
+    with_keyboard(move |keyboard| keyboard.type_keysyms(keysyms, Duration::ZERO)).await
+}
+
+/// Whether the compositor of this session offers virtual keyboards
+pub fn compositor_supports() -> io::Result<bool> {
+    let mut state = State::default();
+    wayland::event_queue(&wayland::connect()?, &mut state)?;
+    Ok(state
+        .globals
+        .get(ZwpVirtualKeyboardManagerV1::interface().name)
+        .is_some())
+}
+
+/// Keysyms that type `text`; fails on characters no key can produce
+fn text_keysyms(text: &str) -> Result<Vec<String>, OutputError> {
+    text.chars()
+        .map(|c| {
+            keysym_name(c).ok_or_else(|| {
+                OutputError::InjectionFailed(format!("cannot type control character {:?}", c))
+            })
+        })
+        .collect()
+}
+
+/// Text output through a Wayland virtual keyboard
+pub struct VirtualKeyboardOutput {
+    /// Delay between keypresses in milliseconds
+    delay_ms: u32,
+    /// Whether to show a desktop notification
+    notify: bool,
+    /// Whether to send Enter key after output
+    auto_submit: bool,
+}
+
+impl VirtualKeyboardOutput {
+    /// Create a new virtual keyboard output
+    pub fn new(delay_ms: u32, notify: bool, auto_submit: bool) -> Self {
+        Self {
+            delay_ms,
+            notify,
+            auto_submit,
+        }
+    }
+
+    fn delay(&self) -> Duration {
+        Duration::from_millis(self.delay_ms as u64)
+    }
+
+    /// Send a desktop notification
+    async fn send_notification(&self, text: &str) {
+        // Truncate preview for notification
+        let preview: String = text.chars().take(100).collect();
+        let preview = if text.chars().count() > 100 {
+            format!("{}...", preview)
+        } else {
+            preview
+        };
+
+        let _ = Command::new("notify-send")
+            .args([
+                "--app-name=Voxtype",
+                "--expire-time=3000",
+                "Transcribed",
+                &preview,
+            ])
+            .stdout(Stdio::null())
+            .stderr(Stdio::null())
+            .status()
+            .await;
+    }
+}
+
+#[async_trait::async_trait]
+impl TextOutput for VirtualKeyboardOutput {
+    async fn output(&self, text: &str) -> Result<(), OutputError> {
+        if text.is_empty() {
+            return Ok(());
+        }
+
+        let mut keysyms = text_keysyms(text)?;
+        if self.auto_submit {
+            keysyms.push("Return".to_string());
+        }
+        let delay = self.delay();
+        with_keyboard(move |keyboard| keyboard.type_keysyms(keysyms, delay)).await?;
+
+        if self.notify {
+            self.send_notification(text).await;
+        }
+
+        Ok(())
+    }
+
+    async fn erase(&self, count: usize) -> Result<(), OutputError> {
+        if count == 0 {
+            return Ok(());
+        }
+        let keysyms = vec!["BackSpace".to_string(); count];
+        let delay = self.delay();
+        with_keyboard(move |keyboard| keyboard.type_keysyms(keysyms, delay)).await
+    }
+
+    async fn is_available(&self) -> bool {
+        match with_keyboard(|_| Ok(())).await {
+            Ok(()) => true,
+            Err(e) => {
+                tracing::debug!("{}", e);
+                false
+            }
+        }
+    }
+
+    fn name(&self) -> &'static str {
+        "virtual-keyboard"
+    }
+
+    fn backend(&self) -> OutputBackend {
+        OutputBackend::VirtualKeyboard
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::wayland::mock::Compositor;
+    use super::*;
+    use std::io::{Read, Seek};
+    use wayland_protocols_misc::zwp_virtual_keyboard_v1::server::{
+        zwp_virtual_keyboard_manager_v1 as manager, zwp_virtual_keyboard_v1 as keyboard,
+    };
+    use wayland_server::protocol::wl_seat;
+    use wayland_server::{Client, DataInit, Dispatch, DisplayHandle, GlobalDispatch, New};
+
+    /// Requests the compositor received from virtual keyboards
+    #[derive(Debug, Default)]
+    struct Received(Vec<Request>);
+
+    #[derive(Debug, PartialEq)]
+    enum Request {
+        Keymap(String),
+        Modifiers,
+        Key(u32, u32),
+    }
+
+    impl GlobalDispatch<wl_seat::WlSeat, ()> for Received {
+        fn bind(
+            _: &mut Self,
+            _: &DisplayHandle,
+            _: &Client,
+            seat: New<wl_seat::WlSeat>,
+            _: &(),
+            init: &mut DataInit<'_, Self>,
+        ) {
+            init.init(seat, ());
+        }
+    }
+
+    impl Dispatch<wl_seat::WlSeat, ()> for Received {
+        fn request(
+            _: &mut Self,
+            _: &Client,
+            _: &wl_seat::WlSeat,
+            _: wl_seat::Request,
+            _: &(),
+            _: &DisplayHandle,
+            _: &mut DataInit<'_, Self>,
+        ) {
+        }
+    }
+
+    impl GlobalDispatch<manager::ZwpVirtualKeyboardManagerV1, ()> for Received {
+        fn bind(
+            _: &mut Self,
+            _: &DisplayHandle,
+            _: &Client,
+            manager: New<manager::ZwpVirtualKeyboardManagerV1>,
+            _: &(),
+            init: &mut DataInit<'_, Self>,
+        ) {
+            init.init(manager, ());
+        }
+    }
+
+    impl Dispatch<manager::ZwpVirtualKeyboardManagerV1, ()> for Received {
+        fn request(
+            _: &mut Self,
+            _: &Client,
+            _: &manager::ZwpVirtualKeyboardManagerV1,
+            request: manager::Request,
+            _: &(),
+            _: &DisplayHandle,
+            init: &mut DataInit<'_, Self>,
+        ) {
+            if let manager::Request::CreateVirtualKeyboard { id, .. } = request {
+                init.init(id, ());
+            }
+        }
+    }
+
+    impl Dispatch<keyboard::ZwpVirtualKeyboardV1, ()> for Received {
+        fn request(
+            received: &mut Self,
+            _: &Client,
+            _: &keyboard::ZwpVirtualKeyboardV1,
+            request: keyboard::Request,
+            _: &(),
+            _: &DisplayHandle,
+            _: &mut DataInit<'_, Self>,
+        ) {
+            received.0.push(match request {
+                keyboard::Request::Keymap { fd, .. } => {
+                    // The memfd is shared with the sender, which left it at the end
+                    let mut file = std::fs::File::from(fd);
+                    let mut keymap = String::new();
+                    file.rewind().unwrap();
+                    file.read_to_string(&mut keymap).unwrap();
+                    Request::Keymap(keymap)
+                }
+                keyboard::Request::Modifiers { .. } => Request::Modifiers,
+                keyboard::Request::Key { key, state, .. } => Request::Key(key, state),
+                _ => return,
+            });
+        }
+    }
+
+    #[test]
+    fn test_keysym_names() {
+        assert_eq!(keysym_name('a').unwrap(), "U0061");
+        assert_eq!(keysym_name('ü').unwrap(), "U00FC");
+        assert_eq!(keysym_name('中').unwrap(), "U4E2D");
+        assert_eq!(keysym_name('😀').unwrap(), "U1F600");
+        assert_eq!(keysym_name('\n').unwrap(), "Return");
+        assert!(keysym_name('\x07').is_none());
+        assert!(text_keysyms("bell\x07").is_err());
+    }
+
+    #[test]
+    fn test_plan_reuses_keys() {
+        let batches = plan(&[], text_keysyms("abba").unwrap());
+        assert_eq!(
+            batches,
+            vec![Batch {
+                keysyms: vec!["U0061".to_string(), "U0062".to_string()],
+                keys: vec![1, 2, 2, 1],
+            }]
+        );
+
+        // Keys of the current keymap keep their codes
+        let batches = plan(&batches[0].keysyms, text_keysyms("bc").unwrap());
+        assert_eq!(
+            batches,
+            vec![Batch {
+                keysyms: vec![
+                    "U0061".to_string(),
+                    "U0062".to_string(),
+                    "U0063".to_string()
+                ],
+                keys: vec![2, 3],
+            }]
+        );
+    }
+
+    #[test]
+    fn test_plan_splits_large_texts() {
+        // 300 different characters, then one from the first batch again
+        let text: String = (0..300u32)
+            .map(|i| char::from_u32(0x4e00 + i).unwrap())
+            .chain(['\u{4e00}'])
+            .collect();
+        let batches = plan(&[], text_keysyms(&text).unwrap());
+        assert_eq!(batches.len(), 2);
+        assert_eq!(batches[0].keysyms.len(), MAX_KEYS);
+        assert_eq!(batches[1].keysyms.len(), 300 - MAX_KEYS + 1);
+        assert_eq!(batches[1].keysyms.last().unwrap(), "U4E00");
+    }
+
+    #[test]
+    fn test_keymap_text() {
+        let keymap = keymap_text(&["U4E2D".to_string(), "Return".to_string()]);
+        assert!(keymap.contains("maximum = 10;"));
+        assert!(keymap.contains("<K1> = 9;"));
+        assert!(keymap.contains("key <K1> { [ U4E2D ] };"));
+        assert!(keymap.contains("key <K2> { [ Return ] };"));
+    }
+
+    #[test]
+    fn test_types_through_protocol() {
+        let (compositor, connection) = Compositor::start(Received::default(), |display| {
+            display.create_global::<Received, wl_seat::WlSeat, ()>(8, ());
+            display.create_global::<Received, manager::ZwpVirtualKeyboardManagerV1, ()>(1, ());
+        });
+        let mut keyboard = VirtualKeyboard::new(connection).unwrap();
+        keyboard
+            .type_keysyms(text_keysyms("日本日").unwrap(), Duration::ZERO)
+            .unwrap();
+        // Characters already on the keyboard: no new keymap
+        keyboard
+            .type_keysyms(text_keysyms("本").unwrap(), Duration::ZERO)
+            .unwrap();
+        drop(keyboard);
+
+        let mut requests = compositor.finish().0.into_iter();
+
+        // Keymap, then modifiers cleared, then taps
+        let Some(Request::Keymap(keymap)) = requests.next() else {
+            panic!("keymap first");
+        };
+        assert!(keymap.contains("key <K1> { [ U65E5 ] };"));
+        assert!(keymap.contains("key <K2> { [ U672C ] };"));
+        assert!(keymap.ends_with('\0'));
+        assert_eq!(requests.next(), Some(Request::Modifiers));
+        assert_eq!(
+            requests.collect::<Vec<_>>(),
+            [
+                (1, 1),
+                (1, 0),
+                (2, 1),
+                (2, 0),
+                (1, 1),
+                (1, 0),
+                (2, 1),
+                (2, 0)
+            ]
+            .map(|(key, state)| Request::Key(key, state))
+        );
+    }
+}
@@ -0,0 +1,390 @@
+//! Wayland plumbing for the output backends
+//!
+//! The backends that talk to the compositor directly use wayland-client
+//! from a blocking thread. They share the globals list, and a roundtrip
+//! that gives up after a timeout instead of hanging the output on a
+//! compositor that stopped answering.
+
+use std::io;
+use std::os::fd::{AsRawFd, BorrowedFd};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use wayland_client::backend::WaylandError;
+use wayland_client::protocol::{wl_callback, wl_registry};
+use wayland_client::{Dispatch, EventQueue, Proxy, QueueHandle};
+
+/// How long to wait for the compositor before giving up
+const TIMEOUT: Duration = Duration::from_secs(2);
+
+/// Globals announced by the compositor, kept in a client's dispatch state
+#[derive(Debug, Default)]
+pub struct Globals {
+    registry: Option<wl_registry::WlRegistry>,
+    list: Vec<Global>,
+}
+
+impl Globals {
+    /// The compositor's global with this interface
+    pub fn get(&self, interface: &str) -> Option<&Global> {
+        self.list.iter().find(|g| g.interface == interface)
+    }
+
+    /// Bind the global of interface `I`, at most at `max_version`
+    pub fn bind<I, D>(&self, qh: &QueueHandle<D>, max_version: u32) -> io::Result<I>
+    where
+        I: Proxy + 'static,
+        D: Dispatch<I, ()> + 'static,
+    {
+        let interface = I::interface().name;
+        let (Some(registry), Some(global)) = (&self.registry, self.get(interface)) else {
+            return Err(io::Error::new(
+                io::ErrorKind::Unsupported,
+                format!("compositor doesn't support {}", interface),
+            ));
+        };
+        Ok(registry.bind(global.name, global.version.min(max_version), qh, ()))
+    }
+}
+
+/// Dispatch state that keeps the compositor's globals
+pub trait WithGlobals {
+    fn globals(&mut self) -> &mut Globals;
+}
+
+impl<D> Dispatch<wl_registry::WlRegistry, (), D> for Globals
+where
+    D: WithGlobals + Dispatch<wl_registry::WlRegistry, ()>,
+{
+    fn event(
+        state: &mut D,
+        _: &wl_registry::WlRegistry,
+        event: wl_registry::Event,
+        _: &(),
+        _: &wayland_client::Connection,
+        _: &QueueHandle<D>,
+    ) {
+        let list = &mut state.globals().list;
+        match event {
+            wl_registry::Event::Global {
+                name,
+                interface,
+                version,
+            } => list.push(Global {
+                name,
+                interface,
+                version,
+            }),
+            wl_registry::Event::GlobalRemove { name } => list.retain(|g| g.name != name),
+            _ => {}
+        }
+    }
+}
+
+/// The `done` of a roundtrip's wl_display.sync
+impl<D> Dispatch<wl_callback::WlCallback, Arc<AtomicBool>, D> for Globals
+where
+    D: Dispatch<wl_callback::WlCallback, Arc<AtomicBool>>,
+{
+    fn event(
+        _: &mut D,
+        _: &wl_callback::WlCallback,
+        _: wl_callback::Event,
+        done: &Arc<AtomicBool>,
+        _: &wayland_client::Connection,
+        _: &QueueHandle<D>,
+    ) {
+        done.store(true, Ordering::Relaxed);
+    }
+}
+
+/// Dispatch the registry and roundtrip events of a [`WithGlobals`] state
+macro_rules! delegate_globals {
+    ($state:ty) => {
+        wayland_client::delegate_dispatch!($state:
+            [wayland_client::protocol::wl_registry::WlRegistry: ()]
+            => $crate::output::wayland::Globals);
+        wayland_client::delegate_dispatch!($state:
+            [wayland_client::protocol::wl_callback::WlCallback:
+                std::sync::Arc<std::sync::atomic::AtomicBool>]
+            => $crate::output::wayland::Globals);
+    };
+}
+pub(crate) use delegate_globals;
+
+/// Connect to the compositor of this session (WAYLAND_DISPLAY)
+pub fn connect() -> io::Result<wayland_client::Connection> {
+    wayland_client::Connection::connect_to_env()
+        .map_err(|e| io::Error::new(io::ErrorKind::NotFound, e))
+}
+
+/// Create an event queue and read the compositor's globals into `state`
+pub fn event_queue<D>(
+    connection: &wayland_client::Connection,
+    state: &mut D,
+) -> io::Result<EventQueue<D>>
+where
+    D: WithGlobals
+        + Dispatch<wl_registry::WlRegistry, ()>
+        + Dispatch<wl_callback::WlCallback, Arc<AtomicBool>>
+        + 'static,
+{
+    let mut queue = connection.new_event_queue();
+    state.globals().registry = Some(connection.display().get_registry(&queue.handle(), ()));
+    roundtrip(connection, &mut queue, state)?;
+    Ok(queue)
+}
+
+/// Wait until the compositor has handled every request sent so far,
+/// dispatching the events that arrive meanwhile
+///
+/// Protocol errors are returned as errors, and so is a compositor that
+/// doesn't answer within TIMEOUT.
+pub fn roundtrip<D>(
+    connection: &wayland_client::Connection,
+    queue: &mut EventQueue<D>,
+    state: &mut D,
+) -> io::Result<()>
+where
+    D: Dispatch<wl_callback::WlCallback, Arc<AtomicBool>> + 'static,
+{
+    let done = Arc::new(AtomicBool::new(false));
+    connection.display().sync(&queue.handle(), done.clone());
+
+    let deadline = Instant::now() + TIMEOUT;
+    loop {
+        queue.dispatch_pending(state).map_err(io::Error::other)?;
+        if done.load(Ordering::Relaxed) {
+            return Ok(());
+        }
+        flush(connection)?;
+
+        // None: events arrived meanwhile, dispatch them first
+        let Some(guard) = queue.prepare_read() else {
+            continue;
+        };
+        if !wait_readable(guard.connection_fd(), deadline)? {
+            return Err(io::Error::new(
+                io::ErrorKind::TimedOut,
+                "compositor didn't answer",
+            ));
+        }
+        match guard.read() {
+            Err(WaylandError::Io(e)) if e.kind() == io::ErrorKind::WouldBlock => {}
+            result => {
+                result.map_err(io::Error::other)?;
+            }
+        }
+    }
+}
+
+/// Send the requests queued so far, waiting while the socket is full
+pub fn flush(connection: &wayland_client::Connection) -> io::Result<()> {
+    let deadline = Instant::now() + TIMEOUT;
+    loop {
+        match connection.flush() {
+            Err(WaylandError::Io(e)) if e.kind() == io::ErrorKind::WouldBlock => {
+                let backend = connection.backend();
+                if !wait(backend.poll_fd(), libc::POLLOUT, deadline)? {
+                    return Err(io::Error::new(
+                        io::ErrorKind::TimedOut,
+                        "compositor doesn't read its requests",
+                    ));
+                }
+            }
+            result => return result.map_err(io::Error::other),
+        }
+    }
+}
+
+/// Wait until `fd` is readable; false if `deadline` passed first
+pub fn wait_readable(fd: BorrowedFd, deadline: Instant) -> io::Result<bool> {
+    wait(fd, libc::POLLIN, deadline)
+}
+
+/// Wait for `events` on `fd`; false if `deadline` passed first
+fn wait(fd: BorrowedFd, events: libc::c_short, deadline: Instant) -> io::Result<bool> {
+    loop {
+        let left = deadline.saturating_duration_since(Instant::now());
+        let mut poll = libc::pollfd {
+            fd: fd.as_raw_fd(),
+            events,
+            revents: 0,
+        };
+        // SAFETY: one valid pollfd
+        let ready = unsafe { libc::poll(&mut poll, 1, left.as_millis() as libc::c_int) };
+        if ready >= 0 {
+            return Ok(ready > 0);
+        }
+        let error = io::Error::last_os_error();
+        if error.kind() != io::ErrorKind::Interrupted {
+            return Err(error);
+        }
+    }
+}
+
+/// A global object announced by the compositor
+#[derive(Debug, Clone)]
+pub struct Global {
+    pub name: u32,
+    pub interface: String,
+    pub version: u32,
+}
+
+/// An in-process compositor stand-in for tests
+#[cfg(test)]
+pub(crate) mod mock {
+    use super::*;
+    use std::os::unix::net::UnixStream;
+    use std::thread::JoinHandle;
+    use wayland_server::backend::{ClientData, ClientId, DisconnectReason};
+    use wayland_server::{Display, DisplayHandle};
+
+    /// A wayland-server display with a single client, run on a thread
+    pub struct Compositor<S> {
+        thread: JoinHandle<S>,
+    }
+
+    impl<S: Send + 'static> Compositor<S> {
+        /// Serve a client with `state`, after `setup` created the globals
+        ///
+        /// Returns the compositor and a client connection to it.
+        pub fn start(
+            mut state: S,
+            setup: impl FnOnce(&DisplayHandle) + Send + 'static,
+        ) -> (Self, wayland_client::Connection) {
+            let (client, server) = UnixStream::pair().unwrap();
+            let thread = std::thread::spawn(move || {
+                let mut display = Display::<S>::new().unwrap();
+                let mut handle = display.handle();
+                setup(&handle);
+                let gone = Arc::new(AtomicBool::new(false));
+                handle
+                    .insert_client(server, Arc::new(TestClient(gone.clone())))
+                    .unwrap();
+
+                // The client hangs up when done
+                while !gone.load(Ordering::Relaxed) {
+                    display.dispatch_clients(&mut state).unwrap();
+                    display.flush_clients().unwrap();
+                    let deadline = Instant::now() + Duration::from_millis(20);
+                    wait_readable(display.backend().poll_fd(), deadline).unwrap();
+                }
+                state
+            });
+
+            let client = wayland_client::Connection::from_socket(client).unwrap();
+            (Self { thread }, client)
+        }
+
+        /// Wait for the client to disconnect and return the final state
+        pub fn finish(self) -> S {
+            self.thread.join().unwrap()
+        }
+    }
+
+    struct TestClient(Arc<AtomicBool>);
+
+    impl ClientData for TestClient {
+        fn disconnected(&self, _: ClientId, _: DisconnectReason) {
+            self.0.store(true, Ordering::Relaxed);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::mock::Compositor;
+    use super::*;
+    use wayland_client::delegate_noop;
+    use wayland_client::protocol::{wl_compositor, wl_keyboard, wl_seat};
+    use wayland_server::{DataInit, DisplayHandle, GlobalDispatch, New, Resource};
+
+    /// Client state with nothing but the globals
+    #[derive(Default)]
+    struct Client {
+        globals: Globals,
+    }
+
+    impl WithGlobals for Client {
+        fn globals(&mut self) -> &mut Globals {
+            &mut self.globals
+        }
+    }
+
+    delegate_globals!(Client);
+    delegate_noop!(Client: ignore wl_seat::WlSeat);
+    delegate_noop!(Client: ignore wl_keyboard::WlKeyboard);
+    delegate_noop!(Client: wl_compositor::WlCompositor);
+
+    /// Compositor with a seat that refuses keyboards
+    struct Seat;
+
+    type ServerSeat = wayland_server::protocol::wl_seat::WlSeat;
+
+    impl GlobalDispatch<ServerSeat, ()> for Seat {
+        fn bind(
+            _: &mut Self,
+            _: &DisplayHandle,
+            _: &wayland_server::Client,
+            seat: New<ServerSeat>,
+            _: &(),
+            init: &mut DataInit<'_, Self>,
+        ) {
+            init.init(seat, ());
+        }
+    }
+
+    impl wayland_server::Dispatch<ServerSeat, ()> for Seat {
+        fn request(
+            _: &mut Self,
+            _: &wayland_server::Client,
+            seat: &ServerSeat,
+            _: wayland_server::protocol::wl_seat::Request,
+            _: &(),
+            _: &DisplayHandle,
+            _: &mut DataInit<'_, Self>,
+        ) {
+            seat.post_error(0u32, "not allowed");
+        }
+    }
+
+    fn compositor() -> (Compositor<Seat>, wayland_client::Connection) {
+        Compositor::start(Seat, |display| {
+            display.create_global::<Seat, ServerSeat, ()>(7, ());
+        })
+    }
+
+    #[test]
+    fn test_globals() {
+        let (compositor, connection) = compositor();
+        let mut client = Client::default();
+        let queue = event_queue(&connection, &mut client).unwrap();
+        assert_eq!(client.globals.get("wl_seat").unwrap().version, 7);
+        assert!(client.globals.get("wl_compositor").is_none());
+
+        let qh = queue.handle();
+        let error = client
+            .globals
+            .bind::<wl_compositor::WlCompositor, _>(&qh, 4)
+            .unwrap_err();
+        assert_eq!(error.kind(), io::ErrorKind::Unsupported);
+        // The lower of ours and theirs
+        let seat: wl_seat::WlSeat = client.globals.bind(&qh, 5).unwrap();
+        assert_eq!(seat.version(), 5);
+
+        drop((seat, queue, connection));
+        compositor.finish();
+    }
+
+    #[test]
+    fn test_roundtrip_protocol_error() {
+        let (_compositor, connection) = compositor();
+        let mut client = Client::default();
+        let mut queue = event_queue(&connection, &mut client).unwrap();
+        let seat: wl_seat::WlSeat = client.globals.bind(&queue.handle(), 7).unwrap();
+        seat.get_keyboard(&queue.handle(), ());
+        let error = roundtrip(&connection, &mut queue, &mut client).unwrap_err();
+        assert!(error.to_string().contains("not allowed"), "{}", error);
+    }
+}
@@ -10,6 +10,7 @@
 //! - Running on Wayland (WAYLAND_DISPLAY set)
 
 use super::TextOutput;
+use crate::config::OutputBackend;
 use crate::error::OutputError;
 use std::process::Stdio;
 use tokio::process::Command;
@@ -58,6 +59,13 @@ impl WtypeOutput {
     }
 }
 
+/// wtype arguments that tap BackSpace `count` times
+pub(crate) fn backspace_args(count: usize) -> Vec<&'static str> {
+    std::iter::repeat_n(["-k", "BackSpace"], count)
+        .flatten()
+        .collect()
+}
+
 #[async_trait::async_trait]
 impl TextOutput for WtypeOutput {
     async fn output(&self, text: &str) -> Result<(), OutputError> {
@@ -119,6 +127,36 @@ impl TextOutput for WtypeOutput {
         Ok(())
     }
 
+    async fn erase(&self, count: usize) -> Result<(), OutputError> {
+        if count == 0 {
+            return Ok(());
+        }
+
+        let output = Command::new("wtype")
+            .args(backspace_args(count))
+            .stdout(Stdio::null())
+            .stderr(Stdio::piped())
+            .output()
+            .await
+            .map_err(|e| {
+                if e.kind() == std::io::ErrorKind::NotFound {
+                    OutputError::WtypeNotFound
+                } else {
+                    OutputError::InjectionFailed(e.to_string())
+                }
+            })?;
+
+        if !output.status.success() {
+            let stderr = String::from_utf8_lossy(&output.stderr);
+            return Err(OutputError::InjectionFailed(format!(
+                "wtype failed: {}",
+                stderr
+            )));
+        }
+
+        Ok(())
+    }
+
     async fn is_available(&self) -> bool {
         // Just check if wtype exists in PATH
         // Don't check WAYLAND_DISPLAY - systemd services may not have it
@@ -136,6 +174,10 @@ impl TextOutput for WtypeOutput {
     fn name(&self) -> &'static str {
         "wtype"
     }
+
+    fn backend(&self) -> OutputBackend {
+        OutputBackend::Wtype
+    }
 }
 
 #[cfg(test)]
@@ -157,6 +199,15 @@ mod tests {
         assert!(output.auto_submit);
     }
 
+    #[test]
+    fn test_backspace_args() {
+        assert_eq!(
+            backspace_args(2),
+            vec!["-k", "BackSpace", "-k", "BackSpace"]
+        );
+        assert!(backspace_args(0).is_empty());
+    }
+
     #[test]
     fn test_new_with_delay() {
         let output = WtypeOutput::new(false, false, 200);
@@ -0,0 +1,697 @@
+//! X11 connection for the X11 output backends
+//!
+//! The X server is spoken to through x11rb's pure Rust connection, so
+//! voxtype neither links against nor loads libX11. Besides the connection
+//! this module owns the CLIPBOARD and PRIMARY selections for clipboard and
+//! paste output: X11 has no clipboard daemon, so a thread keeps serving the
+//! text to applications until another client takes the selections over.
+//! It can also read a selection in all of its targets, so paste mode can
+//! restore it, and reads which application has the focus, for app rules.
+//!
+//! All X requests block, so the public functions run them on a blocking
+//! thread.
+
+use super::clipboard::Contents;
+use crate::config::PasteSelection;
+use crate::error::OutputError;
+use std::sync::mpsc;
+use std::time::{Duration, Instant};
+use x11rb::connection::Connection;
+use x11rb::errors::{ConnectionError, ReplyOrIdError};
+use x11rb::protocol::xproto::{
+    Atom, AtomEnum, ConnectionExt as _, CreateWindowAux, EventMask, GetPropertyReply, PropMode,
+    Property, SelectionNotifyEvent, SelectionRequestEvent, Window, WindowClass,
+    SELECTION_NOTIFY_EVENT,
+};
+use x11rb::protocol::Event;
+use x11rb::rust_connection::RustConnection;
+use x11rb::wrapper::ConnectionExt as _;
+use x11rb::{COPY_DEPTH_FROM_PARENT, COPY_FROM_PARENT, CURRENT_TIME, NONE};
+
+pub type KeySym = x11rb::protocol::xproto::Keysym;
+
+/// `NoSymbol`
+pub const NO_SYMBOL: KeySym = 0;
+
+/// Longest property read, in 32-bit units (the server sends what there is)
+const MAX_PROPERTY_LENGTH: u32 = 0x1fff_ffff;
+
+/// Result of a sequence of X requests
+pub type XResult<T> = Result<T, ReplyOrIdError>;
+
+/// An X error (or lost connection) as an output error
+pub fn x11_error(e: ReplyOrIdError) -> OutputError {
+    OutputError::InjectionFailed(format!("X11: {}", e))
+}
+
+/// Run blocking X requests without holding up the runtime
+async fn blocking<T, F>(f: F) -> Result<T, OutputError>
+where
+    T: Send + 'static,
+    F: FnOnce() -> Result<T, OutputError> + Send + 'static,
+{
+    tokio::task::spawn_blocking(f)
+        .await
+        .map_err(|e| OutputError::InjectionFailed(e.to_string()))?
+}
+
+/// A connection to an X server
+pub struct Display {
+    pub connection: RustConnection,
+    /// Root window of the default screen
+    pub root: Window,
+}
+
+impl Display {
+    /// Connect to the X server of this session (DISPLAY)
+    pub fn open() -> Result<Self, OutputError> {
+        if std::env::var_os("DISPLAY").is_none() {
+            return Err(OutputError::X11Unavailable("DISPLAY not set".to_string()));
+        }
+        Self::connect(None)
+    }
+
+    /// Connect to the X server `name` (e.g. ":1"), or DISPLAY's if None
+    pub fn connect(name: Option<&str>) -> Result<Self, OutputError> {
+        let (connection, screen) = x11rb::connect(name)
+            .map_err(|e| OutputError::X11Unavailable(format!("cannot open X display: {}", e)))?;
+        let root = connection.setup().roots[screen].root;
+        Ok(Self { connection, root })
+    }
+
+    fn intern_atom(&self, name: &str) -> XResult<Atom> {
+        Ok(self
+            .connection
+            .intern_atom(false, name.as_bytes())?
+            .reply()?
+            .atom)
+    }
+
+    fn atom_name(&self, atom: Atom) -> XResult<String> {
+        let name = self.connection.get_atom_name(atom)?.reply()?.name;
+        Ok(String::from_utf8_lossy(&name).into_owned())
+    }
+
+    fn selection_atom(&self, selection: PasteSelection) -> XResult<Atom> {
+        match selection {
+            PasteSelection::Clipboard => self.intern_atom("CLIPBOARD"),
+            PasteSelection::Primary => Ok(AtomEnum::PRIMARY.into()),
+        }
+    }
+
+    /// An unmapped window of our own, receiving `events`
+    fn create_window(&self, events: EventMask) -> XResult<Window> {
+        let window = self.connection.generate_id()?;
+        self.connection.create_window(
+            COPY_DEPTH_FROM_PARENT,
+            window,
+            self.root,
+            0,
+            0,
+            1,
+            1,
+            0,
+            WindowClass::INPUT_OUTPUT,
+            COPY_FROM_PARENT,
+            &CreateWindowAux::new().event_mask(events),
+        )?;
+        Ok(window)
+    }
+
+    /// A window property of any type, None if it isn't set
+    ///
+    /// With `delete`, the property is deleted once read, as selection
+    /// transfers require.
+    fn property(
+        &self,
+        window: Window,
+        property: Atom,
+        delete: bool,
+    ) -> XResult<Option<GetPropertyReply>> {
+        let reply = self
+            .connection
+            .get_property(
+                delete,
+                window,
+                property,
+                AtomEnum::ANY,
+                0,
+                MAX_PROPERTY_LENGTH,
+            )?
+            .reply()?;
+        // An unset property has type None
+        Ok((reply.type_ != NONE).then_some(reply))
+    }
+
+    /// Wait for an event that `wanted` accepts, polling until `deadline`
+    ///
+    /// Other events are dropped.
+    fn wait_event(
+        &self,
+        deadline: Instant,
+        wanted: impl Fn(&Event) -> bool,
+    ) -> Result<Option<Event>, ConnectionError> {
+        loop {
+            while let Some(event) = self.connection.poll_for_event()? {
+                if wanted(&event) {
+                    return Ok(Some(event));
+                }
+            }
+            if Instant::now() >= deadline {
+                return Ok(None);
+            }
+            std::thread::sleep(Duration::from_millis(5));
+        }
+    }
+
+    /// WM_CLASS class of the active window (per `_NET_ACTIVE_WINDOW`)
+    fn active_window_class(&self) -> XResult<Option<String>> {
+        let active = self.intern_atom("_NET_ACTIVE_WINDOW")?;
+        let window = self
+            .property(self.root, active, false)?
+            .and_then(|property| property.value32()?.next());
+        let window = match window {
+            Some(window) if window != NONE => window,
+            _ => return Ok(None),
+        };
+
+        Ok(self
+            .property(window, AtomEnum::WM_CLASS.into(), false)?
+            .filter(|property| property.format == 8)
+            .and_then(|property| wm_class_name(&property.value)))
+    }
+
+    /// Read `selection` in all of its targets (empty if nobody owns it)
+    fn read_selection(&self, selection: PasteSelection) -> XResult<Contents> {
+        let selection = self.selection_atom(selection)?;
+        if self
+            .connection
+            .get_selection_owner(selection)?
+            .reply()?
+            .owner
+            == NONE
+        {
+            return Ok(Vec::new());
+        }
+        // Property changes are needed for INCR transfers
+        let window = self.create_window(EventMask::PROPERTY_CHANGE)?;
+
+        let reader = SelectionReader {
+            display: self,
+            window,
+            selection,
+            property: self.intern_atom("VOXTYPE_SELECTION")?,
+            incr: self.intern_atom("INCR")?,
+            deadline: Instant::now() + READ_TIMEOUT,
+        };
+        let contents = reader.read_all();
+        self.connection.destroy_window(window)?;
+        self.connection.flush()?;
+        contents
+    }
+}
+
+/// WM_CLASS class of the active window (per `_NET_ACTIVE_WINDOW`)
+pub async fn active_window_class() -> Result<Option<String>, OutputError> {
+    blocking(|| Display::open()?.active_window_class().map_err(x11_error)).await
+}
+
+/// The class of a WM_CLASS value ("instance\0class\0")
+fn wm_class_name(value: &[u8]) -> Option<String> {
+    value
+        .split(|&b| b == 0)
+        .nth(1)
+        .filter(|class| !class.is_empty())
+        .map(|class| String::from_utf8_lossy(class).into_owned())
+}
+
+/// Check that the X server of this session can be reached
+pub async fn probe() -> Result<(), OutputError> {
+    blocking(|| Display::open().map(drop)).await
+}
+
+/// Targets that ask the owner to do something rather than for data
+const SPECIAL_TARGETS: [&str; 7] = [
+    "TARGETS",
+    "MULTIPLE",
+    "TIMESTAMP",
+    "SAVE_TARGETS",
+    "DELETE",
+    "INSERT_SELECTION",
+    "INSERT_PROPERTY",
+];
+
+/// How long a selection owner gets to send all of its targets
+const READ_TIMEOUT: Duration = Duration::from_secs(2);
+
+/// Text in the targets applications ask for
+fn text_contents(text: &str) -> Contents {
+    // STRING is Latin-1; the others are UTF-8
+    vec![
+        ("UTF8_STRING".to_string(), text.as_bytes().to_vec()),
+        (
+            "text/plain;charset=utf-8".to_string(),
+            text.as_bytes().to_vec(),
+        ),
+        ("TEXT".to_string(), text.as_bytes().to_vec()),
+        ("STRING".to_string(), latin1(text)),
+    ]
+}
+
+/// Put `text` in the CLIPBOARD and PRIMARY selections
+///
+/// Returns once both are owned; a thread then serves the text until other
+/// clients own both selections.
+pub async fn set_selections(text: &str) -> Result<(), OutputError> {
+    let contents = text_contents(text);
+    blocking(move || {
+        own_selections(
+            Display::open()?,
+            vec![PasteSelection::Clipboard, PasteSelection::Primary],
+            contents,
+        )
+    })
+    .await
+}
+
+/// Put `text` in one selection, like `set_selections`
+pub async fn set_selection(selection: PasteSelection, text: &str) -> Result<(), OutputError> {
+    let contents = text_contents(text);
+    blocking(move || own_selections(Display::open()?, vec![selection], contents)).await
+}
+
+/// Give a selection the contents read by `read_selection` back
+pub async fn restore_selection(
+    selection: PasteSelection,
+    contents: Contents,
+) -> Result<(), OutputError> {
+    blocking(move || own_selections(Display::open()?, vec![selection], contents)).await
+}
+
+/// Own `selections` with `contents`, served on `display` from a thread
+fn own_selections(
+    display: Display,
+    selections: Vec<PasteSelection>,
+    contents: Contents,
+) -> Result<(), OutputError> {
+    let (ready_tx, ready_rx) = mpsc::channel();
+
+    std::thread::Builder::new()
+        .name("x11-selection".to_string())
+        .spawn(move || {
+            if let Err(e) = serve_selections(&display, &selections, &contents, &ready_tx) {
+                tracing::debug!("Stopped serving the X11 selections: {}", e);
+                // Only heard if the selections weren't owned yet
+                let _ = ready_tx.send(Err(x11_error(e)));
+            }
+        })
+        .map_err(|e| OutputError::InjectionFailed(e.to_string()))?;
+
+    ready_rx
+        .recv()
+        .map_err(|_| OutputError::InjectionFailed("selection thread exited".to_string()))?
+}
+
+/// Own the selections and answer requests for them until all are lost
+fn serve_selections(
+    display: &Display,
+    selections: &[PasteSelection],
+    contents: &Contents,
+    ready: &mpsc::Sender<Result<(), OutputError>>,
+) -> XResult<()> {
+    let connection = &display.connection;
+    let targets_atom = display.intern_atom("TARGETS")?;
+    let targets = contents
+        .iter()
+        .map(|(name, data)| Ok((display.intern_atom(name)?, data.as_slice())))
+        .collect::<XResult<Vec<(Atom, &[u8])>>>()?;
+
+    let window = display.create_window(EventMask::NO_EVENT)?;
+    let mut owned = selections
+        .iter()
+        .map(|&selection| display.selection_atom(selection))
+        .collect::<XResult<Vec<Atom>>>()?;
+    for &selection in &owned {
+        connection.set_selection_owner(window, selection, CURRENT_TIME)?;
+    }
+    for &selection in &owned {
+        if connection.get_selection_owner(selection)?.reply()?.owner != window {
+            connection.destroy_window(window)?;
+            connection.flush()?;
+            let _ = ready.send(Err(OutputError::InjectionFailed(
+                "could not take over the X11 selections".to_string(),
+            )));
+            return Ok(());
+        }
+    }
+    let _ = ready.send(Ok(()));
+
+    while !owned.is_empty() {
+        match connection.wait_for_event()? {
+            Event::SelectionClear(clear) => owned.retain(|&selection| selection != clear.selection),
+            Event::SelectionRequest(request) => {
+                answer_request(connection, targets_atom, &targets, &request)?
+            }
+            // Including errors of requestors that went away meanwhile
+            _ => {}
+        }
+    }
+    connection.destroy_window(window)?;
+    connection.flush()?;
+    tracing::debug!("X11 selections taken over by another client");
+    Ok(())
+}
+
+/// Text as Latin-1, with '?' for characters outside it
+fn latin1(text: &str) -> Vec<u8> {
+    text.chars()
+        .map(|c| u8::try_from(u32::from(c)).unwrap_or(b'?'))
+        .collect()
+}
+
+/// Write the requested target to the requestor's property and notify it
+///
+/// The data is sent in one piece (no INCR transfer), which covers anything
+/// up to the server's maximum request size: any dictation, and all but
+/// the largest restored clipboard contents.
+fn answer_request(
+    connection: &RustConnection,
+    targets_atom: Atom,
+    targets: &[(Atom, &[u8])],
+    request: &SelectionRequestEvent,
+) -> XResult<()> {
+    // Obsolete clients leave the property unset
+    let property = if request.property == NONE {
+        request.target
+    } else {
+        request.property
+    };
+
+    let answered = if request.target == targets_atom {
+        let atoms: Vec<Atom> = std::iter::once(targets_atom)
+            .chain(targets.iter().map(|&(atom, _)| atom))
+            .collect();
+        connection.change_property32(
+            PropMode::REPLACE,
+            request.requestor,
+            property,
+            AtomEnum::ATOM,
+            &atoms,
+        )?;
+        true
+    } else if let Some(&(_, data)) = targets.iter().find(|&&(atom, _)| atom == request.target) {
+        match connection.change_property8(
+            PropMode::REPLACE,
+            request.requestor,
+            property,
+            request.target,
+            data,
+        ) {
+            Ok(_) => true,
+            Err(ConnectionError::MaximumRequestLengthExceeded) => {
+                tracing::debug!("Selection data too large to send in one piece");
+                false
+            }
+            Err(e) => return Err(e.into()),
+        }
+    } else {
+        false
+    };
+
+    let notify = SelectionNotifyEvent {
+        response_type: SELECTION_NOTIFY_EVENT,
+        sequence: 0,
+        time: request.time,
+        requestor: request.requestor,
+        selection: request.selection,
+        target: request.target,
+        // None tells the requestor the target isn't available
+        property: if answered { property } else { NONE },
+    };
+    connection.send_event(false, request.requestor, EventMask::NO_EVENT, notify)?;
+    connection.flush()?;
+    Ok(())
+}
+
+/// Read `selection` in all of its targets (empty if nobody owns it)
+///
+/// Only 8-bit data (text and MIME types) is kept; targets the owner
+/// refuses or doesn't send in time are left out.
+pub async fn read_selection(selection: PasteSelection) -> Result<Contents, OutputError> {
+    blocking(move || {
+        Display::open()?
+            .read_selection(selection)
+            .map_err(x11_error)
+    })
+    .await
+}
+
+/// Requests a selection's targets into a property of our window
+struct SelectionReader<'a> {
+    display: &'a Display,
+    window: Window,
+    selection: Atom,
+    property: Atom,
+    incr: Atom,
+    deadline: Instant,
+}
+
+impl SelectionReader<'_> {
+    fn read_all(&self) -> XResult<Contents> {
+        let targets: Vec<Atom> = match self.convert(self.display.intern_atom("TARGETS")?)? {
+            Some(property) => property
+                .value32()
+                .map(Iterator::collect)
+                .unwrap_or_default(),
+            None => return Ok(Vec::new()),
+        };
+
+        let mut contents: Contents = Vec::new();
+        for target in targets {
+            if Instant::now() >= self.deadline {
+                tracing::debug!("Selection owner too slow, some targets not read");
+                break;
+            }
+            let Ok(name) = self.display.atom_name(target) else {
+                continue;
+            };
+            if SPECIAL_TARGETS.contains(&name.as_str()) || contents.iter().any(|(n, _)| *n == name)
+            {
+                continue;
+            }
+            match self.convert(target)? {
+                Some(property) if property.format == 8 => contents.push((name, property.value)),
+                _ => tracing::debug!("Selection target {} not read", name),
+            }
+        }
+        Ok(contents)
+    }
+
+    /// Ask the owner for `target` and read the answer
+    fn convert(&self, target: Atom) -> XResult<Option<GetPropertyReply>> {
+        let connection = &self.display.connection;
+        // Property changes left over from the previous target
+        while connection.poll_for_event()?.is_some() {}
+
+        connection.convert_selection(
+            self.window,
+            self.selection,
+            target,
+            self.property,
+            CURRENT_TIME,
+        )?;
+        connection.flush()?;
+        let notify = self.display.wait_event(self.deadline, |event| {
+            matches!(event, Event::SelectionNotify(notify) if notify.requestor == self.window)
+        })?;
+        match notify {
+            Some(Event::SelectionNotify(notify)) if notify.property != NONE => {}
+            // Refused, or no answer in time
+            _ => return Ok(None),
+        }
+
+        match self.display.property(self.window, self.property, true)? {
+            Some(property) if property.type_ == self.incr => self.read_incr(),
+            property => Ok(property),
+        }
+    }
+
+    /// Read an INCR transfer: the owner writes a chunk each time we delete
+    /// the previous one, and ends with an empty chunk
+    fn read_incr(&self) -> XResult<Option<GetPropertyReply>> {
+        let mut transfer: Option<GetPropertyReply> = None;
+        loop {
+            let changed = self.display.wait_event(self.deadline, |event| {
+                matches!(event, Event::PropertyNotify(change)
+                    if change.window == self.window
+                        && change.atom == self.property
+                        && change.state == Property::NEW_VALUE)
+            })?;
+            if changed.is_none() {
+                return Ok(None);
+            }
+            // Stale notifications find the property already deleted
+            let Some(chunk) = self.display.property(self.window, self.property, true)? else {
+                continue;
+            };
+            match transfer {
+                _ if chunk.value.is_empty() => return Ok(transfer.or(Some(chunk))),
+                Some(ref mut transfer) => transfer.value.extend_from_slice(&chunk.value),
+                None => transfer = Some(chunk),
+            }
+        }
+    }
+}
+
+/// An X server for tests, killed when dropped
+#[cfg(test)]
+pub(crate) mod xvfb {
+    use super::Display;
+    use std::io::{BufRead, BufReader};
+    use std::process::{Child, Command, Stdio};
+
+    pub struct Xvfb {
+        child: Child,
+        name: String,
+    }
+
+    impl Xvfb {
+        /// Start Xvfb on a free display, or None if it isn't installed
+        pub fn start() -> Option<Self> {
+            let mut child = Command::new("Xvfb")
+                .args(["-displayfd", "1", "-nolisten", "tcp"])
+                .stdout(Stdio::piped())
+                .stderr(Stdio::null())
+                .spawn()
+                .ok()?;
+
+            // Printed once the server accepts connections
+            let mut number = String::new();
+            BufReader::new(child.stdout.take()?)
+                .read_line(&mut number)
+                .ok()?;
+
+            Some(Self {
+                child,
+                name: format!(":{}", number.trim()),
+            })
+        }
+
+        pub fn connect(&self) -> Display {
+            Display::connect(Some(&self.name)).unwrap()
+        }
+    }
+
+    impl Drop for Xvfb {
+        fn drop(&mut self) {
+            let _ = self.child.kill();
+            let _ = self.child.wait();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::xvfb::Xvfb;
+    use super::*;
+
+    #[test]
+    fn test_latin1() {
+        assert_eq!(latin1("Grüße"), b"Gr\xfc\xdfe");
+        assert_eq!(latin1("日本"), b"??");
+    }
+
+    #[test]
+    fn test_text_contents() {
+        let contents = text_contents("Grüße");
+        let target = |name: &str| {
+            contents
+                .iter()
+                .find(|(n, _)| n == name)
+                .map(|(_, data)| data.as_slice())
+        };
+        assert_eq!(target("UTF8_STRING"), Some("Grüße".as_bytes()));
+        assert_eq!(target("STRING"), Some(&b"Gr\xfc\xdfe"[..]));
+    }
+
+    #[test]
+    fn test_wm_class_name() {
+        assert_eq!(
+            wm_class_name(b"Navigator\0firefox\0").as_deref(),
+            Some("firefox")
+        );
+        assert_eq!(wm_class_name(b"xterm\0").as_deref(), None);
+        assert_eq!(wm_class_name(b"").as_deref(), None);
+    }
+
+    #[test]
+    fn test_selection_roundtrip() {
+        let Some(server) = Xvfb::start() else {
+            eprintln!("Xvfb not found, skipping");
+            return;
+        };
+
+        let reader = server.connect();
+        assert!(reader
+            .read_selection(PasteSelection::Clipboard)
+            .unwrap()
+            .is_empty());
+
+        let contents = vec![
+            ("text/html".to_string(), b"<b>hi</b>".to_vec()),
+            ("UTF8_STRING".to_string(), "hï".as_bytes().to_vec()),
+        ];
+        own_selections(
+            server.connect(),
+            vec![PasteSelection::Clipboard],
+            contents.clone(),
+        )
+        .unwrap();
+        assert_eq!(
+            reader.read_selection(PasteSelection::Clipboard).unwrap(),
+            contents
+        );
+        assert!(reader
+            .read_selection(PasteSelection::Primary)
+            .unwrap()
+            .is_empty());
+    }
+
+    #[test]
+    fn test_active_window_class() {
+        let Some(server) = Xvfb::start() else {
+            eprintln!("Xvfb not found, skipping");
+            return;
+        };
+
+        let display = server.connect();
+        assert_eq!(display.active_window_class().unwrap(), None);
+
+        // What a window manager would set
+        let window = display.create_window(EventMask::NO_EVENT).unwrap();
+        let connection = &display.connection;
+        connection
+            .change_property8(
+                PropMode::REPLACE,
+                window,
+                AtomEnum::WM_CLASS,
+                AtomEnum::STRING,
+                b"xterm\0XTerm\0",
+            )
+            .unwrap();
+        let active = display.intern_atom("_NET_ACTIVE_WINDOW").unwrap();
+        connection
+            .change_property32(
+                PropMode::REPLACE,
+                display.root,
+                active,
+                AtomEnum::WINDOW,
+                &[window],
+            )
+            .unwrap();
+        assert_eq!(
+            display.active_window_class().unwrap().as_deref(),
+            Some("XTerm")
+        );
+    }
+}
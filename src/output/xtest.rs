@@ -0,0 +1,548 @@
+//! XTEST-based text output
+//!
+//! Types text on X11 through the XTEST extension, like xdotool. Characters
+//! of the current keyboard layout are typed with their key (holding Shift
+//! for the shifted level). Anything else (other scripts, emoji, AltGr
+//! characters) is typed by mapping its keysym to a spare keycode for the
+//! moment, which is cleared again afterwards. Clients pick up a changed
+//! mapping asynchronously, so a few spare keycodes are used in turn: a
+//! press is never read with the keysym meant for the next character.
+//!
+//! Requires:
+//! - An X11 session (DISPLAY set) with the XTEST extension
+
+use super::x11::{self, Display, KeySym, XResult, NO_SYMBOL};
+use super::TextOutput;
+use crate::config::OutputBackend;
+use crate::error::OutputError;
+use std::process::Stdio;
+use std::sync::Mutex;
+use std::time::Duration;
+use tokio::process::Command;
+use x11rb::connection::{Connection, RequestConnection};
+use x11rb::protocol::xproto::{ConnectionExt as _, KEY_PRESS_EVENT, KEY_RELEASE_EVENT};
+use x11rb::protocol::xtest::{self, ConnectionExt as _};
+use x11rb::wrapper::ConnectionExt as _;
+use x11rb::{CURRENT_TIME, NONE};
+
+pub const KEYSYM_BACKSPACE: KeySym = 0xff08;
+pub const KEYSYM_TAB: KeySym = 0xff09;
+pub const KEYSYM_RETURN: KeySym = 0xff0d;
+pub const KEYSYM_SHIFT_L: KeySym = 0xffe1;
+
+/// Spare keycodes to use in turn for characters outside the layout
+const SPARE_KEYCODES: usize = 8;
+
+/// Time for clients to read presses before their keycode is remapped again
+const REMAP_DELAY: Duration = Duration::from_millis(20);
+
+/// The X connection used for typing, opened on first use
+static DISPLAY: Mutex<Option<Display>> = Mutex::new(None);
+
+/// Keysym that types a character
+pub fn keysym_for_char(c: char) -> Option<KeySym> {
+    let code = c as KeySym;
+    match c {
+        '\n' => Some(KEYSYM_RETURN),
+        '\t' => Some(KEYSYM_TAB),
+        c if c.is_control() => None,
+        // Latin-1 keysyms equal the code point, the rest are Unicode keysyms
+        _ if code < 0x100 => Some(code),
+        _ => Some(0x0100_0000 | code),
+    }
+}
+
+/// How to type one keysym
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Stroke {
+    /// A key of the layout, with Shift held for the second level
+    Key { keycode: u8, shift: bool },
+    /// Not on the layout: map it to the spare keycode first
+    Remap(KeySym),
+}
+
+/// The server's keyboard mapping
+struct KeyboardMapping {
+    min_keycode: u8,
+    /// Keysyms listed per keycode
+    per_keycode: usize,
+    keysyms: Vec<KeySym>,
+}
+
+impl KeyboardMapping {
+    fn read(display: &Display) -> XResult<Self> {
+        let setup = display.connection.setup();
+        let (min, max) = (setup.min_keycode, setup.max_keycode);
+        let reply = display
+            .connection
+            .get_keyboard_mapping(min, max - min + 1)?
+            .reply()?;
+        Ok(Self {
+            min_keycode: min,
+            per_keycode: reply.keysyms_per_keycode as usize,
+            keysyms: reply.keysyms,
+        })
+    }
+
+    fn keycodes(&self) -> impl Iterator<Item = (u8, &[KeySym])> {
+        self.keysyms
+            .chunks(self.per_keycode.max(1))
+            .enumerate()
+            .map(|(i, keysyms)| (self.min_keycode + i as u8, keysyms))
+    }
+
+    /// The key typing `keysym`, preferring keys that don't need Shift
+    fn find(&self, keysym: KeySym) -> Option<Stroke> {
+        [false, true].into_iter().find_map(|shift| {
+            self.keycodes()
+                .find(|(_, keysyms)| keysyms.get(shift as usize) == Some(&keysym))
+                .map(|(keycode, _)| Stroke::Key { keycode, shift })
+        })
+    }
+
+    fn stroke(&self, keysym: KeySym) -> Stroke {
+        self.find(keysym).unwrap_or(Stroke::Remap(keysym))
+    }
+
+    /// Keycodes without keysyms, for remapping (the highest ones first,
+    /// as xdotool does)
+    fn spare_keycodes(&self) -> Vec<u8> {
+        let spare: Vec<u8> = self
+            .keycodes()
+            .filter(|(_, keysyms)| keysyms.iter().all(|&k| k == NO_SYMBOL))
+            .map(|(keycode, _)| keycode)
+            .collect();
+        spare.into_iter().rev().take(SPARE_KEYCODES).collect()
+    }
+}
+
+/// Presses keys on one display with the mapping read at creation
+struct Typist<'a> {
+    display: &'a mut Display,
+    mapping: KeyboardMapping,
+    /// Keycodes free for remapping
+    spare: Vec<u8>,
+    /// Index into `spare` of the keycode to remap next
+    next_spare: usize,
+    /// Spare keycodes currently mapped, with their keysym
+    remapped: Vec<(u8, KeySym)>,
+}
+
+impl<'a> Typist<'a> {
+    fn new(display: &'a mut Display) -> XResult<Self> {
+        let mapping = KeyboardMapping::read(display)?;
+        Ok(Self {
+            spare: mapping.spare_keycodes(),
+            mapping,
+            display,
+            next_spare: 0,
+            remapped: Vec::new(),
+        })
+    }
+
+    fn key(&self, keycode: u8, press: bool) -> XResult<()> {
+        let kind = if press {
+            KEY_PRESS_EVENT
+        } else {
+            KEY_RELEASE_EVENT
+        };
+        self.display
+            .connection
+            .xtest_fake_input(kind, keycode, CURRENT_TIME, NONE, 0, 0, 0)?;
+        Ok(())
+    }
+
+    /// Point a spare keycode at `keysym` (both levels)
+    fn map_spare(&self, keycode: u8, keysym: KeySym) -> XResult<()> {
+        let connection = &self.display.connection;
+        connection.change_keyboard_mapping(1, keycode, 2, &[keysym, keysym])?;
+        connection.sync()?;
+        Ok(())
+    }
+
+    /// The keycode and Shift state for `keysym`, remapping if needed
+    fn resolve(&mut self, keysym: KeySym) -> Result<(u8, bool), OutputError> {
+        let keysym = match self.mapping.stroke(keysym) {
+            Stroke::Key { keycode, shift } => return Ok((keycode, shift)),
+            Stroke::Remap(keysym) => keysym,
+        };
+        if let Some(&(keycode, _)) = self.remapped.iter().find(|(_, k)| *k == keysym) {
+            return Ok((keycode, false));
+        }
+
+        if self.spare.is_empty() {
+            return Err(OutputError::InjectionFailed(
+                "no spare keycode to type characters outside the layout".to_string(),
+            ));
+        }
+        let keycode = self.spare[self.next_spare % self.spare.len()];
+        self.next_spare += 1;
+
+        // Back at a keycode used before: let clients read its last press first
+        if let Some(index) = self.remapped.iter().position(|(k, _)| *k == keycode) {
+            self.remapped.remove(index);
+            std::thread::sleep(REMAP_DELAY);
+        }
+        self.map_spare(keycode, keysym).map_err(x11::x11_error)?;
+        self.remapped.push((keycode, keysym));
+        Ok((keycode, false))
+    }
+
+    /// Tap the key for `keysym` while holding `modifiers`
+    fn tap(&mut self, modifiers: &[KeySym], keysym: KeySym) -> Result<(), OutputError> {
+        let mut held = Vec::new();
+        for &modifier in modifiers {
+            match self.mapping.find(modifier) {
+                Some(Stroke::Key { keycode, .. }) => held.push(keycode),
+                _ => {
+                    return Err(OutputError::InjectionFailed(format!(
+                        "modifier keysym {:#x} is not on the keyboard",
+                        modifier
+                    )))
+                }
+            }
+        }
+
+        let (keycode, shift) = self.resolve(keysym)?;
+        if shift && !modifiers.contains(&KEYSYM_SHIFT_L) {
+            if let Some(Stroke::Key { keycode, .. }) = self.mapping.find(KEYSYM_SHIFT_L) {
+                held.push(keycode);
+            }
+        }
+
+        self.press(&held, keycode).map_err(x11::x11_error)
+    }
+
+    /// Tap `keycode` with the `held` keycodes pressed around it
+    fn press(&self, held: &[u8], keycode: u8) -> XResult<()> {
+        for &modifier in held {
+            self.key(modifier, true)?;
+        }
+        self.key(keycode, true)?;
+        self.key(keycode, false)?;
+        for &modifier in held.iter().rev() {
+            self.key(modifier, false)?;
+        }
+        self.display.connection.flush()?;
+        Ok(())
+    }
+}
+
+impl Drop for Typist<'_> {
+    fn drop(&mut self) {
+        if self.remapped.is_empty() {
+            return;
+        }
+        // Clients may not have read the last presses yet
+        std::thread::sleep(REMAP_DELAY);
+        for (keycode, _) in std::mem::take(&mut self.remapped) {
+            if let Err(e) = self.map_spare(keycode, NO_SYMBOL) {
+                tracing::debug!("Cannot clear remapped keycode {}: {}", keycode, e);
+            }
+        }
+    }
+}
+
+/// Check that `display` has the XTEST extension
+fn check_xtest(display: Display) -> Result<Display, OutputError> {
+    let xtest = display
+        .connection
+        .extension_information(xtest::X11_EXTENSION_NAME)
+        .map_err(|e| OutputError::X11Unavailable(e.to_string()))?;
+    if xtest.is_none() {
+        return Err(OutputError::X11Unavailable(
+            "X server has no XTEST extension".to_string(),
+        ));
+    }
+    Ok(display)
+}
+
+/// Run `f` with the typing display on a blocking thread, opening it first
+/// if needed
+///
+/// The display is closed when `f` fails, so the next call reconnects.
+async fn with_display<F>(f: F) -> Result<(), OutputError>
+where
+    F: FnOnce(&mut Display) -> Result<(), OutputError> + Send + 'static,
+{
+    tokio::task::spawn_blocking(move || {
+        let mut display = DISPLAY.lock().unwrap_or_else(|e| e.into_inner());
+        if display.is_none() {
+            *display = Some(check_xtest(Display::open()?)?);
+            tracing::debug!("Opened X display for XTEST typing");
+        }
+
+        let result = f(display.as_mut().expect("display opened above"));
+        if result.is_err() {
+            *display = None;
+        }
+        result
+    })
+    .await
+    .map_err(|e| OutputError::InjectionFailed(e.to_string()))?
+}
+
+/// Type `keysyms` with `modifiers` held for each, waiting `delay` between keys
+fn type_keysyms(
+    display: &mut Display,
+    modifiers: &[KeySym],
+    keysyms: &[KeySym],
+    delay: Duration,
+) -> Result<(), OutputError> {
+    let mut typist = Typist::new(display).map_err(x11::x11_error)?;
+    for (i, &keysym) in keysyms.iter().enumerate() {
+        if i > 0 && !delay.is_zero() {
+            std::thread::sleep(delay);
+        }
+        typist.tap(modifiers, keysym)?;
+    }
+    Ok(())
+}
+
+/// Check that the X server can be reached and supports XTEST (blocking)
+pub fn probe() -> Result<(), OutputError> {
+    check_xtest(Display::open()?).map(drop)
+}
+
+/// Whether typing through XTEST works in this session
+pub async fn is_available() -> bool {
+    match with_display(|_| Ok(())).await {
+        Ok(()) => true,
+        Err(e) => {
+            tracing::debug!("{}", e);
+            false
+        }
+    }
+}
+
+/// Press a key combination (e.g. Ctrl+V for paste mode)
+pub async fn send_keystroke(modifiers: &[KeySym], key: KeySym) -> Result<(), OutputError> {
+    let modifiers = modifiers.to_vec();
+    with_display(move |display| type_keysyms(display, &modifiers, &[key], Duration::ZERO)).await
+}
+
+/// Tap BackSpace `count` times
+pub async fn send_backspaces(count: usize, delay: Duration) -> Result<(), OutputError> {
+    with_display(move |display| type_keysyms(display, &[], &vec![KEYSYM_BACKSPACE; count], delay))
+        .await
+}
+
+/// Text output through the XTEST extension
+pub struct XtestOutput {
+    /// Delay between keypresses in milliseconds
+    delay_ms: u32,
+    /// Whether to show a desktop notification
+    notify: bool,
+    /// Whether to send Enter key after output
+    auto_submit: bool,
+}
+
+impl XtestOutput {
+    /// Create a new XTEST output
+    pub fn new(delay_ms: u32, notify: bool, auto_submit: bool) -> Self {
+        Self {
+            delay_ms,
+            notify,
+            auto_submit,
+        }
+    }
+
+    /// Send a desktop notification
+    async fn send_notification(&self, text: &str) {
+        // Truncate preview for notification
+        let preview: String = text.chars().take(100).collect();
+        let preview = if text.chars().count() > 100 {
+            format!("{}...", preview)
+        } else {
+            preview
+        };
+
+        let _ = Command::new("notify-send")
+            .args([
+                "--app-name=Voxtype",
+                "--expire-time=3000",
+                "Transcribed",
+                &preview,
+            ])
+            .stdout(Stdio::null())
+            .stderr(Stdio::null())
+            .status()
+            .await;
+    }
+}
+
+#[async_trait::async_trait]
+impl TextOutput for XtestOutput {
+    async fn output(&self, text: &str) -> Result<(), OutputError> {
+        if text.is_empty() {
+            return Ok(());
+        }
+
+        let mut keysyms = text
+            .chars()
+            .map(|c| {
+                keysym_for_char(c).ok_or_else(|| {
+                    OutputError::InjectionFailed(format!("cannot type control character {:?}", c))
+                })
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+        if self.auto_submit {
+            keysyms.push(KEYSYM_RETURN);
+        }
+
+        let delay = Duration::from_millis(self.delay_ms as u64);
+        with_display(move |display| type_keysyms(display, &[], &keysyms, delay)).await?;
+
+        if self.notify {
+            self.send_notification(text).await;
+        }
+
+        Ok(())
+    }
+
+    async fn erase(&self, count: usize) -> Result<(), OutputError> {
+        send_backspaces(count, Duration::from_millis(self.delay_ms as u64)).await
+    }
+
+    async fn is_available(&self) -> bool {
+        is_available().await
+    }
+
+    fn name(&self) -> &'static str {
+        "xtest"
+    }
+
+    fn backend(&self) -> OutputBackend {
+        OutputBackend::Xtest
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::x11::xvfb::Xvfb;
+    use super::*;
+    use x11rb::protocol::xproto::{CreateWindowAux, EventMask, InputFocus, WindowClass};
+    use x11rb::protocol::Event;
+
+    /// US-like mapping: 'a'/'A' on 38, '1'/'!' on 10, Shift_L on 50,
+    /// keycode 200 free
+    fn mapping() -> KeyboardMapping {
+        let mut keysyms = vec![NO_SYMBOL; (255 - 8 + 1) * 2];
+        let mut set = |keycode: usize, syms: [KeySym; 2]| {
+            keysyms[(keycode - 8) * 2..(keycode - 8) * 2 + 2].copy_from_slice(&syms);
+        };
+        set(38, ['a' as KeySym, 'A' as KeySym]);
+        set(10, ['1' as KeySym, '!' as KeySym]);
+        set(50, [KEYSYM_SHIFT_L, NO_SYMBOL]);
+        set(255, [KEYSYM_RETURN, NO_SYMBOL]);
+        KeyboardMapping {
+            min_keycode: 8,
+            per_keycode: 2,
+            keysyms,
+        }
+    }
+
+    #[test]
+    fn test_keysym_for_char() {
+        assert_eq!(keysym_for_char('a'), Some(0x61));
+        assert_eq!(keysym_for_char('ß'), Some(0xdf));
+        assert_eq!(keysym_for_char('€'), Some(0x10020ac));
+        assert_eq!(keysym_for_char('\n'), Some(KEYSYM_RETURN));
+        assert_eq!(keysym_for_char('\x07'), None);
+    }
+
+    #[test]
+    fn test_strokes() {
+        let mapping = mapping();
+        assert_eq!(
+            mapping.stroke('a' as KeySym),
+            Stroke::Key {
+                keycode: 38,
+                shift: false
+            }
+        );
+        assert_eq!(
+            mapping.stroke('!' as KeySym),
+            Stroke::Key {
+                keycode: 10,
+                shift: true
+            }
+        );
+        assert_eq!(mapping.stroke(0x10020ac), Stroke::Remap(0x10020ac));
+    }
+
+    #[test]
+    fn test_spare_keycodes() {
+        // 255 is taken, so the highest free ones
+        let spare = mapping().spare_keycodes();
+        assert_eq!(spare.len(), SPARE_KEYCODES);
+        assert_eq!(spare[..3], [254, 253, 252]);
+    }
+
+    #[test]
+    fn test_types_into_focused_window() {
+        let Some(server) = Xvfb::start() else {
+            eprintln!("Xvfb not found, skipping");
+            return;
+        };
+
+        // A focused window that reports the keys it receives
+        let observer = server.connect();
+        let connection = &observer.connection;
+        let window = connection.generate_id().unwrap();
+        connection
+            .create_window(
+                x11rb::COPY_DEPTH_FROM_PARENT,
+                window,
+                observer.root,
+                0,
+                0,
+                10,
+                10,
+                0,
+                WindowClass::INPUT_OUTPUT,
+                x11rb::COPY_FROM_PARENT,
+                &CreateWindowAux::new().event_mask(EventMask::KEY_PRESS),
+            )
+            .unwrap();
+        connection.map_window(window).unwrap();
+        connection
+            .set_input_focus(InputFocus::PARENT, window, CURRENT_TIME)
+            .unwrap();
+        connection.sync().unwrap();
+
+        let mut display = check_xtest(server.connect()).unwrap();
+        let spare = KeyboardMapping::read(&display).unwrap().spare_keycodes();
+
+        // Two characters outside the layout in a row, with the mapping
+        // still in place while the keys are looked up
+        let mut typist = Typist::new(&mut display).unwrap();
+        for keysym in [0x61, 0x10020ac, 0x1002713, 0x10020ac] {
+            typist.tap(&[], keysym).unwrap();
+        }
+
+        let mut pressed = Vec::new();
+        while pressed.len() < 4 {
+            if let Event::KeyPress(key) = connection.wait_for_event().unwrap() {
+                pressed.push(key.detail);
+            }
+        }
+        let mapping = KeyboardMapping::read(&observer).unwrap();
+        let keysyms: Vec<KeySym> = pressed
+            .iter()
+            .map(|&keycode| {
+                mapping
+                    .keycodes()
+                    .find(|&(k, _)| k == keycode)
+                    .map_or(NO_SYMBOL, |(_, keysyms)| keysyms[0])
+            })
+            .collect();
+        assert_eq!(keysyms, [0x61, 0x10020ac, 0x1002713, 0x10020ac]);
+        assert_eq!(pressed[1..], [spare[0], spare[1], spare[0]]);
+
+        // The spare keycodes are free again
+        drop(typist);
+        let mapping = KeyboardMapping::read(&display).unwrap();
+        assert_eq!(mapping.spare_keycodes(), spare);
+    }
+}
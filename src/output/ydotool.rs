@@ -9,6 +9,7 @@
 //! - User in 'input' group
 
 use super::TextOutput;
+use crate::config::OutputBackend;
 use crate::error::OutputError;
 use std::process::Stdio;
 use tokio::process::Command;
@@ -84,6 +85,14 @@ impl YdotoolOutput {
     }
 }
 
+/// ydotool key arguments that tap Backspace `count` times
+/// (evdev code 14 is KEY_BACKSPACE)
+pub(crate) fn backspace_args(count: usize) -> Vec<&'static str> {
+    std::iter::repeat_n(["14:1", "14:0"], count)
+        .flatten()
+        .collect()
+}
+
 #[async_trait::async_trait]
 impl TextOutput for YdotoolOutput {
     async fn output(&self, text: &str) -> Result<(), OutputError> {
@@ -158,6 +167,40 @@ impl TextOutput for YdotoolOutput {
         Ok(())
     }
 
+    async fn erase(&self, count: usize) -> Result<(), OutputError> {
+        if count == 0 {
+            return Ok(());
+        }
+
+        let output = Command::new("ydotool")
+            .arg("key")
+            .arg("--key-delay")
+            .arg(self.delay_ms.to_string())
+            .args(backspace_args(count))
+            .stdout(Stdio::null())
+            .stderr(Stdio::piped())
+            .output()
+            .await
+            .map_err(|e| {
+                if e.kind() == std::io::ErrorKind::NotFound {
+                    OutputError::YdotoolNotFound
+                } else {
+                    OutputError::InjectionFailed(e.to_string())
+                }
+            })?;
+
+        if !output.status.success() {
+            let stderr = String::from_utf8_lossy(&output.stderr);
+            if stderr.contains("socket") || stderr.contains("connect") || stderr.contains("daemon")
+            {
+                return Err(OutputError::YdotoolNotRunning);
+            }
+            return Err(OutputError::InjectionFailed(stderr.to_string()));
+        }
+
+        Ok(())
+    }
+
     async fn is_available(&self) -> bool {
         // Check if ydotool exists in PATH
         let which_result = Command::new("which")
@@ -186,6 +229,10 @@ impl TextOutput for YdotoolOutput {
     fn name(&self) -> &'static str {
         "ydotool"
     }
+
+    fn backend(&self) -> OutputBackend {
+        OutputBackend::Ydotool
+    }
 }
 
 #[cfg(test)]
@@ -210,6 +257,11 @@ mod tests {
         assert!(output.auto_submit);
     }
 
+    #[test]
+    fn test_backspace_args() {
+        assert_eq!(backspace_args(2), vec!["14:1", "14:0", "14:1", "14:0"]);
+    }
+
     #[test]
     fn test_detect_key_hold_support() {
         // This test will pass regardless of ydotool version - it just shouldn't panic
@@ -0,0 +1,239 @@
+//! Configuration hot reload
+//!
+//! The daemon re-reads its configuration on SIGHUP (`systemctl --user reload
+//! voxtype`) and whenever the config file changes on disk. This module
+//! watches the file and works out what changed; the daemon decides which
+//! components need to be rebuilt.
+
+use crate::config::Config;
+use crate::error::VoxtypeError;
+use notify::{RecommendedWatcher, RecursiveMode, Watcher};
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+use tokio::sync::mpsc;
+
+/// Editors write a file in several steps (truncate, write, rename);
+/// changes closer together than this trigger a single reload
+const DEBOUNCE: Duration = Duration::from_millis(200);
+
+/// Re-reads the configuration (file, environment and command line overrides)
+pub type ConfigLoader = Box<dyn Fn() -> Result<Config, VoxtypeError> + Send + Sync>;
+
+/// Watches the config file for changes
+pub struct ConfigWatcher {
+    // Kept alive for as long as we want events
+    _watcher: RecommendedWatcher,
+    rx: mpsc::UnboundedReceiver<()>,
+    // When the last change settles (kept here so `changed` is cancel safe)
+    deadline: Option<tokio::time::Instant>,
+}
+
+impl ConfigWatcher {
+    /// Watch the given config file
+    /// The parent directory is watched so the file may be replaced or created later
+    pub fn new(path: &Path) -> notify::Result<Self> {
+        let (tx, rx) = mpsc::unbounded_channel();
+        let file_name = path.file_name().map(|name| name.to_os_string());
+
+        let mut watcher =
+            notify::recommended_watcher(move |res: notify::Result<notify::Event>| match res {
+                Ok(event) => {
+                    let relevant = !event.kind.is_access()
+                        && event
+                            .paths
+                            .iter()
+                            .any(|p| p.file_name().map(|n| n.to_os_string()) == file_name);
+                    if relevant {
+                        let _ = tx.send(());
+                    }
+                }
+                Err(e) => tracing::warn!("Config watch error: {}", e),
+            })?;
+
+        let dir = match path.parent() {
+            Some(parent) if !parent.as_os_str().is_empty() => parent.to_path_buf(),
+            _ => PathBuf::from("."),
+        };
+        watcher.watch(&dir, RecursiveMode::NonRecursive)?;
+
+        Ok(Self {
+            _watcher: watcher,
+            rx,
+            deadline: None,
+        })
+    }
+
+    /// Wait until the file has changed and stopped changing
+    /// Cancel safe: a change seen by a dropped call is reported by the next one
+    pub async fn changed(&mut self) {
+        loop {
+            match self.deadline {
+                None => {
+                    if self.rx.recv().await.is_none() {
+                        // Watcher is gone; never fire again
+                        std::future::pending::<()>().await;
+                    }
+                    self.deadline = Some(tokio::time::Instant::now() + DEBOUNCE);
+                }
+                Some(deadline) => {
+                    tokio::select! {
+                        Some(()) = self.rx.recv() => {
+                            self.deadline = Some(tokio::time::Instant::now() + DEBOUNCE);
+                        }
+                        _ = tokio::time::sleep_until(deadline) => {
+                            self.deadline = None;
+                            return;
+                        }
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// Top-level config sections that differ between two configurations
+pub fn changed_sections(old: &Config, new: &Config) -> Vec<String> {
+    let (old, new) = match (to_table(old), to_table(new)) {
+        (Some(old), Some(new)) => (old, new),
+        // Can't compare: assume everything changed
+        _ => {
+            return [
+                "hotkey", "audio", "whisper", "output", "text", "status", "history",
+            ]
+            .iter()
+            .map(|s| s.to_string())
+            .collect();
+        }
+    };
+
+    let mut keys: Vec<&String> = old.keys().chain(new.keys()).collect();
+    keys.sort();
+    keys.dedup();
+    keys.into_iter()
+        .filter(|key| old.get(*key) != new.get(*key))
+        .cloned()
+        .collect()
+}
+
+fn to_table(config: &Config) -> Option<toml::Table> {
+    match toml::Value::try_from(config) {
+        Ok(toml::Value::Table(table)) => Some(table),
+        Ok(_) => None,
+        Err(e) => {
+            tracing::debug!("Failed to serialize config for comparison: {}", e);
+            None
+        }
+    }
+}
+
+/// Line-by-line diff of two versions of a file
+/// Returns only the changed lines, prefixed with "-" (removed) or "+" (added)
+pub fn line_diff(old: &str, new: &str) -> Vec<String> {
+    let old: Vec<&str> = old.lines().collect();
+    let new: Vec<&str> = new.lines().collect();
+
+    // Longest common subsequence table, filled from the end
+    let mut lcs = vec![vec![0usize; new.len() + 1]; old.len() + 1];
+    for i in (0..old.len()).rev() {
+        for j in (0..new.len()).rev() {
+            lcs[i][j] = if old[i] == new[j] {
+                lcs[i + 1][j + 1] + 1
+            } else {
+                lcs[i + 1][j].max(lcs[i][j + 1])
+            };
+        }
+    }
+
+    let mut diff = Vec::new();
+    let (mut i, mut j) = (0, 0);
+    while i < old.len() || j < new.len() {
+        if i < old.len() && j < new.len() && old[i] == new[j] {
+            i += 1;
+            j += 1;
+        } else if i < old.len() && (j == new.len() || lcs[i + 1][j] >= lcs[i][j + 1]) {
+            diff.push(format!("-{}", old[i]));
+            i += 1;
+        } else {
+            diff.push(format!("+{}", new[j]));
+            j += 1;
+        }
+    }
+    diff
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_no_changes() {
+        let config = Config::default();
+        assert!(changed_sections(&config, &config.clone()).is_empty());
+    }
+
+    #[test]
+    fn test_changed_sections() {
+        let old = Config::default();
+        let mut new = old.clone();
+        new.whisper.model = "small.en".to_string();
+        new.text
+            .replacements
+            .insert("vox type".into(), "voxtype".into());
+        assert_eq!(changed_sections(&old, &new), vec!["text", "whisper"]);
+    }
+
+    #[test]
+    fn test_removed_optional_setting() {
+        let old = Config::default();
+        let mut new = old.clone();
+        new.state_file = None;
+        assert_eq!(changed_sections(&old, &new), vec!["state_file"]);
+    }
+
+    #[test]
+    fn test_line_diff() {
+        let old = "[whisper]\nmodel = \"base.en\"\nlanguage = \"en\"\n";
+        let new = "[whisper]\nmodel = \"small.en\nlanguage = \"en\"\n";
+        assert_eq!(
+            line_diff(old, new),
+            vec!["-model = \"base.en\"", "+model = \"small.en"]
+        );
+        assert!(line_diff(old, old).is_empty());
+    }
+
+    #[test]
+    fn test_line_diff_added_and_removed_lines() {
+        assert_eq!(line_diff("a\nb\nc", "a\nc\nd"), vec!["-b", "+d"]);
+        assert_eq!(line_diff("", "a"), vec!["+a"]);
+    }
+
+    #[tokio::test]
+    async fn test_watcher_reports_changes() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let path = dir.path().join("config.toml");
+        std::fs::write(&path, "[hotkey]\n").unwrap();
+
+        let mut watcher = ConfigWatcher::new(&path).unwrap();
+        std::fs::write(dir.path().join("other.toml"), "").unwrap();
+        std::fs::write(&path, "[hotkey]\nkey = \"F13\"\n").unwrap();
+
+        tokio::time::timeout(Duration::from_secs(5), watcher.changed())
+            .await
+            .expect("config change not reported");
+    }
+
+    #[tokio::test]
+    async fn test_watcher_change_survives_cancellation() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let path = dir.path().join("config.toml");
+        let mut watcher = ConfigWatcher::new(&path).unwrap();
+        std::fs::write(&path, "[hotkey]\n").unwrap();
+
+        // Give up waiting before the change has settled
+        let _ = tokio::time::timeout(Duration::from_millis(50), watcher.changed()).await;
+
+        tokio::time::timeout(Duration::from_secs(5), watcher.changed())
+            .await
+            .expect("config change lost");
+    }
+}
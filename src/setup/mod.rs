@@ -14,7 +14,7 @@ pub mod model;
 pub mod systemd;
 pub mod waybar;
 
-use crate::config::Config;
+use crate::config::{Config, OutputBackend, OutputChainEntry, OutputMode};
 use std::process::Stdio;
 use tokio::process::Command;
 
@@ -50,14 +50,101 @@ pub struct OutputToolStatus {
 #[derive(Debug)]
 pub struct OutputChainStatus {
     pub display_server: DisplayServer,
+    pub virtual_keyboard: OutputToolStatus,
+    pub xtest: OutputToolStatus,
     pub wtype: OutputToolStatus,
+    pub uinput: OutputToolStatus,
     pub ydotool: OutputToolStatus,
     pub ydotool_daemon: bool,
     pub wl_copy: OutputToolStatus,
     pub xclip: OutputToolStatus,
+    pub input_method: OutputToolStatus,
+    /// Backends of `output.chain` with the reason each can't be used
+    pub configured_chain: Vec<(OutputBackend, Option<String>)>,
     pub primary_method: Option<String>,
 }
 
+impl OutputChainStatus {
+    /// Check the backends of a custom `output.chain`, which then decides
+    /// the primary method
+    pub fn with_configured_chain(mut self, chain: Option<&[OutputChainEntry]>) -> Self {
+        let Some(chain) = chain else {
+            return self;
+        };
+
+        self.configured_chain = chain
+            .iter()
+            .map(|entry| (entry.backend(), self.unavailable_reason(entry.backend())))
+            .collect();
+        self.primary_method = self
+            .configured_chain
+            .iter()
+            .find(|(_, reason)| reason.is_none())
+            .map(|(backend, _)| match backend {
+                OutputBackend::VirtualKeyboard => "virtual-keyboard".to_string(),
+                backend => backend.to_string(),
+            });
+        self
+    }
+
+    /// A sink `mode` (file, fifo, command, stdout) is the primary method
+    /// whatever the session offers
+    pub fn with_output_mode(mut self, mode: &OutputMode) -> Self {
+        let sink = match mode {
+            OutputMode::File => OutputBackend::File,
+            OutputMode::Fifo => OutputBackend::Fifo,
+            OutputMode::Command => OutputBackend::Command,
+            OutputMode::Stdout => OutputBackend::Stdout,
+            _ => return self,
+        };
+        self.primary_method = Some(sink.to_string());
+        self
+    }
+
+    /// Why `backend` can't be used in this session, if it can't
+    pub fn unavailable_reason(&self, backend: OutputBackend) -> Option<String> {
+        let wayland = self.display_server == DisplayServer::Wayland;
+        let x11 = self.display_server == DisplayServer::X11;
+        let tool = |tool: &OutputToolStatus| {
+            (!tool.available).then(|| {
+                tool.note
+                    .clone()
+                    .unwrap_or_else(|| "not installed".to_string())
+            })
+        };
+
+        match backend {
+            OutputBackend::VirtualKeyboard | OutputBackend::InputMethod if !wayland => {
+                Some("Wayland only".to_string())
+            }
+            OutputBackend::Xtest if !x11 => Some("X11 only".to_string()),
+            OutputBackend::VirtualKeyboard => tool(&self.virtual_keyboard),
+            OutputBackend::InputMethod => tool(&self.input_method),
+            OutputBackend::Xtest => tool(&self.xtest),
+            OutputBackend::Wtype => tool(&self.wtype),
+            OutputBackend::Uinput => tool(&self.uinput),
+            OutputBackend::Ydotool => tool(&self.ydotool),
+            // The X11 selections are set without any tool
+            OutputBackend::Clipboard if x11 => None,
+            OutputBackend::Clipboard => tool(&self.wl_copy),
+            OutputBackend::Paste => self
+                .unavailable_reason(OutputBackend::Clipboard)
+                .map(|reason| format!("no clipboard: {}", reason))
+                .or_else(|| {
+                    let keystroke = [&self.wtype, &self.xtest, &self.uinput, &self.ydotool]
+                        .iter()
+                        .any(|tool| tool.available);
+                    (!keystroke).then(|| "no wtype, XTEST, uinput or ydotool".to_string())
+                }),
+            // Sinks need no display server or tool
+            OutputBackend::File
+            | OutputBackend::Fifo
+            | OutputBackend::Command
+            | OutputBackend::Stdout => None,
+        }
+    }
+}
+
 /// Check if user is in a specific group
 pub fn user_in_group(group: &str) -> bool {
     std::process::Command::new("groups")
@@ -165,6 +252,48 @@ pub async fn is_ydotool_daemon_running() -> bool {
 pub async fn detect_output_chain() -> OutputChainStatus {
     let display_server = detect_display_server();
 
+    // Check the built-in Wayland virtual keyboard
+    let (virtual_keyboard_installed, virtual_keyboard_available, virtual_keyboard_note) =
+        if display_server == DisplayServer::Wayland {
+            match crate::output::virtual_keyboard::compositor_supports() {
+                Ok(true) => (true, true, None),
+                Ok(false) => (
+                    true,
+                    false,
+                    Some("compositor has no virtual keyboard protocol".to_string()),
+                ),
+                Err(e) => (true, false, Some(format!("cannot connect: {}", e))),
+            }
+        } else {
+            (false, false, None)
+        };
+
+    // Check the built-in X11 XTEST typing
+    let (xtest_installed, xtest_available, xtest_note) = if display_server == DisplayServer::X11 {
+        match crate::output::xtest::probe() {
+            Ok(()) => (true, true, None),
+            Err(e) => (true, false, Some(e.to_string())),
+        }
+    } else {
+        (false, false, None)
+    };
+
+    // Check the built-in Wayland input method
+    let (input_method_installed, input_method_available, input_method_note) =
+        if display_server == DisplayServer::Wayland {
+            match crate::output::input_method::compositor_supports() {
+                Ok(true) => (true, true, None),
+                Ok(false) => (
+                    true,
+                    false,
+                    Some("compositor has no input method protocol".to_string()),
+                ),
+                Err(e) => (true, false, Some(format!("cannot connect: {}", e))),
+            }
+        } else {
+            (false, false, None)
+        };
+
     // Check wtype
     let wtype_path = get_command_path("wtype").await;
     let wtype_installed = wtype_path.is_some();
@@ -175,6 +304,18 @@ pub async fn detect_output_chain() -> OutputChainStatus {
         None
     };
 
+    // Check uinput (built in, needs write access to the device node)
+    let uinput_installed = std::path::Path::new("/dev/uinput").exists();
+    let uinput_available = uinput_installed
+        && std::ffi::CString::new("/dev/uinput")
+            .map(|p| unsafe { libc::access(p.as_ptr(), libc::W_OK) } == 0)
+            .unwrap_or(false);
+    let uinput_note = if uinput_installed && !uinput_available {
+        Some("no write access".to_string())
+    } else {
+        None
+    };
+
     // Check ydotool
     let ydotool_path = get_command_path("ydotool").await;
     let ydotool_installed = ydotool_path.is_some();
@@ -210,12 +351,18 @@ pub async fn detect_output_chain() -> OutputChainStatus {
         None
     };
 
-    // Determine primary method
-    let primary_method = if wtype_available {
+    // Determine primary method (the X11 clipboard is built in)
+    let primary_method = if virtual_keyboard_available {
+        Some("virtual-keyboard".to_string())
+    } else if xtest_available {
+        Some("xtest".to_string())
+    } else if wtype_available {
         Some("wtype".to_string())
+    } else if uinput_available {
+        Some("uinput".to_string())
     } else if ydotool_available {
         Some("ydotool".to_string())
-    } else if wl_copy_available || xclip_available {
+    } else if wl_copy_available || display_server == DisplayServer::X11 {
         Some("clipboard".to_string())
     } else {
         None
@@ -223,6 +370,20 @@ pub async fn detect_output_chain() -> OutputChainStatus {
 
     OutputChainStatus {
         display_server,
+        virtual_keyboard: OutputToolStatus {
+            name: "virtual-kbd",
+            installed: virtual_keyboard_installed,
+            available: virtual_keyboard_available,
+            path: virtual_keyboard_installed.then(|| "built in".to_string()),
+            note: virtual_keyboard_note,
+        },
+        xtest: OutputToolStatus {
+            name: "xtest",
+            installed: xtest_installed,
+            available: xtest_available,
+            path: xtest_installed.then(|| "built in".to_string()),
+            note: xtest_note,
+        },
         wtype: OutputToolStatus {
             name: "wtype",
             installed: wtype_installed,
@@ -230,6 +391,13 @@ pub async fn detect_output_chain() -> OutputChainStatus {
             path: wtype_path,
             note: wtype_note,
         },
+        uinput: OutputToolStatus {
+            name: "uinput",
+            installed: uinput_installed,
+            available: uinput_available,
+            path: uinput_installed.then(|| "/dev/uinput".to_string()),
+            note: uinput_note,
+        },
         ydotool: OutputToolStatus {
             name: "ydotool",
             installed: ydotool_installed,
@@ -252,6 +420,14 @@ pub async fn detect_output_chain() -> OutputChainStatus {
             path: xclip_path,
             note: xclip_note,
         },
+        input_method: OutputToolStatus {
+            name: "input-method",
+            installed: input_method_installed,
+            available: input_method_available,
+            path: input_method_installed.then(|| "built in".to_string()),
+            note: input_method_note,
+        },
+        configured_chain: Vec::new(),
         primary_method,
     }
 }
@@ -274,12 +450,25 @@ pub fn print_output_chain_status(status: &OutputChainStatus) {
     };
     println!("  Display server:  {}", ds_info);
 
+    // Built-in virtual keyboard (only on Wayland)
+    if status.display_server == DisplayServer::Wayland {
+        print_tool_status(&status.virtual_keyboard, true);
+    }
+
+    // Built-in XTEST typing (only on X11)
+    if status.display_server == DisplayServer::X11 {
+        print_tool_status(&status.xtest, true);
+    }
+
     // wtype
     print_tool_status(
         &status.wtype,
         status.display_server == DisplayServer::Wayland,
     );
 
+    // uinput (works everywhere)
+    print_tool_status(&status.uinput, true);
+
     // ydotool
     if status.ydotool.installed {
         let daemon_status = if status.ydotool_daemon {
@@ -315,19 +504,44 @@ pub fn print_output_chain_status(status: &OutputChainStatus) {
         print_tool_status(&status.xclip, status.display_server == DisplayServer::X11);
     }
 
+    // Custom chain from the config
+    if !status.configured_chain.is_empty() {
+        println!("\n  Configured chain (output.chain):");
+        for (i, (backend, reason)) in status.configured_chain.iter().enumerate() {
+            match reason {
+                None => println!("    {}. {} \x1b[32m✓\x1b[0m", i + 1, backend),
+                Some(reason) => println!("    {}. {} \x1b[31m✗\x1b[0m {}", i + 1, backend, reason),
+            }
+        }
+    }
+
     // Summary
     println!();
     if let Some(ref method) = status.primary_method {
         let method_desc = match method.as_str() {
+            "virtual-keyboard" => "Wayland virtual keyboard (CJK supported)",
+            "xtest" => "X11 XTEST (CJK supported)",
+            "input_method" => "Wayland input method (CJK supported)",
+            "paste" => "paste (clipboard + keystroke)",
             "wtype" => "wtype (CJK supported)",
+            "uinput" => "uinput (keyboard layout characters only)",
             "ydotool" => "ydotool (CJK not supported)",
             "clipboard" => "clipboard (requires manual paste)",
+            "file" => "file (appended to [output.file] path)",
+            "fifo" => "fifo (written to [output.fifo] path)",
+            "command" => "command (piped into [output.command])",
+            "stdout" => "stdout (printed by `voxtype record start --wait`)",
             _ => method.as_str(),
         };
-        println!("  \x1b[32m→\x1b[0m Text will be typed via {}", method_desc);
+        let verb = match method.as_str() {
+            "file" | "fifo" | "command" | "stdout" => "sent to",
+            _ => "typed via",
+        };
+        println!("  \x1b[32m→\x1b[0m Text will be {} {}", verb, method_desc);
     } else {
         println!("  \x1b[31m→\x1b[0m No text output method available!");
-        println!("    Install wtype (Wayland) or ydotool (X11) for typing support");
+        println!("    Install wtype (Wayland) or ydotool, enable XTEST (X11), or allow write");
+        println!("    access to /dev/uinput for typing support");
     }
 }
 
@@ -565,9 +779,41 @@ pub async fn run_checks(config: &Config) -> anyhow::Result<()> {
     }
 
     // Check output chain
-    let output_status = detect_output_chain().await;
+    let output_status = detect_output_chain()
+        .await
+        .with_output_mode(&config.output.mode)
+        .with_configured_chain(config.output.chain.as_deref());
     print_output_chain_status(&output_status);
 
+    // Unavailable backends ahead of the first usable one are checked on every output
+    let skipped: Vec<String> = output_status
+        .configured_chain
+        .iter()
+        .take_while(|(_, reason)| reason.is_some())
+        .map(|(backend, _)| backend.to_string())
+        .collect();
+    if !skipped.is_empty() && output_status.primary_method.is_some() {
+        print_warning(&format!(
+            "output.chain starts with unavailable backends: {}",
+            skipped.join(", ")
+        ));
+        println!("       They are skipped on every output; remove them from output.chain");
+    }
+
+    // Restoring the clipboard on Wayland needs the data control protocol
+    if config.output.restore_clipboard && output_status.display_server == DisplayServer::Wayland {
+        match crate::output::data_control::compositor_supports() {
+            Ok(true) => print_success("Clipboard restore available (data control protocol)"),
+            Ok(false) => {
+                print_warning(
+                    "restore_clipboard is set, but the compositor has no data control protocol",
+                );
+                println!("       The clipboard is left holding the pasted text");
+            }
+            Err(e) => print_warning(&format!("Cannot check clipboard restore: {}", e)),
+        }
+    }
+
     if output_status.primary_method.is_none() {
         print_failure("No text output method available");
         if output_status.display_server == DisplayServer::Wayland {
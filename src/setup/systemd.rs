@@ -32,6 +32,7 @@ After=graphical-session.target
 [Service]
 Type=simple
 ExecStart={voxtype_path} daemon
+ExecReload=/bin/kill -HUP $MAINPID
 Restart=on-failure
 RestartSec=5
 
@@ -49,6 +49,16 @@ impl State {
         matches!(self, State::Recording { .. })
     }
 
+    /// Short lowercase name for external integrations ("idle", "recording", ...)
+    pub fn name(&self) -> &'static str {
+        match self {
+            State::Idle => "idle",
+            State::Recording { .. } => "recording",
+            State::Transcribing { .. } => "transcribing",
+            State::Outputting { .. } => "outputting",
+        }
+    }
+
     /// Get recording duration if currently recording
     pub fn recording_duration(&self) -> Option<std::time::Duration> {
         match self {
@@ -124,4 +134,17 @@ mod tests {
         };
         assert!(format!("{}", state).starts_with("Recording"));
     }
+
+    #[test]
+    fn test_state_name() {
+        assert_eq!(State::Idle.name(), "idle");
+        assert_eq!(
+            State::Recording {
+                started_at: Instant::now()
+            }
+            .name(),
+            "recording"
+        );
+        assert_eq!(State::Transcribing { audio: vec![] }.name(), "transcribing");
+    }
 }
@@ -3,6 +3,7 @@
 //! Provides post-transcription text transformations including:
 //! - Spoken punctuation conversion (e.g., "period" → ".")
 //! - Custom word replacements
+//! - Voice commands that undo the last dictation ("scratch that")
 
 use crate::config::TextConfig;
 use regex::Regex;
@@ -14,6 +15,8 @@ pub struct TextProcessor {
     spoken_punctuation: bool,
     /// Custom word replacements (lowercase key → replacement value)
     replacements: HashMap<String, String>,
+    /// Phrases that undo the last dictation (normalized)
+    undo_phrases: Vec<String>,
 }
 
 impl TextProcessor {
@@ -26,12 +29,25 @@ impl TextProcessor {
             .map(|(k, v)| (k.to_lowercase(), v.clone()))
             .collect();
 
+        let undo_phrases = config
+            .undo_phrases
+            .iter()
+            .map(|p| normalize_command(p))
+            .filter(|p| !p.is_empty())
+            .collect();
+
         Self {
             spoken_punctuation: config.spoken_punctuation,
             replacements,
+            undo_phrases,
         }
     }
 
+    /// Check whether a transcription is just an undo phrase ("Scratch that.")
+    pub fn is_undo_command(&self, text: &str) -> bool {
+        !self.undo_phrases.is_empty() && self.undo_phrases.contains(&normalize_command(text))
+    }
+
     /// Process text by applying all enabled transformations
     pub fn process(&self, text: &str) -> String {
         let mut result = text.to_string();
@@ -138,6 +154,17 @@ fn replace_phrase_case_insensitive(text: &str, from: &str, to: &str) -> String {
     }
 }
 
+/// Normalize a spoken command for comparison: lowercase words, no punctuation
+fn normalize_command(text: &str) -> String {
+    text.to_lowercase()
+        .chars()
+        .map(|c| if c.is_alphanumeric() { c } else { ' ' })
+        .collect::<String>()
+        .split_whitespace()
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
 /// Clean up spacing around punctuation marks
 fn clean_punctuation_spacing(text: &str) -> String {
     let mut result = text.to_string();
@@ -187,9 +214,24 @@ mod tests {
                 .iter()
                 .map(|(k, v)| (k.to_string(), v.to_string()))
                 .collect(),
+            undo_phrases: vec![],
         }
     }
 
+    #[test]
+    fn test_undo_command() {
+        let config = TextConfig {
+            undo_phrases: vec!["scratch that".to_string()],
+            ..Default::default()
+        };
+        let processor = TextProcessor::new(&config);
+
+        assert!(processor.is_undo_command(" Scratch that."));
+        assert!(processor.is_undo_command("scratch, that!"));
+        assert!(!processor.is_undo_command("Scratch that idea, start over."));
+        assert!(!TextProcessor::new(&TextConfig::default()).is_undo_command("scratch that"));
+    }
+
     #[test]
     fn test_spoken_punctuation_basic() {
         let config = make_config(true, &[]);
@@ -6,6 +6,7 @@
 //! - Subprocess isolation for GPU memory release
 
 pub mod remote;
+pub mod streaming;
 pub mod subprocess;
 pub mod whisper;
 pub mod worker;
@@ -13,6 +14,17 @@ pub mod worker;
 use crate::config::{WhisperBackend, WhisperConfig};
 use crate::error::TranscribeError;
 
+/// A piece of transcribed text and where it lies in the audio
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Segment {
+    /// Transcribed text
+    pub text: String,
+    /// Start position (sample offset into the transcribed audio)
+    pub start: usize,
+    /// End position (sample offset into the transcribed audio)
+    pub end: usize,
+}
+
 /// Trait for speech-to-text implementations
 pub trait Transcriber: Send + Sync {
     /// Transcribe audio samples to text
@@ -30,6 +42,26 @@ pub trait Transcriber: Send + Sync {
     fn prepare(&self) {
         // Default: no-op
     }
+
+    /// Whether this transcriber is fast enough to re-transcribe audio
+    /// while recording (streaming mode)
+    ///
+    /// Default is false: remote and subprocess backends would pay network
+    /// or process round-trips for every partial result.
+    fn supports_streaming(&self) -> bool {
+        false
+    }
+
+    /// Transcribe audio into timed segments (used by streaming mode)
+    ///
+    /// Default implementation returns a single segment covering all audio.
+    fn transcribe_segments(&self, samples: &[f32]) -> Result<Vec<Segment>, TranscribeError> {
+        Ok(vec![Segment {
+            text: self.transcribe(samples)?,
+            start: 0,
+            end: samples.len(),
+        }])
+    }
 }
 
 /// Factory function to create transcriber based on configured backend
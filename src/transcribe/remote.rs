@@ -263,6 +263,7 @@ mod tests {
             on_demand_loading: false,
             gpu_isolation: false,
             context_window_optimization: true,
+            streaming: false,
             remote_endpoint: Some("http://localhost:8080".to_string()),
             remote_model: None,
             remote_api_key: None,
@@ -297,6 +298,7 @@ mod tests {
             on_demand_loading: false,
             gpu_isolation: false,
             context_window_optimization: true,
+            streaming: false,
             remote_endpoint: None, // Missing!
             remote_model: None,
             remote_api_key: None,
@@ -319,6 +321,7 @@ mod tests {
             on_demand_loading: false,
             gpu_isolation: false,
             context_window_optimization: true,
+            streaming: false,
             remote_endpoint: Some("not-a-url".to_string()),
             remote_model: None,
             remote_api_key: None,
@@ -341,6 +344,7 @@ mod tests {
             on_demand_loading: false,
             gpu_isolation: false,
             context_window_optimization: true,
+            streaming: false,
             remote_endpoint: Some("http://localhost:8080".to_string()),
             remote_model: Some("large-v3".to_string()),
             remote_api_key: None,
@@ -378,6 +382,7 @@ mod tests {
             on_demand_loading: false,
             gpu_isolation: false,
             context_window_optimization: true,
+            streaming: false,
             remote_endpoint: Some("http://localhost:8080".to_string()),
             remote_model: None,
             remote_api_key: None,
@@ -409,6 +414,7 @@ mod tests {
             on_demand_loading: false,
             gpu_isolation: false,
             context_window_optimization: true,
+            streaming: false,
             remote_endpoint: Some("http://localhost:8080".to_string()),
             remote_model: None,
             remote_api_key: None,
@@ -440,6 +446,7 @@ mod tests {
             on_demand_loading: false,
             gpu_isolation: false,
             context_window_optimization: true,
+            streaming: false,
             remote_endpoint: Some("http://localhost:8080".to_string()),
             remote_model: None,
             remote_api_key: Some("sk-test-key-123".to_string()),
@@ -461,6 +468,7 @@ mod tests {
             on_demand_loading: false,
             gpu_isolation: false,
             context_window_optimization: true,
+            streaming: false,
             remote_endpoint: Some("http://localhost:8080".to_string()),
             remote_model: None,
             remote_api_key: None,
@@ -482,6 +490,7 @@ mod tests {
             on_demand_loading: false,
             gpu_isolation: false,
             context_window_optimization: true,
+            streaming: false,
             remote_endpoint: Some("http://localhost:8080".to_string()),
             remote_model: None,
             remote_api_key: None,
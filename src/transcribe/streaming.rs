@@ -0,0 +1,231 @@
+//! Streaming (partial) transcription
+//!
+//! While recording, the live chunk stream is re-transcribed every
+//! [`STEP_SAMPLES`] of new audio so status consumers can show partial
+//! results. Only audio that hasn't been committed yet is decoded: once the
+//! window grows past [`MAX_WINDOW_SAMPLES`], every segment but the last is
+//! committed (its text is final and its audio leaves the window). When
+//! recording stops, only the uncommitted tail still needs transcribing.
+//!
+//! This type only does the bookkeeping; the daemon runs the actual
+//! transcription of each window on a blocking thread.
+
+use super::Segment;
+
+/// New audio needed before the window is transcribed again (1s)
+pub const STEP_SAMPLES: usize = 16000;
+
+/// Window length after which finished segments are committed (15s)
+pub const MAX_WINDOW_SAMPLES: usize = 16000 * 15;
+
+/// A window of audio to transcribe
+#[derive(Debug)]
+pub struct Window {
+    /// Offset of the window in the recording
+    pub offset: usize,
+    /// Audio samples in the window
+    pub samples: Vec<f32>,
+}
+
+/// Incremental transcription state for one recording
+#[derive(Debug, Default)]
+pub struct StreamingTranscription {
+    /// Audio received so far
+    samples: Vec<f32>,
+    /// Offset of the first uncommitted sample
+    committed: usize,
+    /// Final text of the committed audio
+    committed_text: String,
+    /// Recording length when the last window was taken
+    last_window_end: usize,
+    /// Whether a window is currently being transcribed
+    in_flight: bool,
+}
+
+impl StreamingTranscription {
+    /// Start tracking a new recording
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add audio from the live chunk stream
+    pub fn push(&mut self, chunk: &[f32]) {
+        self.samples.extend_from_slice(chunk);
+    }
+
+    /// Take the next window to transcribe
+    /// Returns None while a window is in flight or not enough new audio arrived
+    pub fn next_window(&mut self) -> Option<Window> {
+        if self.in_flight || self.samples.len() < self.last_window_end + STEP_SAMPLES {
+            return None;
+        }
+
+        self.in_flight = true;
+        self.last_window_end = self.samples.len();
+        Some(Window {
+            offset: self.committed,
+            samples: self.samples[self.committed..].to_vec(),
+        })
+    }
+
+    /// Record the segments transcribed from a window
+    /// Returns the partial transcription of everything heard so far
+    pub fn apply(&mut self, window: &Window, segments: &[Segment]) -> String {
+        self.in_flight = false;
+
+        let window_len = window.samples.len();
+        let mut tentative = segments;
+        if window.offset == self.committed && window_len >= MAX_WINDOW_SAMPLES {
+            let commit_count = if window_len >= 2 * MAX_WINDOW_SAMPLES {
+                // No usable segment boundary for a long time: commit everything
+                segments.len()
+            } else {
+                segments.len().saturating_sub(1)
+            };
+
+            let (done, rest) = segments.split_at(commit_count);
+            for segment in done {
+                self.committed_text.push_str(&segment.text);
+            }
+            // Without a segment left (or any speech at all), the whole window is done
+            self.committed += rest.first().map_or(window_len, |next| next.start);
+            tentative = rest;
+        }
+
+        let tentative: String = tentative.iter().map(|s| s.text.as_str()).collect();
+        join_text(&self.committed_text, &tentative)
+    }
+
+    /// Give up on the window in flight (transcription failed)
+    pub fn abandon(&mut self) {
+        self.in_flight = false;
+    }
+
+    /// The part of the final recording that still needs transcribing
+    /// Offsets are valid in `recording` because the chunk stream never drops samples.
+    pub fn tail<'a>(&self, recording: &'a [f32]) -> &'a [f32] {
+        &recording[self.committed.min(recording.len())..]
+    }
+
+    /// Combine the committed text with the transcription of the tail
+    pub fn finish(&self, tail_text: &str) -> String {
+        join_text(&self.committed_text, tail_text)
+    }
+}
+
+/// Join two pieces of transcribed text with a single space
+fn join_text(first: &str, second: &str) -> String {
+    match (first.trim(), second.trim()) {
+        ("", text) | (text, "") => text.to_string(),
+        (first, second) => format!("{} {}", first, second),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn segment(text: &str, start_secs: usize, end_secs: usize) -> Segment {
+        Segment {
+            text: text.to_string(),
+            start: start_secs * 16000,
+            end: end_secs * 16000,
+        }
+    }
+
+    #[test]
+    fn test_window_needs_new_audio() {
+        let mut streaming = StreamingTranscription::new();
+        streaming.push(&vec![0.0; STEP_SAMPLES / 2]);
+        assert!(streaming.next_window().is_none());
+
+        streaming.push(&vec![0.0; STEP_SAMPLES / 2]);
+        let window = streaming.next_window().unwrap();
+        assert_eq!(window.offset, 0);
+        assert_eq!(window.samples.len(), STEP_SAMPLES);
+    }
+
+    #[test]
+    fn test_one_window_in_flight() {
+        let mut streaming = StreamingTranscription::new();
+        streaming.push(&vec![0.0; STEP_SAMPLES]);
+        let window = streaming.next_window().unwrap();
+
+        streaming.push(&vec![0.0; STEP_SAMPLES]);
+        assert!(streaming.next_window().is_none());
+
+        streaming.apply(&window, &[]);
+        assert!(streaming.next_window().is_some());
+    }
+
+    #[test]
+    fn test_short_window_is_tentative() {
+        let mut streaming = StreamingTranscription::new();
+        streaming.push(&vec![0.0; 3 * 16000]);
+        let window = streaming.next_window().unwrap();
+
+        let partial = streaming.apply(&window, &[segment(" Hello", 0, 1), segment(" world", 1, 3)]);
+        assert_eq!(partial, "Hello world");
+
+        // Nothing committed: the whole recording is still the tail
+        let recording = vec![0.0; 4 * 16000];
+        assert_eq!(streaming.tail(&recording).len(), recording.len());
+        assert_eq!(
+            streaming.finish(" Hello world again."),
+            "Hello world again."
+        );
+    }
+
+    #[test]
+    fn test_long_window_commits_all_but_last_segment() {
+        let mut streaming = StreamingTranscription::new();
+        streaming.push(&vec![0.0; 16 * 16000]);
+        let window = streaming.next_window().unwrap();
+
+        let partial = streaming.apply(
+            &window,
+            &[
+                segment(" First sentence.", 0, 6),
+                segment(" Second sentence.", 6, 12),
+                segment(" Third", 12, 16),
+            ],
+        );
+        assert_eq!(partial, "First sentence. Second sentence. Third");
+
+        // Next window starts at the uncommitted segment
+        streaming.push(&vec![0.0; STEP_SAMPLES]);
+        let window = streaming.next_window().unwrap();
+        assert_eq!(window.offset, 12 * 16000);
+
+        let recording = vec![0.0; 20 * 16000];
+        assert_eq!(streaming.tail(&recording).len(), 8 * 16000);
+        assert_eq!(
+            streaming.finish(" Third sentence."),
+            "First sentence. Second sentence. Third sentence."
+        );
+    }
+
+    #[test]
+    fn test_very_long_single_segment_is_committed() {
+        let mut streaming = StreamingTranscription::new();
+        streaming.push(&vec![0.0; 2 * MAX_WINDOW_SAMPLES]);
+        let window = streaming.next_window().unwrap();
+
+        streaming.apply(&window, &[segment(" Run-on", 0, 30)]);
+        let recording = vec![0.0; 2 * MAX_WINDOW_SAMPLES + 16000];
+        assert_eq!(streaming.tail(&recording).len(), 16000);
+        assert_eq!(streaming.finish(" end."), "Run-on end.");
+    }
+
+    #[test]
+    fn test_tail_clamped_to_recording() {
+        let mut streaming = StreamingTranscription::new();
+        streaming.push(&vec![0.0; 16 * 16000]);
+        let window = streaming.next_window().unwrap();
+        streaming.apply(&window, &[segment(" A.", 0, 10), segment(" B", 10, 16)]);
+
+        // Final recording shorter than the chunk stream (dropped chunks)
+        let recording = vec![0.0; 5 * 16000];
+        assert!(streaming.tail(&recording).is_empty());
+    }
+}
@@ -2,7 +2,7 @@
 //!
 //! Uses whisper.cpp via the whisper-rs crate for fast, local transcription.
 
-use super::Transcriber;
+use super::{Segment, Transcriber};
 use crate::config::{Config, WhisperConfig};
 use crate::error::TranscribeError;
 use std::path::PathBuf;
@@ -50,10 +50,10 @@ impl WhisperTranscriber {
             context_window_optimization: config.context_window_optimization,
         })
     }
-}
 
-impl Transcriber for WhisperTranscriber {
-    fn transcribe(&self, samples: &[f32]) -> Result<String, TranscribeError> {
+    /// Run inference and collect the resulting segments
+    /// With `timed`, segment boundaries and timestamps are kept (streaming mode)
+    fn infer(&self, samples: &[f32], timed: bool) -> Result<Vec<Segment>, TranscribeError> {
         if samples.is_empty() {
             return Err(TranscribeError::AudioFormat(
                 "Empty audio buffer".to_string(),
@@ -67,8 +67,6 @@ impl Transcriber for WhisperTranscriber {
             samples.len()
         );
 
-        let start = std::time::Instant::now();
-
         // Create state for this transcription
         let mut state = self
             .ctx
@@ -100,7 +98,8 @@ impl Transcriber for WhisperTranscriber {
         params.set_suppress_nst(true);
 
         // For short recordings, use single segment mode
-        if duration_secs < 30.0 {
+        // (streaming needs segment boundaries to commit finished text)
+        if duration_secs < 30.0 && !timed {
             params.set_single_segment(true);
         }
 
@@ -123,15 +122,32 @@ impl Transcriber for WhisperTranscriber {
             .map_err(|e| TranscribeError::InferenceFailed(e.to_string()))?;
 
         // Collect all segments using iterator API
-        let mut text = String::new();
+        // Timestamps are in centiseconds; convert to sample offsets
+        let mut segments = Vec::new();
         for segment in state.as_iter() {
-            text.push_str(
-                segment
-                    .to_str()
-                    .map_err(|e| TranscribeError::InferenceFailed(e.to_string()))?,
-            );
+            let text = segment
+                .to_str()
+                .map_err(|e| TranscribeError::InferenceFailed(e.to_string()))?;
+            segments.push(Segment {
+                text: text.to_string(),
+                start: (segment.start_timestamp().max(0) as usize * 160).min(samples.len()),
+                end: (segment.end_timestamp().max(0) as usize * 160).min(samples.len()),
+            });
         }
 
+        Ok(segments)
+    }
+}
+
+impl Transcriber for WhisperTranscriber {
+    fn transcribe(&self, samples: &[f32]) -> Result<String, TranscribeError> {
+        let start = std::time::Instant::now();
+
+        let text: String = self
+            .infer(samples, false)?
+            .into_iter()
+            .map(|segment| segment.text)
+            .collect();
         let result = text.trim().to_string();
 
         tracing::info!(
@@ -146,6 +162,14 @@ impl Transcriber for WhisperTranscriber {
 
         Ok(result)
     }
+
+    fn supports_streaming(&self) -> bool {
+        true
+    }
+
+    fn transcribe_segments(&self, samples: &[f32]) -> Result<Vec<Segment>, TranscribeError> {
+        self.infer(samples, true)
+    }
 }
 
 /// Resolve model name to file path